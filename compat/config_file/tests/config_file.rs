@@ -0,0 +1,22 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FILE: AtomicUsize = AtomicUsize::new(0);
+static OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+
+#[quickcheck_async::futures]
+async fn file(v: Vec<u8>) -> bool {
+    FILE.fetch_add(1, Ordering::SeqCst) < 7 && v.len() <= 4
+}
+
+#[quickcheck_async::futures(tests = 3)]
+async fn override_file(v: Vec<u8>) -> bool {
+    OVERRIDE.fetch_add(1, Ordering::SeqCst) < 3 && v.len() <= 4
+}
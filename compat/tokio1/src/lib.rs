@@ -0,0 +1,8 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//! Tests for `quickcheck_async::tokio` against tokio 1.x, which can't share a crate with
+//! the tokio 0.2 tests at the workspace root.
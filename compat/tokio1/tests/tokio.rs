@@ -0,0 +1,81 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::{net::SocketAddr, rc::Rc, time::Duration};
+
+#[quickcheck_async::tokio]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::tokio(flavor = "multi_thread", worker_threads = 2)]
+async fn modern_args() {}
+
+#[quickcheck_async::tokio(core_threads = 1)]
+async fn core_threads_basic() {}
+
+#[quickcheck_async::tokio(core_threads = 3)]
+async fn core_threads() {}
+
+#[quickcheck_async::tokio(threaded_scheduler)]
+async fn threaded_scheduler() {}
+
+#[quickcheck_async::tokio(basic_scheduler)]
+async fn basic_scheduler() {}
+
+fn make_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("fuzzer")
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[quickcheck_async::tokio(builder = "make_runtime")]
+async fn builder() -> bool {
+    std::thread::current().name() == Some("fuzzer")
+}
+
+#[quickcheck_async::tokio(isolate_cases)]
+async fn isolate_cases(x: u8) -> bool {
+    tokio::spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(local)]
+async fn local(x: u8) -> bool {
+    let rc = Rc::new(x);
+    tokio::task::spawn_local(async move { *rc }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(shared)]
+async fn shared(x: u8, handle: tokio::runtime::Handle) -> bool {
+    handle.spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(start_paused = true)]
+async fn start_paused(secs: u16) -> bool {
+    let start = tokio::time::Instant::now();
+    tokio::time::sleep(Duration::from_secs(secs as u64)).await;
+    start.elapsed() >= Duration::from_secs(secs as u64)
+}
+
+#[quickcheck_async::tokio]
+async fn handle(x: u8, handle: tokio::runtime::Handle) -> bool {
+    handle.spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio]
+async fn runtime(x: u8) -> bool {
+    tokio::time::sleep(Duration::from_micros(x as u64)).await;
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (conn, accept) = tokio::join!(tokio::net::TcpStream::connect(addr), listener.accept());
+    conn.is_ok() && accept.is_ok() && tokio::spawn(async move { x }).await.unwrap() == x
+}
@@ -0,0 +1,4239 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//! Procedural macros for [quickcheck_async](https://docs.rs/quickcheck_async).
+//!
+//! This crate should not be used directly; use the re-exports in `quickcheck_async`.
+#![warn(rust_2018_idioms, missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Delimiter, Ident, Span, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use std::{
+    env, fs,
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+};
+use syn::{
+    parse::Parser,
+    parse_macro_input,
+    punctuated::Punctuated,
+    token::Comma,
+    visit_mut::{self, VisitMut},
+    Attribute, AttributeArgs, Data, DeriveInput, Error, Expr, ExprLit, Fields, FnArg,
+    GenericArgument, GenericParam, Item, ItemFn, ItemMod, Lit, LitBool, LitInt, LitStr, Meta,
+    MetaNameValue, NestedMeta, Pat, Path, PathArguments, ReturnType, Type, TypeParamBound, Variant,
+};
+
+struct Arguments {
+    ids: Punctuated<Pat, Comma>,
+    tys: Punctuated<Type, Comma>,
+}
+
+fn parse_args(fn_item: &ItemFn) -> Result<Arguments, TokenStream> {
+    let mut args = Arguments {
+        ids: Punctuated::new(),
+        tys: Punctuated::new(),
+    };
+
+    for pt in fn_item.sig.inputs.iter() {
+        match pt {
+            FnArg::Receiver(_) => {
+                return Err(
+                    Error::new_spanned(fn_item, "test fn cannot take a receiver")
+                        .to_compile_error()
+                        .into(),
+                )
+            }
+
+            FnArg::Typed(pt) => {
+                args.ids.push(*pt.pat.clone());
+                args.tys.push(*pt.ty.clone());
+            }
+        }
+    }
+
+    Ok(args)
+}
+
+fn lit_path(nv: &MetaNameValue) -> Result<Path, TokenStream> {
+    let name = nv
+        .path
+        .get_ident()
+        .map(|id| id.to_string())
+        .unwrap_or_default();
+
+    match &nv.lit {
+        Lit::Str(lit) => lit.parse().map_err(|e| e.to_compile_error().into()),
+        lit => Err(
+            Error::new_spanned(lit, format!("{} must be a string containing a path", name))
+                .to_compile_error()
+                .into(),
+        ),
+    }
+}
+
+fn is_flag(arg: &NestedMeta, name: &str) -> bool {
+    matches!(arg, NestedMeta::Meta(Meta::Path(path)) if path.is_ident(name))
+}
+
+fn output_ty(fn_item: &ItemFn) -> proc_macro2::TokenStream {
+    match &fn_item.sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => quote!(#ty),
+    }
+}
+
+fn is_last_ident(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(ty) => {
+            ty.qself.is_none() && ty.path.segments.last().is_some_and(|s| s.ident == name)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `ty` is one of the types from other crates of the async ecosystem which are
+/// generated as if marked `#[foreign]`, for the features enabled. Only paths naming the
+/// crate, such as `uuid::Uuid`, are recognised, so that types of the same name from
+/// elsewhere are left to their own `Arbitrary` impls.
+fn is_foreign(ty: &Type) -> bool {
+    let mut names = Vec::new();
+    if cfg!(feature = "bytes") {
+        names.extend([("bytes", "Bytes"), ("bytes", "BytesMut")]);
+    }
+    if cfg!(feature = "uuid") {
+        names.push(("uuid", "Uuid"));
+    }
+    if cfg!(feature = "http") {
+        names.extend([("http", "Uri"), ("http", "HeaderMap")]);
+    }
+
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() && ty.path.segments.len() > 1 => &ty.path,
+        _ => return false,
+    };
+    let (first, last) = (
+        &path.segments[0].ident,
+        &path.segments[path.segments.len() - 1].ident,
+    );
+    names
+        .into_iter()
+        .any(|(krate, name)| first == krate && last == name)
+}
+
+/// Whether `ty` is a tokio runtime handle, named by a path ending in `runtime::Handle`,
+/// which the tokio attribute passes to each case rather than generating.
+fn is_tokio_handle(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => {
+            let mut names = ty.path.segments.iter().rev().map(|s| &s.ident);
+            names.next().is_some_and(|id| id == "Handle")
+                && names.next().is_some_and(|id| id == "runtime")
+        }
+        _ => false,
+    }
+}
+
+/// A test fn, along with the items it depends on. These are emitted ahead of it.
+struct TestFn {
+    item: ItemFn,
+    support: proc_macro2::TokenStream,
+}
+
+impl Deref for TestFn {
+    type Target = ItemFn;
+
+    fn deref(&self) -> &ItemFn {
+        &self.item
+    }
+}
+
+impl DerefMut for TestFn {
+    fn deref_mut(&mut self) -> &mut ItemFn {
+        &mut self.item
+    }
+}
+
+impl ToTokens for TestFn {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.support.to_tokens(tokens);
+        self.item.to_tokens(tokens);
+    }
+}
+
+/// Checks that `fn_item` can be a test, returning it as an async fn. A fn returning
+/// `impl Future` is wrapped in one which awaits it, and the body of a `fallible` test is
+/// wrapped so that errors propagate from it with `?`.
+fn check_fn(mut fn_item: ItemFn, qc: &mut QcArgs, attr: &str) -> Result<TestFn, TokenStream> {
+    if let Some(types) = &qc.types {
+        return Err(instantiate(fn_item, types, attr));
+    }
+
+    apply_streams(&mut fn_item)?;
+    if has_impl_args(&fn_item) {
+        let msg = "a test fn with impl Trait arguments needs `types(..)` to instantiate them";
+        return Err(Error::new_spanned(&fn_item.sig, msg)
+            .to_compile_error()
+            .into());
+    }
+
+    // only the tokio attribute passes handles to its cases
+    let handles = attr == "tokio";
+
+    mark_foreign(&mut fn_item);
+    let examples = take_examples(&mut fn_item, qc.arbitrary.is_some(), handles)?;
+    apply_dependents(&mut fn_item)?;
+    apply_fixtures(&mut fn_item)?;
+    name_args(&mut fn_item);
+    apply_filters(&mut fn_item)?;
+    apply_async_arbitrary(&mut fn_item)?;
+    apply_generator(&mut fn_item);
+    apply_borrows(&mut fn_item);
+    if let Some(id) = &qc.arbitrary {
+        from_bytes(&mut fn_item, id, handles)?;
+    }
+    let support = custom_gens(&mut fn_item)?;
+    bundle_args(&mut fn_item, handles);
+    qc.examples = examples;
+    let mut fn_item = async_fn(fn_item)?;
+
+    if qc.fallible.is_some() {
+        let output = match &fn_item.sig.output {
+            ReturnType::Type(_, ty) => quote!(#ty),
+            ReturnType::Default => quote!(()),
+        };
+
+        fn_item.sig.output = syn::parse_quote!(
+            -> ::std::result::Result<
+                #output,
+                ::std::boxed::Box<
+                    dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync
+                >,
+            >
+        );
+
+        WrapReturns.visit_block_mut(&mut fn_item.block);
+        let block = &fn_item.block;
+        fn_item.block = syn::parse_quote!({ ::std::result::Result::Ok(#block) });
+    }
+
+    Ok(TestFn {
+        item: fn_item,
+        support,
+    })
+}
+
+/// Marks each argument of `fn_item` of a type from another crate that quickcheck_async
+/// can generate `#[foreign]`, unless it has a generator of its own.
+fn mark_foreign(fn_item: &mut ItemFn) {
+    for arg in fn_item.sig.inputs.iter_mut() {
+        if let FnArg::Typed(pt) = arg {
+            let generated =
+                (pt.attrs.iter()).any(|a| is_custom_gen(a) && !is_shrinker(a) && !is_resizer(a));
+            if is_foreign(&pt.ty) && !generated {
+                pt.attrs.push(syn::parse_quote!(#[foreign]));
+            }
+        }
+    }
+}
+
+/// Removes `#[quickcheck_async::examples(..)]` from `fn_item`, returning a closure making
+/// the arguments its test fn receives from each example given. An example is a tuple of
+/// every argument that is generated, or just its value if there is only one, so it is
+/// converted the way the passes of [check_fn] will convert each argument. Tokio handles,
+/// if `handles` are injected, aren't given by examples.
+fn take_examples(
+    fn_item: &mut ItemFn,
+    arbitrary: bool,
+    handles: bool,
+) -> Result<Option<proc_macro2::TokenStream>, TokenStream> {
+    let (marks, attrs): (Vec<Attribute>, _) = fn_item.attrs.drain(..).partition(is_examples);
+    fn_item.attrs = attrs;
+
+    let mut examples = Vec::new();
+    for mark in &marks {
+        let parser = Punctuated::<Expr, Comma>::parse_terminated;
+        let given = mark
+            .parse_args_with(parser)
+            .map_err(|e| e.to_compile_error())?;
+        examples.extend(given);
+    }
+
+    if marks.is_empty() {
+        return Ok(None);
+    }
+
+    let name = &fn_item.sig.ident;
+    let (mut ids, mut tys) = (Vec::new(), Vec::new());
+    let mut generated = Vec::new();
+
+    // fixtures are removed before the arguments are numbered
+    let inputs = fn_item.sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(pt) if !pt.attrs.iter().any(|a| a.path.is_ident("fixture")) => Some(pt),
+        _ => None,
+    });
+
+    for (i, pt) in inputs.enumerate() {
+        let ty = &pt.ty;
+        let marked = |name: &str| pt.attrs.iter().any(|a| a.path.is_ident(name));
+
+        if handles && is_tokio_handle(ty) {
+            continue;
+        }
+
+        // arguments made in the body from a generator can't be given
+        if is_generator(ty) || marked("dependent") || marked("async_arbitrary") {
+            generated.push((i, quote!(::quickcheck_async::__private::Fork::default())));
+            continue;
+        }
+
+        if ["strategy", "matches", "map", "flat_map"]
+            .iter()
+            .any(|name| marked(name))
+        {
+            let msg =
+                "examples can't be given for an argument generated by a strategy, regex, map or flat_map";
+            return Err(Error::new_spanned(pt, msg).to_compile_error().into());
+        }
+
+        let id = format_ident!("__example{}", i);
+        let mut value = match &**ty {
+            Type::Reference(r) if r.mutability.is_none() => {
+                quote!(::std::borrow::ToOwned::to_owned(#id))
+            }
+            _ => quote!(#id),
+        };
+
+        if pt.attrs.iter().any(is_custom_gen) {
+            let wrapper = format_ident!("__{}_gen{}", name, i);
+            value = quote!(#wrapper(#value));
+        } else if arbitrary {
+            value = quote!(::quickcheck_async::__private::FromBytes::example(#value));
+        }
+
+        ids.push(id);
+        tys.push(ty.clone());
+        generated.push((i, value));
+    }
+
+    let binding = match (ids.as_slice(), tys.as_slice()) {
+        ([id], [ty]) => quote!(#id: #ty),
+        (ids, tys) => quote!((#(#ids,)*): (#(#tys,)*)),
+    };
+
+    // bundled as bundle_args will bundle the arguments
+    let args = if generated.len() <= MAX_ARGS {
+        let values = generated.iter().map(|(_, value)| value);
+        quote!((#(#values,)*))
+    } else {
+        let (kept, rest): (Vec<_>, Vec<_>) =
+            generated.into_iter().partition(|(i, _)| *i < MAX_ARGS - 1);
+        let kept = kept.into_iter().map(|(_, value)| value);
+        let rest: Vec<_> = rest.into_iter().map(|(_, value)| value).collect();
+        let rest = nest(&rest);
+        quote!((#(#kept,)* #rest))
+    };
+
+    Ok(Some(quote! {
+        || ::std::vec![#({
+            let #binding = #examples;
+            #args
+        }),*]
+    }))
+}
+
+/// Removes each argument marked `#[fixture(path)]` from `fn_item`, starting its body by
+/// binding the result of calling `path` in its place instead. A bare `#[fixture]` uses
+/// the argument type's `Default` impl.
+fn apply_fixtures(fn_item: &mut ItemFn) -> Result<(), TokenStream> {
+    let mut inputs = Punctuated::new();
+    let mut fixtures = Vec::new();
+
+    for mut arg in fn_item.sig.inputs.clone() {
+        if let FnArg::Typed(pt) = &mut arg {
+            let (marks, attrs): (Vec<Attribute>, _) =
+                pt.attrs.drain(..).partition(|a| a.path.is_ident("fixture"));
+            pt.attrs = attrs;
+
+            if let Some(mark) = marks.first() {
+                let init = if mark.tokens.is_empty() {
+                    quote!(::std::default::Default::default())
+                } else {
+                    let path: Path = mark.parse_args().map_err(|e| e.to_compile_error())?;
+                    quote!(#path())
+                };
+                let (pat, ty) = (&pt.pat, &pt.ty);
+                fixtures.push(syn::parse_quote!(let #pat: #ty = #init;));
+                continue;
+            }
+        }
+
+        inputs.push(arg);
+    }
+
+    fn_item.sig.inputs = inputs;
+    fn_item.block.stmts.splice(0..0, fixtures);
+
+    Ok(())
+}
+
+/// Replaces each argument of `fn_item` marked `#[dependent(f)]` with the seed of a
+/// generator, ending the bindings at the start of its body by calling `f` with the
+/// generator it seeds. As every other argument is bound by then, `f` may use them.
+fn apply_dependents(fn_item: &mut ItemFn) -> Result<(), TokenStream> {
+    let mut bindings = Vec::new();
+
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        let (marks, attrs): (Vec<Attribute>, _) = pt
+            .attrs
+            .drain(..)
+            .partition(|a| a.path.is_ident("dependent"));
+        pt.attrs = attrs;
+
+        let f: Expr = match marks.first() {
+            Some(mark) => mark.parse_args().map_err(|e| e.to_compile_error())?,
+            None => continue,
+        };
+
+        let fork = format_ident!("__fork{}", i);
+        let (pat, ty) = (&pt.pat, &pt.ty);
+
+        bindings.push(syn::parse_quote! {
+            let #pat: #ty = ::quickcheck_async::__private::dependent(&#fork, #f);
+        });
+
+        *pt.pat = syn::parse_quote!(#fork);
+        *pt.ty = syn::parse_quote!(::quickcheck_async::__private::Fork);
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+
+    Ok(())
+}
+
+/// Whether `ty` is `&mut Generator`, which is passed the generator for a case rather than
+/// being generated.
+fn is_generator(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(r) => r.mutability.is_some() && is_last_ident(&r.elem, "Generator"),
+        _ => false,
+    }
+}
+
+/// Replaces each `&mut Generator` argument of `fn_item` with the seed of one, starting its
+/// body by binding the generator it seeds in its place.
+fn apply_generator(fn_item: &mut ItemFn) {
+    let mut bindings = Vec::new();
+
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let pt = match arg {
+            FnArg::Typed(pt) if is_generator(&pt.ty) => pt,
+            _ => continue,
+        };
+
+        let (fork, gen) = (format_ident!("__fork{}", i), format_ident!("__gen{}", i));
+        let (pat, ty) = (&pt.pat, &pt.ty);
+
+        bindings.push(syn::parse_quote!(let mut #gen = #fork.generator();));
+        bindings.push(syn::parse_quote!(let #pat: #ty = &mut #gen;));
+
+        *pt.pat = syn::parse_quote!(#fork);
+        *pt.ty = syn::parse_quote!(::quickcheck_async::__private::Fork);
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+}
+
+/// Whether `ty` is the seed of a generator, which is generated as is.
+fn is_fork(ty: &Type) -> bool {
+    is_last_ident(ty, "Fork")
+}
+
+/// Replaces each argument of `fn_item` marked `#[async_arbitrary]` with the seed of a
+/// generator, starting its body by awaiting the value `AsyncArbitrary` makes with it.
+fn apply_async_arbitrary(fn_item: &mut ItemFn) -> Result<(), TokenStream> {
+    let asyncness = fn_item.sig.asyncness.is_some();
+    let mut bindings = Vec::new();
+
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        let (marks, attrs): (Vec<Attribute>, _) = pt
+            .attrs
+            .drain(..)
+            .partition(|a| a.path.is_ident("async_arbitrary"));
+        pt.attrs = attrs;
+
+        let mark = match marks.first() {
+            Some(mark) => mark,
+            None => continue,
+        };
+
+        if !asyncness {
+            let msg = "#[async_arbitrary] arguments are only supported by async test fns";
+            return Err(Error::new_spanned(mark, msg).to_compile_error().into());
+        }
+
+        let fork = format_ident!("__fork{}", i);
+        let (pat, ty) = (&pt.pat, &pt.ty);
+
+        bindings.push(syn::parse_quote! {
+            let #pat: #ty = <#ty as ::quickcheck_async::AsyncArbitrary>::arbitrary(
+                &mut #fork.generator(),
+            )
+            .await;
+        });
+
+        *pt.pat = syn::parse_quote!(#fork);
+        *pt.ty = syn::parse_quote!(::quickcheck_async::__private::Fork);
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+
+    Ok(())
+}
+
+/// Replaces each argument of `fn_item` taken by shared reference with an owned one, which
+/// is generated instead, starting its body by borrowing it in place of the original. This
+/// is a `String` for a `&str`, a `Vec<T>` for a `&[T]`, and a `T` for any other `&T`.
+fn apply_borrows(fn_item: &mut ItemFn) {
+    let mut bindings = Vec::new();
+
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let elem = match arg {
+            // borrowing the runtime is reported by the tokio attribute
+            FnArg::Typed(pt) => match &*pt.ty {
+                Type::Reference(r)
+                    if r.mutability.is_none() && !is_last_ident(&r.elem, "Runtime") =>
+                {
+                    r.elem.clone()
+                }
+                _ => continue,
+            },
+            FnArg::Receiver(_) => continue,
+        };
+
+        let owned: Type = match &*elem {
+            Type::Path(p) if p.qself.is_none() && p.path.is_ident("str") => {
+                syn::parse_quote!(::std::string::String)
+            }
+            Type::Slice(slice) => {
+                let elem = &slice.elem;
+                syn::parse_quote!(::std::vec::Vec<#elem>)
+            }
+            elem => elem.clone(),
+        };
+
+        if let FnArg::Typed(pt) = arg {
+            let id = format_ident!("__owned{}", i);
+            let pat = &pt.pat;
+
+            bindings.push(syn::parse_quote!(let #pat: &#elem = &#id;));
+
+            *pt.pat = syn::parse_quote!(#id);
+            *pt.ty = owned;
+        }
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+}
+
+/// Renames each argument of `fn_item` bound by a pattern other than a plain name, starting
+/// its body by destructuring it. Arguments are passed on by name, which a pattern such as
+/// `_` or `(a, b)` isn't always valid as.
+fn name_args(fn_item: &mut ItemFn) {
+    let mut bindings = Vec::new();
+
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        match &*pt.pat {
+            Pat::Ident(pat)
+                if pat.by_ref.is_none() && pat.mutability.is_none() && pat.subpat.is_none() =>
+            {
+                continue
+            }
+            pat => {
+                let id = format_ident!("__arg{}", i);
+                bindings.push(syn::parse_quote!(let #pat = #id;));
+                *pt.pat = syn::parse_quote!(#id);
+            }
+        }
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+}
+
+/// The most arguments a property can be called with, as for `quickcheck::Testable`.
+const MAX_ARGS: usize = 8;
+
+/// Bundles the arguments of `fn_item` beyond those a property can take into a single
+/// trailing tuple, starting its body by unpacking it. Tokio handles, if `handles` are
+/// injected, are left in place, as they aren't generated.
+fn bundle_args(fn_item: &mut ItemFn, handles: bool) {
+    let generated = fn_item
+        .sig
+        .inputs
+        .iter()
+        .filter(|arg| !matches!(arg, FnArg::Typed(pt) if handles && is_tokio_handle(&pt.ty)))
+        .count();
+
+    if generated <= MAX_ARGS {
+        return;
+    }
+
+    let mut kept = Punctuated::new();
+    let (mut pats, mut tys) = (Vec::new(), Vec::new());
+
+    for (i, arg) in fn_item.sig.inputs.clone().into_iter().enumerate() {
+        match arg {
+            FnArg::Typed(pt) if i >= MAX_ARGS - 1 && !(handles && is_tokio_handle(&pt.ty)) => {
+                pats.push(*pt.pat);
+                tys.push(*pt.ty);
+            }
+            arg => kept.push(arg),
+        }
+    }
+
+    let (pat, ty) = bundle(&pats, &tys);
+    kept.push(syn::parse_quote!(__bundle: #ty));
+
+    fn_item.sig.inputs = kept;
+    fn_item
+        .block
+        .stmts
+        .insert(0, syn::parse_quote!(let #pat = __bundle;));
+}
+
+/// A tuple pattern and type holding `pats`, nested so that no tuple is too wide to be
+/// generated.
+fn bundle(pats: &[Pat], tys: &[Type]) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    (nest(pats), nest(tys))
+}
+
+/// A tuple holding `items`, nested as [bundle] nests them.
+fn nest<T: ToTokens>(items: &[T]) -> proc_macro2::TokenStream {
+    if items.len() <= MAX_ARGS {
+        return quote!((#(#items,)*));
+    }
+
+    let inner = nest(&items[MAX_ARGS - 1..]);
+    let items = &items[..MAX_ARGS - 1];
+
+    quote!((#(#items,)* #inner))
+}
+
+/// Expands a generic test fn into a module of tests, one per type given by `types(..)`,
+/// each marked with `attr` and its other arguments. Each calls the original fn,
+/// instantiated with its type. A fn with several type parameters is given a tuple of
+/// types for each test, in order.
+fn instantiate(mut fn_item: ItemFn, types: &Types, attr: &str) -> TokenStream {
+    desugar_impl_args(&mut fn_item);
+
+    let mut params = Vec::new();
+    for param in &fn_item.sig.generics.params {
+        match param {
+            GenericParam::Type(param) => params.push(param.ident.clone()),
+            param => {
+                let msg = "a test fn with `types` can only have type parameters";
+                return Error::new_spanned(param, msg).to_compile_error().into();
+            }
+        }
+    }
+
+    if params.is_empty() {
+        let msg = "a test fn with `types` must have a type parameter";
+        return Error::new_spanned(&fn_item.sig, msg)
+            .to_compile_error()
+            .into();
+    }
+
+    let (attr, attr_args) = (format_ident!("{}", attr), &types.args);
+    let name = &fn_item.sig.ident;
+    let vis = &fn_item.vis;
+
+    // the test's own attributes (and those of its args) apply to each instantiation
+    let mut inner = fn_item.clone();
+    inner.attrs.clear();
+    for arg in inner.sig.inputs.iter_mut() {
+        if let FnArg::Typed(pt) = arg {
+            pt.attrs.clear();
+        }
+    }
+
+    let mut tests = Vec::new();
+
+    for ty in &types.tys {
+        let tys: Vec<Type> = match ty {
+            _ if params.len() == 1 => vec![ty.clone()],
+            Type::Tuple(tuple) if tuple.elems.len() == params.len() => {
+                tuple.elems.iter().cloned().collect()
+            }
+            ty => {
+                let msg = format!("expected a tuple of {} types", params.len());
+                return Error::new_spanned(ty, msg).to_compile_error().into();
+            }
+        };
+
+        let mut outer = fn_item.clone();
+        outer.sig.generics = Default::default();
+        outer.sig.asyncness = Some(Default::default());
+        outer.sig.ident = type_ident(ty);
+
+        let mut args = Vec::new();
+        for (i, arg) in outer.sig.inputs.iter_mut().enumerate() {
+            if let FnArg::Typed(pt) = arg {
+                let id = format_ident!("__arg{}", i);
+                *pt.pat = syn::parse_quote!(#id);
+                args.push(id);
+            }
+        }
+
+        if let ReturnType::Type(_, out) = &mut outer.sig.output {
+            // a fn returning impl Future is awaited, so the test returns its output
+            if let Some(output) = future_output(out) {
+                **out = output;
+            }
+        }
+
+        let mut subst = Subst {
+            params: &params,
+            tys: &tys,
+        };
+        subst.visit_signature_mut(&mut outer.sig);
+
+        outer.block = syn::parse_quote!({
+            #inner
+            #name::<#(#tys),*>(#(#args),*).await
+        });
+
+        tests.push(quote! {
+            #[::quickcheck_async::#attr(#(#attr_args),*)]
+            #outer
+        });
+    }
+
+    quote! {
+        #vis mod #name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #(#tests)*
+        }
+    }
+    .into()
+}
+
+/// Whether any argument of `fn_item` is of an `impl Trait` type.
+fn has_impl_args(fn_item: &ItemFn) -> bool {
+    fn_item
+        .sig
+        .inputs
+        .iter()
+        .any(|arg| matches!(arg, FnArg::Typed(pt) if matches!(*pt.ty, Type::ImplTrait(_))))
+}
+
+/// Replaces each `impl Trait` argument type of `fn_item` with a new type parameter bound
+/// by the same traits, so that it can be instantiated explicitly.
+fn desugar_impl_args(fn_item: &mut ItemFn) {
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        if let Type::ImplTrait(ty) = &*pt.ty {
+            let (param, bounds) = (format_ident!("__Impl{}", i), &ty.bounds);
+            fn_item
+                .sig
+                .generics
+                .params
+                .push(syn::parse_quote!(#param: #bounds));
+            *pt.ty = syn::parse_quote!(#param);
+        }
+    }
+}
+
+/// Replaces type parameters with the types they are instantiated with.
+struct Subst<'a> {
+    params: &'a [Ident],
+    tys: &'a [Type],
+}
+
+impl VisitMut for Subst<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(path) = ty {
+            if let (None, Some(id)) = (&path.qself, path.path.get_ident()) {
+                if let Some(i) = self.params.iter().position(|param| param == id) {
+                    *ty = self.tys[i].clone();
+                    return;
+                }
+            }
+        }
+
+        visit_mut::visit_type_mut(self, ty)
+    }
+}
+
+/// A name for the test of an instantiation with `ty`, such as `vec_u32` for `Vec<u32>`.
+fn type_ident(ty: &Type) -> Ident {
+    let name = quote!(#ty).to_string().to_lowercase();
+    let words: Vec<_> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    format_ident!("{}", words.join("_"))
+}
+
+/// Replaces the type of each generated argument of `fn_item` with one built by the
+/// `arbitrary` crate from generated bytes, starting its body by unwrapping it. Arguments
+/// with a generator of their own, and tokio handles if `handles` are injected, are left
+/// alone.
+fn from_bytes(fn_item: &mut ItemFn, id: &Ident, handles: bool) -> Result<(), TokenStream> {
+    if cfg!(not(feature = "arbitrary")) {
+        return Err(
+            Error::new_spanned(id, "arbitrary requires the `arbitrary` feature")
+                .to_compile_error()
+                .into(),
+        );
+    }
+
+    let mut bindings = Vec::new();
+
+    for arg in fn_item.sig.inputs.iter_mut() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        let handle = handles && is_tokio_handle(&pt.ty);
+        if pt.attrs.iter().any(is_custom_gen) || is_fork(&pt.ty) || handle {
+            continue;
+        }
+
+        // arguments are named by now
+        let (pat, ty) = (&pt.pat, &pt.ty);
+        bindings.push(syn::parse_quote!(let #pat: #ty = #pat.into_inner();));
+        *pt.ty = syn::parse_quote!(::quickcheck_async::__private::FromBytes<#ty>);
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+
+    Ok(())
+}
+
+/// Whether `attr` is `#[quickcheck_async::filter]`, which test attributes apply.
+fn is_filter(attr: &Attribute) -> bool {
+    is_applied(attr, "filter")
+}
+
+/// Whether `attr` is `#[quickcheck_async::examples]`, which test attributes apply.
+fn is_examples(attr: &Attribute) -> bool {
+    is_applied(attr, "examples")
+}
+
+/// Whether `attr` is `#[quickcheck_async::name]`, or just `#[name]`.
+fn is_applied(attr: &Attribute, name: &str) -> bool {
+    let segments = &attr.path.segments;
+    let first = segments.first().map(|s| s.ident.to_string());
+    let last = segments.last().map(|s| s.ident.to_string());
+
+    last.as_deref() == Some(name)
+        && (segments.len() == 1 || first.as_deref() == Some("quickcheck_async"))
+}
+
+/// Removes each `#[quickcheck_async::filter(f)]` from `fn_item`, starting its body with a
+/// check which discards the case unless `f` holds for references to its arguments.
+fn apply_filters(fn_item: &mut ItemFn) -> Result<(), TokenStream> {
+    let (filters, attrs): (Vec<Attribute>, _) = fn_item.attrs.drain(..).partition(is_filter);
+    fn_item.attrs = attrs;
+
+    if filters.is_empty() {
+        return Ok(());
+    }
+
+    let mut args = Vec::new();
+
+    // every argument is named by now, though it may be destructured in the body
+    for arg in &fn_item.sig.inputs {
+        match arg {
+            FnArg::Typed(pt) if is_generator(&pt.ty) || is_fork(&pt.ty) => {}
+            FnArg::Typed(pt) => {
+                let pat = &pt.pat;
+                args.push(quote!(#pat));
+            }
+            FnArg::Receiver(_) => {}
+        }
+    }
+
+    for filter in filters.iter().rev() {
+        let filter: Expr = filter.parse_args().map_err(|e| e.to_compile_error())?;
+
+        fn_item.block.stmts.insert(
+            0,
+            syn::parse_quote! {
+                if !(#filter)(#(&#args),*) {
+                    ::quickcheck_async::__private::filtered();
+                }
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Expands the test fn `item` with `expand`, moving its `#[should_panic]` and `#[ignore]`
+/// attributes onto the test generated for it, as the test harness only reads them there.
+fn hoist_test_attrs(
+    item: TokenStream,
+    expand: impl FnOnce(TokenStream) -> TokenStream,
+) -> TokenStream {
+    let mut fn_item = match syn::parse::<ItemFn>(item.clone()) {
+        Ok(fn_item) => fn_item,
+        Err(_) => return expand(item),
+    };
+    let (hoisted, attrs): (Vec<Attribute>, _) = fn_item.attrs.drain(..).partition(is_test_attr);
+    fn_item.attrs = attrs;
+
+    let expanded = expand(quote!(#fn_item).into());
+    match syn::parse::<ItemFn>(expanded.clone()) {
+        Ok(mut test) => {
+            test.attrs.extend(hoisted);
+            quote!(#test).into()
+        }
+        // errors are reported as they are
+        Err(_) => expanded,
+    }
+}
+
+/// Whether `attr` is read by the test harness from the test itself.
+fn is_test_attr(attr: &Attribute) -> bool {
+    attr.path.is_ident("should_panic") || attr.path.is_ident("ignore")
+}
+
+/// Whether `attr` gives an argument a generator of its own.
+fn is_custom_gen(attr: &Attribute) -> bool {
+    [
+        "gen",
+        "range",
+        "strategy",
+        "weights",
+        "matches",
+        "len",
+        "dictionary",
+        "alphabet",
+        "floats",
+        "time",
+        "net",
+        "schema",
+        "message",
+        "foreign",
+        "map",
+        "flat_map",
+        "shrink",
+        "shrink_toward",
+        "no_shrink",
+        "size",
+    ]
+    .iter()
+    .any(|name| attr.path.is_ident(name))
+}
+
+/// Whether `attr` is `#[shrink(path)]`, `#[shrink_toward(target)]` or `#[no_shrink]`,
+/// which change how an argument shrinks rather than how it is generated.
+fn is_shrinker(attr: &Attribute) -> bool {
+    ["shrink", "shrink_toward", "no_shrink"]
+        .iter()
+        .any(|name| attr.path.is_ident(name))
+}
+
+/// Whether `attr` is `#[size(n)]`, which changes the size an argument is generated with
+/// rather than how.
+fn is_resizer(attr: &Attribute) -> bool {
+    attr.path.is_ident("size")
+}
+
+/// The path of the enum `ty`, and each variant given in
+/// `#[weights(A(u8) = 1, B { x: u32 } = 2)]` with its weight.
+fn weighted_variants(
+    gen: &Attribute,
+    ty: &Type,
+) -> Result<(Path, Vec<(Variant, LitInt)>), TokenStream> {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() => p.path.clone(),
+        _ => {
+            return Err(
+                Error::new_spanned(ty, "weights can only be given for an enum")
+                    .to_compile_error()
+                    .into(),
+            )
+        }
+    };
+
+    let given = gen
+        .parse_args_with(Punctuated::<Variant, Comma>::parse_terminated)
+        .map_err(|e| e.to_compile_error())?;
+
+    let mut variants: Vec<(Variant, LitInt)> = Vec::new();
+    for mut v in given {
+        let weight = match v.discriminant.take() {
+            Some((
+                _,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(weight),
+                    ..
+                }),
+            )) => weight,
+            Some((_, expr)) => {
+                return Err(Error::new_spanned(expr, "expected an integer weight")
+                    .to_compile_error()
+                    .into())
+            }
+            None => {
+                let msg = "expected a weight, as in `A = 1`";
+                return Err(Error::new_spanned(v, msg).to_compile_error().into());
+            }
+        };
+        if variants.iter().any(|(u, _)| u.ident == v.ident) {
+            return Err(Error::new_spanned(&v.ident, "variant given more than once")
+                .to_compile_error()
+                .into());
+        }
+        if weight
+            .base10_parse::<u32>()
+            .map_err(|e| e.to_compile_error())?
+            == 0
+        {
+            let msg = "a variant of weight zero is never generated; leave it out instead";
+            return Err(Error::new_spanned(weight, msg).to_compile_error().into());
+        }
+        variants.push((v, weight));
+    }
+
+    if variants.is_empty() {
+        return Err(
+            Error::new_spanned(gen, "expected at least one weighted variant")
+                .to_compile_error()
+                .into(),
+        );
+    }
+
+    Ok((path, variants))
+}
+
+/// Replaces the type of each argument with a generator of its own, such as `#[gen(path)]`
+/// or `#[range(a..b)]`, or a shrinker marked `#[shrink(path)]`, with one generated and
+/// shrunk that way, returning the definitions of those types. The original argument is
+/// unwrapped from it, so the body of the test fn is unchanged.
+fn custom_gens(fn_item: &mut ItemFn) -> Result<proc_macro2::TokenStream, TokenStream> {
+    let name = fn_item.sig.ident.clone();
+    let mut support = proc_macro2::TokenStream::new();
+    let mut bindings = Vec::new();
+
+    for (i, arg) in fn_item.sig.inputs.iter_mut().enumerate() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        let (gens, attrs): (Vec<Attribute>, _) = pt.attrs.drain(..).partition(is_custom_gen);
+        pt.attrs = attrs;
+        let (shrinkers, gens): (Vec<Attribute>, Vec<Attribute>) =
+            gens.into_iter().partition(is_shrinker);
+        let (sizes, gens): (Vec<Attribute>, Vec<Attribute>) =
+            gens.into_iter().partition(is_resizer);
+
+        if let [_, extra, ..] = sizes.as_slice() {
+            return Err(
+                Error::new_spanned(extra, "an argument can only have one size")
+                    .to_compile_error()
+                    .into(),
+            );
+        }
+
+        // an argument with only a shrinker or size is generated as usual
+        let gen = match (gens.as_slice(), shrinkers.as_slice()) {
+            (_, [_, extra, ..]) => {
+                return Err(
+                    Error::new_spanned(extra, "an argument can only have one shrinker")
+                        .to_compile_error()
+                        .into(),
+                )
+            }
+            ([_, extra, ..], _) => {
+                return Err(
+                    Error::new_spanned(extra, "an argument can only have one generator")
+                        .to_compile_error()
+                        .into(),
+                )
+            }
+            ([], []) => match sizes.first() {
+                Some(size) => size,
+                None => continue,
+            },
+            ([], [shrinker]) => shrinker,
+            ([gen], _) => gen,
+        };
+
+        let wrapper = format_ident!("__{}_gen{}", name, i);
+        let (pat, ty) = (&pt.pat, &pt.ty);
+        let mut held = quote!(#ty);
+
+        let (mut arbitrary, mut shrink) = if is_shrinker(gen) {
+            let arbitrary = quote! {
+                <#ty as ::quickcheck_async::__private::quickcheck::Arbitrary>::arbitrary(g)
+            };
+            (arbitrary, quote!())
+        } else if is_resizer(gen) {
+            let arbitrary = quote! {
+                <#ty as ::quickcheck_async::__private::quickcheck::Arbitrary>::arbitrary(g)
+            };
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(&self.0)
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("gen") {
+            let path: Path = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            (quote!(#path(g)), quote!())
+        } else if gen.path.is_ident("strategy") {
+            if cfg!(not(feature = "proptest")) {
+                return Err(
+                    Error::new_spanned(gen, "strategy requires the `proptest` feature")
+                        .to_compile_error()
+                        .into(),
+                );
+            }
+
+            let strategy: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            held = quote!(::quickcheck_async::__private::Shrinking<#ty>);
+            let arbitrary = quote! {
+                ::quickcheck_async::__private::from_strategy(&(#strategy), g)
+            };
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::shrink_strategy(#strategy, &self.0)
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("matches") {
+            if cfg!(not(feature = "regex")) {
+                return Err(
+                    Error::new_spanned(gen, "matches requires the `regex` feature")
+                        .to_compile_error()
+                        .into(),
+                );
+            }
+
+            let pattern: LitStr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            held = quote!(::quickcheck_async::__private::Matching);
+            let arbitrary = quote! {
+                ::quickcheck_async::__private::from_regex(#pattern, g)
+            };
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::shrink_regex(#pattern, &self.0)
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("alphabet") {
+            let alphabet: Ident = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            let variant = match alphabet.to_string().as_str() {
+                "ascii" => quote!(Ascii),
+                "alphanumeric" => quote!(Alphanumeric),
+                "multibyte" => quote!(Multibyte),
+                "lossy" => quote!(Lossy),
+                _ => {
+                    let msg = "alphabet must be one of ascii, alphanumeric, multibyte or lossy";
+                    return Err(Error::new_spanned(alphabet, msg).to_compile_error().into());
+                }
+            };
+            let alphabet = quote!(::quickcheck_async::__private::Alphabet::#variant);
+            let arbitrary = quote!(#alphabet.string(g));
+            // shrinks as a string would, skipping those with chars outside the alphabet
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(&self.0)
+                            .filter(|s: &::std::string::String| #alphabet.contains(s))
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("floats") {
+            let specials = gen
+                .parse_args_with(Punctuated::<Ident, Comma>::parse_terminated)
+                .map_err(|e| e.to_compile_error())?;
+            let (mut nan, mut infinite, mut subnormal) = (false, false, false);
+            for special in specials {
+                match special.to_string().as_str() {
+                    "nan" => nan = true,
+                    "infinite" => infinite = true,
+                    "subnormal" => subnormal = true,
+                    _ => {
+                        let msg = "floats takes any of nan, infinite and subnormal";
+                        return Err(Error::new_spanned(special, msg).to_compile_error().into());
+                    }
+                }
+            }
+            let floats = quote! {
+                ::quickcheck_async::__private::Floats {
+                    nan: #nan,
+                    infinite: #infinite,
+                    subnormal: #subnormal,
+                }
+            };
+            let arbitrary = quote!(#floats.float(g));
+            // shrinks as the type would, skipping special values that weren't allowed
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(&self.0)
+                            .filter(|&x| #floats.allows(x))
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("time") {
+            if !gen.tokens.is_empty() {
+                let msg = "time takes no arguments";
+                return Err(Error::new_spanned(&gen.tokens, msg)
+                    .to_compile_error()
+                    .into());
+            }
+            let arbitrary = quote!(::quickcheck_async::__private::time(g));
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::shrink_time(&self.0).map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("net") {
+            if !gen.tokens.is_empty() {
+                let msg = "net takes no arguments";
+                return Err(Error::new_spanned(&gen.tokens, msg)
+                    .to_compile_error()
+                    .into());
+            }
+            let arbitrary = quote!(<#ty as ::quickcheck_async::__private::Net>::net(g));
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::Net::shrink_net(&self.0).map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("schema") {
+            if cfg!(not(feature = "json-schema")) {
+                return Err(
+                    Error::new_spanned(gen, "schema requires the `json-schema` feature")
+                        .to_compile_error()
+                        .into(),
+                );
+            }
+
+            let schema: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            let arbitrary = quote!(::quickcheck_async::__private::from_schema(#schema, g));
+            // shrinks to simpler values which still conform
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::shrink_schema(#schema, &self.0)
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("message") {
+            if cfg!(not(feature = "prost")) {
+                return Err(
+                    Error::new_spanned(gen, "message requires the `prost` feature")
+                        .to_compile_error()
+                        .into(),
+                );
+            }
+            if !gen.tokens.is_empty() {
+                let msg = "message takes no arguments";
+                return Err(Error::new_spanned(&gen.tokens, msg)
+                    .to_compile_error()
+                    .into());
+            }
+
+            let arbitrary = quote!(::quickcheck_async::__private::from_message(g));
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::shrink_message(&self.0).map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("foreign") {
+            if !gen.tokens.is_empty() {
+                let msg = "foreign takes no arguments";
+                return Err(Error::new_spanned(&gen.tokens, msg)
+                    .to_compile_error()
+                    .into());
+            }
+            let arbitrary = quote!(<#ty as ::quickcheck_async::__private::Foreign>::arbitrary(g));
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::Foreign::shrink(&self.0).map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("map") {
+            let f: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            held = quote!(::quickcheck_async::__private::Mapped<#ty>);
+            let arbitrary = quote!(::quickcheck_async::__private::mapped(g, #f));
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(self.0.shrink().map(#wrapper))
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("flat_map") {
+            let f: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            held = quote!(::quickcheck_async::__private::Mapped<#ty>);
+            let arbitrary = quote!(::quickcheck_async::__private::flat_mapped(g, #f));
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(self.0.shrink().map(#wrapper))
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("dictionary") {
+            let entries: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            let arbitrary = quote! {
+                <#ty as ::quickcheck_async::__private::Dictionary>::with_dictionary(
+                    g,
+                    &#entries,
+                )
+            };
+            // shrinks as the type would, which may take entries apart
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(&self.0)
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("len") {
+            let range: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            let arbitrary = quote! {{
+                let len = <usize as ::quickcheck_async::__private::InRange>::in_range(g, #range);
+                <#ty as ::quickcheck_async::__private::WithLen>::with_len(g, len)
+            }};
+            // shrinks as the type would, skipping values of a length out of the range
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    let range = #range;
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(&self.0)
+                            .filter(move |x| {
+                                let len = ::quickcheck_async::__private::WithLen::length(x);
+                                ::std::ops::RangeBounds::contains(&range, &len)
+                            })
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        } else if gen.path.is_ident("weights") {
+            let (path, variants) = weighted_variants(gen, ty)?;
+            let weights = variants.iter().map(|(_, weight)| weight);
+            let arms = variants.iter().enumerate().map(|(i, (v, _))| {
+                let id = &v.ident;
+                let value = construct(quote!(#path::#id), &v.fields);
+                quote!(#i => #value,)
+            });
+            let arbitrary = quote! {
+                match ::quickcheck_async::__private::weighted(g, &[#(#weights),*]) {
+                    #(#arms)*
+                    _ => ::std::unreachable!(),
+                }
+            };
+            // shrinks the fields of the variant together, so it stays the same variant
+            let arms = variants.iter().map(|(v, _)| {
+                let id = &v.ident;
+                let ids: Vec<_> = v
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| f.ident.clone().unwrap_or_else(|| format_ident!("__f{}", i)))
+                    .collect();
+                let value = match &v.fields {
+                    Fields::Named(_) => quote!(#path::#id { #(#ids),* }),
+                    Fields::Unnamed(_) => quote!(#path::#id(#(#ids),*)),
+                    Fields::Unit => quote!(#path::#id),
+                };
+                quote! {
+                    #value => ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(
+                            &(#(::std::clone::Clone::clone(#ids),)*),
+                        )
+                        .map(|(#(#ids,)*)| #wrapper(#value)),
+                    ),
+                }
+            });
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    #[allow(unreachable_patterns)]
+                    match &self.0 {
+                        #(#arms)*
+                        _ => ::std::boxed::Box::new(::std::iter::empty()),
+                    }
+                }
+            };
+            (arbitrary, shrink)
+        } else {
+            let range: Expr = gen.parse_args().map_err(|e| e.to_compile_error())?;
+            let arbitrary = quote! {
+                <#ty as ::quickcheck_async::__private::InRange>::in_range(g, #range)
+            };
+            // shrinks as the type would, skipping values out of the range
+            let shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    let range = #range;
+                    ::std::boxed::Box::new(
+                        ::quickcheck_async::__private::quickcheck::Arbitrary::shrink(&self.0)
+                            .filter(move |x| ::std::ops::RangeBounds::contains(&range, x))
+                            .map(#wrapper),
+                    )
+                }
+            };
+            (arbitrary, shrink)
+        };
+
+        let unwrapped = ["strategy", "matches", "map", "flat_map"]
+            .iter()
+            .any(|name| gen.path.is_ident(name));
+
+        if let Some(size) = sizes.first() {
+            let size: Expr = size.parse_args().map_err(|e| e.to_compile_error())?;
+            arbitrary = quote! {{
+                let g = &mut ::quickcheck_async::__private::resized(g, #size);
+                #arbitrary
+            }};
+        }
+
+        if let Some(shrinker) = shrinkers.first().filter(|s| s.path.is_ident("no_shrink")) {
+            if !shrinker.tokens.is_empty() {
+                let msg = "no_shrink takes no arguments";
+                return Err(Error::new_spanned(shrinker, msg).to_compile_error().into());
+            }
+
+            shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(::std::iter::empty())
+                }
+            };
+        } else if let Some(shrinker) = shrinkers.first() {
+            if unwrapped {
+                let msg = "an argument generated by a strategy, regex, map or flat_map shrinks as it does";
+                return Err(Error::new_spanned(shrinker, msg).to_compile_error().into());
+            }
+
+            let candidates = if shrinker.path.is_ident("shrink_toward") {
+                let target: Expr = shrinker.parse_args().map_err(|e| e.to_compile_error())?;
+                quote!(::quickcheck_async::__private::Toward::toward(&self.0, #target))
+            } else {
+                let path: Path = shrinker.parse_args().map_err(|e| e.to_compile_error())?;
+                quote!(::std::iter::IntoIterator::into_iter(#path(&self.0)))
+            };
+            shrink = quote! {
+                fn shrink(&self) -> ::std::boxed::Box<dyn ::std::iter::Iterator<Item = Self>> {
+                    ::std::boxed::Box::new(#candidates.map(#wrapper))
+                }
+            };
+        }
+
+        support.extend(quote! {
+            #[allow(non_camel_case_types)]
+            #[derive(Clone)]
+            struct #wrapper(#held);
+
+            // shown as the value itself, as that is what the test fn receives
+            impl ::std::fmt::Debug for #wrapper {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    ::std::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+
+            impl ::quickcheck_async::__private::quickcheck::Arbitrary for #wrapper {
+                fn arbitrary<G: ::quickcheck_async::__private::quickcheck::Gen>(g: &mut G) -> Self {
+                    #wrapper(#arbitrary)
+                }
+
+                #shrink
+            }
+        });
+
+        if unwrapped {
+            bindings.push(syn::parse_quote!(let #pat: #ty = #pat.0.into_inner();));
+        } else {
+            *pt.pat = syn::parse_quote!(#wrapper(#pat));
+        }
+        *pt.ty = syn::parse_quote!(#wrapper);
+    }
+
+    fn_item.block.stmts.splice(0..0, bindings);
+
+    Ok(support)
+}
+
+/// Wraps the value of each `return` in the body of a `fallible` test in `Ok`, leaving
+/// those of nested closures, async blocks and items alone.
+struct WrapReturns;
+
+impl VisitMut for WrapReturns {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Closure(_) | Expr::Async(_) => {}
+            Expr::Return(ret) => {
+                let value = match ret.expr.take() {
+                    Some(mut value) => {
+                        self.visit_expr_mut(&mut value);
+                        quote!(#value)
+                    }
+                    None => quote!(()),
+                };
+                ret.expr = Some(syn::parse_quote!(::std::result::Result::Ok(#value)));
+            }
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
+
+/// Returns `fn_item` as an async fn, wrapping one returning `impl Future` in one which
+/// awaits it.
+fn async_fn(fn_item: ItemFn) -> Result<ItemFn, TokenStream> {
+    for attr in &fn_item.attrs {
+        if attr.path.is_ident("test") {
+            return Err(
+                Error::new_spanned(fn_item, "multiple #[test] attributes were supplied")
+                    .to_compile_error()
+                    .into(),
+            );
+        }
+    }
+
+    if fn_item.sig.asyncness.is_some() {
+        return Ok(fn_item);
+    }
+
+    let output = match &fn_item.sig.output {
+        ReturnType::Type(_, ty) => future_output(ty),
+        ReturnType::Default => None,
+    };
+
+    let output = match output {
+        Some(output) => output,
+        None => {
+            return Err(
+                Error::new_spanned(fn_item, "test fn must be async, or return impl Future")
+                    .to_compile_error()
+                    .into(),
+            )
+        }
+    };
+
+    // the args are renamed, as the original patterns needn't be valid expressions
+    let mut outer = fn_item.clone();
+    let mut args = Vec::new();
+
+    for (i, arg) in outer.sig.inputs.iter_mut().enumerate() {
+        if let FnArg::Typed(pt) = arg {
+            let id = format_ident!("__arg{}", i);
+            *pt.pat = syn::parse_quote!(#id);
+            args.push(id);
+        }
+    }
+
+    let mut inner = fn_item;
+    inner.attrs.clear();
+    let name = &inner.sig.ident;
+
+    outer.sig.asyncness = Some(Default::default());
+    outer.sig.output = syn::parse_quote!(-> #output);
+    outer.block = syn::parse_quote!({
+        #inner
+        #name(#(#args),*).await
+    });
+
+    Ok(outer)
+}
+
+/// Returns `T` if `ty` is `impl Future<Output = T>`.
+fn future_output(ty: &Type) -> Option<Type> {
+    impl_binding(ty, "Future", "Output")
+}
+
+/// Returns `T` if `ty` is `impl Stream<Item = T>`.
+fn stream_item(ty: &Type) -> Option<Type> {
+    impl_binding(ty, "Stream", "Item")
+}
+
+/// Returns `T` if `ty` is `impl Trait<Assoc = T>`, among any other bounds.
+fn impl_binding(ty: &Type, trait_name: &str, assoc: &str) -> Option<Type> {
+    let bounds = match ty {
+        Type::ImplTrait(ty) => &ty.bounds,
+        _ => return None,
+    };
+
+    bounds.iter().find_map(|bound| {
+        let segment = match bound {
+            TypeParamBound::Trait(bound) => bound.path.segments.last()?,
+            _ => return None,
+        };
+
+        match &segment.arguments {
+            PathArguments::AngleBracketed(args) if segment.ident == trait_name => {
+                args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Binding(b) if b.ident == assoc => Some(b.ty.clone()),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Replaces each `impl Stream<Item = T>` argument type of `fn_item` with
+/// `ArbStream<T>`, which implements it.
+fn apply_streams(fn_item: &mut ItemFn) -> Result<(), TokenStream> {
+    for arg in fn_item.sig.inputs.iter_mut() {
+        let pt = match arg {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => continue,
+        };
+
+        if let Some(item) = stream_item(&pt.ty) {
+            if cfg!(not(feature = "stream")) {
+                let msg = "impl Stream arguments require the `stream` feature";
+                return Err(Error::new_spanned(&pt.ty, msg).to_compile_error().into());
+            }
+            *pt.ty = syn::parse_quote!(::quickcheck_async::ArbStream<#item>);
+        }
+    }
+
+    Ok(())
+}
+
+/// quickcheck configuration accepted by every attribute.
+struct QcArgs {
+    config: Option<Path>,
+    tests: Option<LitInt>,
+    duration: Option<u64>,
+    gen_size: Option<LitInt>,
+    seed: Option<LitInt>,
+    rng: Option<Ident>,
+    max_discard_ratio: Option<f64>,
+    shrink: Option<LitBool>,
+    max_shrink_iters: Option<LitInt>,
+    shrink_timeout: Option<u64>,
+    shrink_parallelism: Option<LitInt>,
+    shrink_progress: Option<LitBool>,
+    shrink_by: Option<Ident>,
+    shrink_checkpoint: Option<LitStr>,
+    exhaustive: Option<Ident>,
+    fallible: Option<Ident>,
+    arbitrary: Option<Ident>,
+    progress: Option<proc_macro2::TokenStream>,
+    verbose: Option<LitBool>,
+    report: Option<Ident>,
+    growth: Option<Ident>,
+    ignore_env: Option<LitBool>,
+    retries: Option<LitInt>,
+    max_depth: Option<LitInt>,
+    ci_scale: Option<LitInt>,
+    ci_var: Option<LitStr>,
+    deterministic: Option<LitBool>,
+    fail_fast: Option<LitBool>,
+    debug_tests: Option<LitInt>,
+    release_tests: Option<LitInt>,
+    confidence: Option<f64>,
+    failure_rate: Option<f64>,
+    expect: Option<Ident>,
+    should_panic: Option<(Ident, Option<LitStr>)>,
+    /// Settings from `quickcheck_async.toml`, and the path they were read from.
+    file: Option<(Box<QcArgs>, String)>,
+    types: Option<Types>,
+    /// A closure making the cases given by `#[examples(..)]`, set by [check_fn].
+    examples: Option<proc_macro2::TokenStream>,
+}
+
+/// The instantiations of a generic test fn given by `types(..)`, along with the other
+/// arguments of its attribute, which each instantiation is tested with.
+struct Types {
+    id: Ident,
+    tys: Vec<Type>,
+    args: Vec<proc_macro2::TokenStream>,
+}
+
+impl ToTokens for QcArgs {
+    /// Expands to a `quickcheck_async::__private::Config` with these settings applied.
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let mut config = match &self.config {
+            Some(path) => quote!(#path()),
+            None => quote!(::quickcheck_async::__private::Config::new()),
+        };
+
+        if let Some(tests) = &self.tests {
+            config = quote!(#config.tests(#tests));
+        }
+
+        // the profile is that of the crate being tested, so is checked in expanded code
+        if let Some(n) = &self.debug_tests {
+            config = quote!({
+                let config = #config;
+                if cfg!(debug_assertions) { config.tests(#n) } else { config }
+            });
+        }
+
+        if let Some(n) = &self.release_tests {
+            config = quote!({
+                let config = #config;
+                if cfg!(debug_assertions) { config } else { config.tests(#n) }
+            });
+        }
+
+        if let Some(confidence) = self.confidence {
+            config = quote!(#config.confidence(#confidence));
+        }
+
+        if let Some(rate) = self.failure_rate {
+            config = quote!(#config.failure_rate(#rate));
+        }
+
+        if let Some(factor) = &self.ci_scale {
+            config = quote!(#config.ci_scale(#factor));
+        }
+
+        if let Some(var) = &self.ci_var {
+            config = quote!(#config.ci_var(#var));
+        }
+
+        if let Some(ms) = self.duration {
+            config = quote!(#config.duration(::std::time::Duration::from_millis(#ms)));
+        }
+
+        if let Some(size) = &self.gen_size {
+            config = quote!(#config.gen_size(#size));
+        }
+
+        if let Some(growth) = &self.growth {
+            config = quote!(#config.growth(::quickcheck_async::__private::Growth::#growth));
+        }
+
+        if let Some(seed) = &self.seed {
+            config = quote!(#config.seed(#seed));
+        }
+
+        if let Some(deterministic) = &self.deterministic {
+            config = quote!(#config.deterministic(#deterministic));
+        }
+
+        if let Some(rng) = &self.rng {
+            config = quote!(#config.rng(::quickcheck_async::__private::Rng::#rng));
+        }
+
+        if let Some(ratio) = self.max_discard_ratio {
+            config = quote!(#config.max_discard_ratio(#ratio));
+        }
+
+        if let Some(retries) = &self.retries {
+            config = quote!(#config.retries(#retries));
+        }
+
+        if let Some(depth) = &self.max_depth {
+            config = quote!(#config.max_depth(#depth));
+        }
+
+        if let Some(expect) = &self.expect {
+            config = quote!(#config.expect(::quickcheck_async::__private::Expect::#expect));
+        }
+
+        match &self.should_panic {
+            Some((_, Some(expected))) => config = quote!(#config.should_panic(Some(#expected))),
+            Some((_, None)) => config = quote!(#config.should_panic(None)),
+            None => {}
+        }
+
+        if let Some(shrink) = &self.shrink {
+            config = quote!(#config.shrink(#shrink));
+        }
+
+        if let Some(iters) = &self.max_shrink_iters {
+            config = quote!(#config.max_shrink_iters(#iters));
+        }
+
+        if let Some(ms) = self.shrink_timeout {
+            config = quote!(#config.shrink_timeout(::std::time::Duration::from_millis(#ms)));
+        }
+
+        if let Some(n) = &self.shrink_parallelism {
+            config = quote!(#config.shrink_parallelism(#n));
+        }
+
+        if let Some(shrink_progress) = &self.shrink_progress {
+            config = quote!(#config.shrink_progress(#shrink_progress));
+        }
+
+        if let Some(path) = &self.shrink_checkpoint {
+            config = quote!(#config.shrink_checkpoint(#path));
+        }
+
+        if let Some(shrink_by) = &self.shrink_by {
+            config = quote!(#config.shrink_by(::quickcheck_async::__private::ShrinkBy::#shrink_by));
+        }
+
+        if let Some(progress) = &self.progress {
+            let progress = quote!(::quickcheck_async::__private::Progress::#progress);
+            config = quote!(#config.progress(#progress));
+        }
+
+        if let Some(fail_fast) = &self.fail_fast {
+            config = quote!(#config.fail_fast(#fail_fast));
+        }
+
+        if let Some(verbose) = &self.verbose {
+            config = quote!(#config.verbose(#verbose));
+        }
+
+        if let Some(report) = &self.report {
+            config = quote!(#config.report(::quickcheck_async::__private::Report::#report));
+        }
+
+        if let Some(ignore) = &self.ignore_env {
+            config = quote!(#config.ignore_env(#ignore));
+        }
+
+        if let Some((file, path)) = &self.file {
+            // including the file makes cargo rebuild the test when it changes, though a file
+            // created where there was none can't be tracked this way
+            config = quote!(#config.file_defaults({
+                const _: &[u8] = include_bytes!(#path);
+                #file
+            }));
+        }
+
+        if let Some(examples) = &self.examples {
+            config = quote!(#config.examples(#examples));
+        }
+
+        if self.exhaustive.is_some() {
+            config = quote!(#config.exhaustive());
+        }
+
+        tokens.extend(config);
+    }
+}
+
+/// Remove the quickcheck configuration from attribute arguments, returning it along with
+/// the remaining runtime-specific arguments. Settings from `quickcheck_async.toml` are
+/// included beneath those on the attribute.
+fn qc_args(args: TokenStream) -> Result<(QcArgs, TokenStream), TokenStream> {
+    let (mut qc, rest) = parse_qc_args(args)?;
+
+    qc.file = file_args()?;
+
+    Ok((qc, rest))
+}
+
+/// Reads the settings in the nearest `quickcheck_async.toml`, searching from the crate
+/// being compiled up to the root of its workspace, or only in the crate's own directory
+/// if it isn't in one. Returns `None` if there is no such file.
+fn file_args() -> Result<Option<(Box<QcArgs>, String)>, TokenStream> {
+    let err = |msg: String| -> TokenStream {
+        let msg = format!("quickcheck_async.toml: {}", msg);
+        Error::new(Span::call_site(), msg).to_compile_error().into()
+    };
+
+    let dir = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => return Ok(None),
+    };
+
+    // the workspace root is the nearest ancestor whose manifest declares a workspace
+    let root = dir
+        .ancestors()
+        .position(|dir| read_manifest(dir).is_some_and(|m| m.get("workspace").is_some()))
+        .unwrap_or(0);
+
+    let path = match dir
+        .ancestors()
+        .take(root + 1)
+        .map(|dir| dir.join("quickcheck_async.toml"))
+        .find(|path| path.is_file())
+    {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    let table = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| s.parse::<toml::Value>().map_err(|e| e.to_string()))
+        .map_err(err)?;
+
+    let table = match table {
+        toml::Value::Table(table) => table,
+        _ => return Err(err("expected a table of settings".to_owned())),
+    };
+
+    let mut args = Vec::new();
+
+    for (key, value) in table {
+        let id = syn::parse_str::<Ident>(&key).map_err(|_| err(format!("bad key {:?}", key)))?;
+
+        let lit = match value {
+            toml::Value::Integer(i) => Lit::Int(LitInt::new(&i.to_string(), Span::call_site())),
+            toml::Value::Float(f) => Lit::Verbatim(proc_macro2::Literal::f64_unsuffixed(f)),
+            toml::Value::Boolean(b) => Lit::Bool(LitBool::new(b, Span::call_site())),
+            toml::Value::String(s) => Lit::Str(LitStr::new(&s, Span::call_site())),
+            _ => return Err(err(format!("{} must be a number, bool or string", key))),
+        };
+
+        args.push(quote!(#id = #lit));
+    }
+
+    let (qc, rest) = parse_qc_args(quote!(#(#args),*).into())?;
+
+    if let Some(arg) = split_args(rest.into()).first() {
+        return Err(err(format!("unknown setting `{}`", arg)));
+    }
+
+    if qc.config.is_some() || qc.should_panic.is_some() {
+        let id = if qc.config.is_some() {
+            "config"
+        } else {
+            "should_panic"
+        };
+        return Err(err(format!("{} can only be set on a test", id)));
+    }
+
+    Ok(Some((Box::new(qc), path.to_string_lossy().into_owned())))
+}
+
+fn parse_qc_args(args: TokenStream) -> Result<(QcArgs, TokenStream), TokenStream> {
+    let mut qc = QcArgs {
+        config: None,
+        tests: None,
+        duration: None,
+        gen_size: None,
+        seed: None,
+        rng: None,
+        max_discard_ratio: None,
+        shrink: None,
+        max_shrink_iters: None,
+        shrink_timeout: None,
+        shrink_parallelism: None,
+        shrink_progress: None,
+        shrink_by: None,
+        shrink_checkpoint: None,
+        exhaustive: None,
+        fallible: None,
+        arbitrary: None,
+        progress: None,
+        verbose: None,
+        report: None,
+        growth: None,
+        ignore_env: None,
+        retries: None,
+        max_depth: None,
+        ci_scale: None,
+        ci_var: None,
+        deterministic: None,
+        fail_fast: None,
+        debug_tests: None,
+        release_tests: None,
+        confidence: None,
+        failure_rate: None,
+        expect: None,
+        should_panic: None,
+        file: None,
+        types: None,
+        examples: None,
+    };
+    let mut rest = Vec::new();
+
+    let mut others = Vec::new();
+    let mut types = None;
+
+    for arg in split_args(args.into()) {
+        let mut tts = arg.clone().into_iter();
+
+        if let (Some(TokenTree::Ident(id)), Some(TokenTree::Group(group)), None) =
+            (tts.next(), tts.next(), tts.next())
+        {
+            if id == "types" && group.delimiter() == Delimiter::Parenthesis {
+                let parser = Punctuated::<Type, Comma>::parse_terminated;
+                let tys = parser
+                    .parse2(group.stream())
+                    .map_err(|e| TokenStream::from(e.to_compile_error()))?;
+                types = Some((id, tys.into_iter().collect()));
+                continue;
+            }
+        }
+
+        others.push(arg.clone());
+        let mut tts = arg.clone().into_iter();
+
+        if let (Some(TokenTree::Ident(id)), None) = (tts.next(), tts.next()) {
+            if id == "exhaustive" {
+                qc.exhaustive = Some(id);
+                continue;
+            }
+
+            if id == "should_panic" {
+                qc.should_panic = Some((id, None));
+                continue;
+            }
+
+            if id == "fallible" {
+                qc.fallible = Some(id);
+                continue;
+            }
+
+            if id == "arbitrary" {
+                qc.arbitrary = Some(id);
+                continue;
+            }
+        }
+
+        let mut tts = arg.clone().into_iter();
+
+        let (id, lit) = match (tts.next(), tts.next(), tts.next(), tts.next()) {
+            (Some(TokenTree::Ident(id)), Some(TokenTree::Punct(eq)), Some(lit), None)
+                if eq.as_char() == '=' =>
+            {
+                (id, lit)
+            }
+
+            _ => {
+                rest.push(arg);
+                continue;
+            }
+        };
+
+        let int = || {
+            syn::parse2::<LitInt>(lit.clone().into()).map_err(|_| -> TokenStream {
+                Error::new_spanned(&arg, format!("{} must be an integer", id))
+                    .to_compile_error()
+                    .into()
+            })
+        };
+
+        let number = || {
+            let n = match syn::parse2::<Lit>(lit.clone().into()) {
+                Ok(Lit::Int(lit)) => lit.base10_parse().ok(),
+                Ok(Lit::Float(lit)) => lit.base10_parse().ok(),
+                _ => None,
+            };
+
+            n.ok_or_else(|| -> TokenStream {
+                Error::new_spanned(&arg, format!("{} must be a number", id))
+                    .to_compile_error()
+                    .into()
+            })
+        };
+
+        let probability = || {
+            number()
+                .ok()
+                .filter(|&p: &f64| p > 0.0 && p < 1.0)
+                .ok_or_else(|| -> TokenStream {
+                    Error::new_spanned(&arg, format!("{} must be between 0 and 1", id))
+                        .to_compile_error()
+                        .into()
+                })
+        };
+
+        let bool = || {
+            syn::parse2::<LitBool>(lit.clone().into()).map_err(|_| -> TokenStream {
+                Error::new_spanned(&arg, format!("{} must be a bool", id))
+                    .to_compile_error()
+                    .into()
+            })
+        };
+
+        let millis = || {
+            syn::parse2::<LitStr>(lit.clone().into())
+                .ok()
+                .and_then(|lit| parse_millis(&lit.value()))
+                .ok_or_else(|| -> TokenStream {
+                    let msg = format!(r#"{} must be a duration like "30s" or "500ms""#, id);
+                    Error::new_spanned(&arg, msg).to_compile_error().into()
+                })
+        };
+
+        match id.to_string().as_str() {
+            "tests" => qc.tests = Some(int()?),
+
+            "debug_tests" => qc.debug_tests = Some(int()?),
+
+            "release_tests" => qc.release_tests = Some(int()?),
+
+            "gen_size" => qc.gen_size = Some(int()?),
+
+            "seed" => qc.seed = Some(int()?),
+
+            "retries" => qc.retries = Some(int()?),
+
+            "max_depth" => qc.max_depth = Some(int()?),
+
+            "ci_scale" => qc.ci_scale = Some(int()?),
+
+            "ci_var" => match syn::parse2::<LitStr>(lit.clone().into()) {
+                Ok(var) => qc.ci_var = Some(var),
+                Err(_) => {
+                    return Err(Error::new_spanned(arg, "ci_var must be a string")
+                        .to_compile_error()
+                        .into())
+                }
+            },
+
+            "shrink_checkpoint" => match syn::parse2::<LitStr>(lit.clone().into()) {
+                Ok(path) => qc.shrink_checkpoint = Some(path),
+                Err(_) => {
+                    return Err(Error::new_spanned(arg, "shrink_checkpoint must be a path")
+                        .to_compile_error()
+                        .into())
+                }
+            },
+
+            "max_shrink_iters" => qc.max_shrink_iters = Some(int()?),
+
+            "shrink_parallelism" => qc.shrink_parallelism = Some(int()?),
+
+            "rng" => {
+                let rng = match syn::parse2::<LitStr>(lit.clone().into()) {
+                    Ok(lit) if lit.value() == "chacha8" => "ChaCha8",
+                    Ok(lit) if lit.value() == "os" => "Os",
+                    _ => {
+                        return Err(Error::new_spanned(arg, r#"rng must be "chacha8" or "os""#)
+                            .to_compile_error()
+                            .into())
+                    }
+                };
+
+                qc.rng = Some(Ident::new(rng, lit.span()));
+            }
+
+            "should_panic" => match syn::parse2::<LitStr>(lit.clone().into()) {
+                Ok(expected) => qc.should_panic = Some((id, Some(expected))),
+                Err(_) => {
+                    return Err(Error::new_spanned(arg, "should_panic must be a string")
+                        .to_compile_error()
+                        .into())
+                }
+            },
+
+            "expect" => {
+                let expect = match syn::parse2::<LitStr>(lit.clone().into()) {
+                    Ok(lit) if lit.value() == "pass" => "Pass",
+                    Ok(lit) if lit.value() == "fail" => "Fail",
+                    _ => {
+                        return Err(
+                            Error::new_spanned(arg, r#"expect must be "pass" or "fail""#)
+                                .to_compile_error()
+                                .into(),
+                        )
+                    }
+                };
+
+                qc.expect = Some(Ident::new(expect, lit.span()));
+            }
+
+            "growth" => {
+                let growth = match syn::parse2::<LitStr>(lit.clone().into()) {
+                    Ok(lit) if lit.value() == "constant" => "Constant",
+                    Ok(lit) if lit.value() == "linear" => "Linear",
+                    Ok(lit) if lit.value() == "log" => "Log",
+                    _ => {
+                        return Err(Error::new_spanned(
+                            arg,
+                            r#"growth must be "constant", "linear" or "log""#,
+                        )
+                        .to_compile_error()
+                        .into())
+                    }
+                };
+
+                qc.growth = Some(Ident::new(growth, lit.span()));
+            }
+
+            "shrink_by" => {
+                let shrink_by = match syn::parse2::<LitStr>(lit.clone().into()) {
+                    Ok(lit) if lit.value() == "values" => "Values",
+                    Ok(lit) if lit.value() == "choices" => "Choices",
+                    _ => {
+                        return Err(Error::new_spanned(
+                            arg,
+                            r#"shrink_by must be "values" or "choices""#,
+                        )
+                        .to_compile_error()
+                        .into())
+                    }
+                };
+
+                qc.shrink_by = Some(Ident::new(shrink_by, lit.span()));
+            }
+
+            "report" => {
+                let report = match syn::parse2::<LitStr>(lit.clone().into()) {
+                    Ok(lit) if lit.value() == "default" => "Default",
+                    Ok(lit) if lit.value() == "quiet" => "Quiet",
+                    Ok(lit) if lit.value() == "summary" => "Summary",
+                    _ => {
+                        return Err(Error::new_spanned(
+                            arg,
+                            r#"report must be "default", "quiet" or "summary""#,
+                        )
+                        .to_compile_error()
+                        .into())
+                    }
+                };
+
+                qc.report = Some(Ident::new(report, lit.span()));
+            }
+
+            "max_discard_ratio" => qc.max_discard_ratio = Some(number()?),
+
+            "confidence" => qc.confidence = Some(probability()?),
+
+            "failure_rate" => qc.failure_rate = Some(probability()?),
+
+            "shrink" => qc.shrink = Some(bool()?),
+
+            "verbose" => qc.verbose = Some(bool()?),
+
+            "shrink_progress" => qc.shrink_progress = Some(bool()?),
+
+            "ignore_env" => qc.ignore_env = Some(bool()?),
+
+            "deterministic" => qc.deterministic = Some(bool()?),
+
+            "fail_fast" => qc.fail_fast = Some(bool()?),
+
+            "config" => match syn::parse2::<LitStr>(lit.clone().into()).and_then(|l| l.parse()) {
+                Ok(path) => qc.config = Some(path),
+                Err(_) => {
+                    return Err(Error::new_spanned(arg, "config must be a path to a fn")
+                        .to_compile_error()
+                        .into())
+                }
+            },
+
+            "duration" => qc.duration = Some(millis()?),
+
+            "shrink_timeout" => qc.shrink_timeout = Some(millis()?),
+
+            "progress" => {
+                qc.progress = Some(match syn::parse2::<LitInt>(lit.clone().into()) {
+                    Ok(n) => quote!(Cases(#n)),
+                    Err(_) => {
+                        let ms = millis().map_err(|_| -> TokenStream {
+                            let msg =
+                                r#"progress must be a number of cases or a duration like "5s""#;
+                            Error::new_spanned(&arg, msg).to_compile_error().into()
+                        })?;
+                        quote!(Every(::std::time::Duration::from_millis(#ms)))
+                    }
+                })
+            }
+
+            _ => rest.push(arg),
+        }
+    }
+
+    if let (Some(seed), Some(rng)) = (&qc.seed, &qc.rng) {
+        if rng == "Os" {
+            return Err(
+                Error::new_spanned(seed, "seed cannot be combined with rng = \"os\"")
+                    .to_compile_error()
+                    .into(),
+            );
+        }
+    }
+
+    qc.types = types.map(|(id, tys)| Types {
+        id,
+        tys,
+        args: others,
+    });
+
+    Ok((qc, quote!(#(#rest),*).into()))
+}
+
+/// Parse a duration such as `"30s"` into milliseconds.
+fn parse_millis(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (n, unit) = s.split_at(split);
+    let n: u64 = n.parse().ok()?;
+
+    let scale = match unit.trim() {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return None,
+    };
+
+    n.checked_mul(scale)
+}
+
+/// Discard the cases of a test for which a predicate over its arguments doesn't hold,
+/// before its body runs.
+///
+/// This must be placed beneath the test attribute, which applies it. The predicate is
+/// called with a reference to each argument, and may be given more than once.
+///
+/// ```
+/// #[quickcheck_async::tokio]
+/// #[quickcheck_async::filter(|a, b| a < b)]
+/// async fn ordered(a: u8, b: u8) -> bool {
+///     b - a > 0
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn filter(_: TokenStream, item: TokenStream) -> TokenStream {
+    let mut out = Error::new(
+        Span::call_site(),
+        "filter must be placed beneath a quickcheck_async test attribute",
+    )
+    .to_compile_error();
+    out.extend(proc_macro2::TokenStream::from(item));
+    out.into()
+}
+
+/// Derive `AsyncArbitrary` for a struct or enum, generating each field in order.
+///
+/// Fields are generated with `quickcheck::Arbitrary`, except those marked
+/// `#[async_arbitrary]`, which are awaited from their own `AsyncArbitrary` impl. An enum
+/// picks each of its variants equally often.
+///
+/// ```
+/// use quickcheck_async::{AsyncArbitrary, Generator};
+///
+/// struct Conn(u16);
+///
+/// impl AsyncArbitrary for Conn {
+///     async fn arbitrary(_: &mut Generator) -> Self {
+///         Conn(8080)
+///     }
+/// }
+///
+/// #[derive(AsyncArbitrary)]
+/// enum Request {
+///     Get { path: String, #[async_arbitrary] conn: Conn },
+///     Ping(u8),
+///     Close,
+/// }
+///
+/// #[quickcheck_async::tokio]
+/// async fn served(#[async_arbitrary] req: Request) -> bool {
+///     match req {
+///         Request::Get { conn, .. } => conn.0 == 8080,
+///         Request::Ping(_) | Request::Close => true,
+///     }
+/// }
+/// ```
+#[proc_macro_derive(AsyncArbitrary, attributes(async_arbitrary))]
+pub fn async_arbitrary(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => construct(quote!(#name), &data.fields),
+        Data::Enum(data) if data.variants.is_empty() => {
+            return Error::new_spanned(name, "cannot generate an enum without variants")
+                .to_compile_error()
+                .into();
+        }
+        Data::Enum(data) => {
+            let n = data.variants.len();
+            let arms = data.variants.iter().enumerate().map(|(i, v)| {
+                let id = &v.ident;
+                let value = construct(quote!(#name::#id), &v.fields);
+                quote!(#i => #value,)
+            });
+            quote! {
+                match ::quickcheck_async::__private::variant(g, #n) {
+                    #(#arms)*
+                    _ => ::std::unreachable!(),
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Error::new_spanned(
+                data.union_token,
+                "cannot derive AsyncArbitrary for a union",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    (quote! {
+        impl #impl_generics ::quickcheck_async::AsyncArbitrary for #name #ty_generics
+        #where_clause
+        {
+            async fn arbitrary(g: &mut ::quickcheck_async::Generator) -> Self {
+                #body
+            }
+        }
+    })
+    .into()
+}
+
+/// An expression building `path` from `fields`, each generated in turn from `g`.
+fn construct(path: proc_macro2::TokenStream, fields: &Fields) -> proc_macro2::TokenStream {
+    let values = fields.iter().map(|f| {
+        let ty = &f.ty;
+        if f.attrs.iter().any(|a| a.path.is_ident("async_arbitrary")) {
+            quote!(<#ty as ::quickcheck_async::AsyncArbitrary>::arbitrary(g).await)
+        } else {
+            quote!(<#ty as ::quickcheck_async::__private::quickcheck::Arbitrary>::arbitrary(g))
+        }
+    });
+
+    match fields {
+        Fields::Named(_) => {
+            let ids = fields.iter().map(|f| &f.ident);
+            quote!(#path { #(#ids: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => path,
+    }
+}
+
+/// Give cases to run before any generated ones, beneath a quickcheck_async test
+/// attribute, so known edge cases are covered on every run.
+///
+/// Each example is a tuple holding a value for every generated argument, or just the
+/// value if there is only one. Arguments made by the test itself, such as fixtures and
+/// `&mut Generator`s, are left out. Examples count towards the number of tests, and are
+/// shrunk like any other case if they fail.
+///
+/// ```
+/// #[quickcheck_async::tokio]
+/// #[quickcheck_async::examples((0, ""), (u64::MAX, "x"))]
+/// async fn display(x: u64, s: &str) -> bool {
+///     format!("{}{}", x, s).starts_with(&x.to_string())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn examples(_: TokenStream, item: TokenStream) -> TokenStream {
+    let mut out = Error::new(
+        Span::call_site(),
+        "examples must be placed beneath a quickcheck_async test attribute",
+    )
+    .to_compile_error();
+    out.extend(proc_macro2::TokenStream::from(item));
+    out.into()
+}
+
+/// Set defaults for every test in the crate, using the same settings accepted by each
+/// attribute.
+///
+/// This should be invoked at most once per test crate. Settings made on a test, and
+/// the `QUICKCHECK_*` environment variables, take precedence over these defaults.
+///
+/// ```
+/// quickcheck_async::config!(tests = 500, gen_size = 200);
+///
+/// #[quickcheck_async::tokio]
+/// async fn fuzz_me(fuzz_arg: Vec<u8>) -> bool {
+///     fuzz_arg.len() <= 200
+/// }
+/// ```
+#[proc_macro]
+pub fn config(input: TokenStream) -> TokenStream {
+    let (qc, rest) = match qc_args(input) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    if let Some(arg) = split_args(rest.into()).first() {
+        return Error::new_spanned(arg, "unknown setting")
+            .to_compile_error()
+            .into();
+    }
+
+    if let Some(id) = qc
+        .exhaustive
+        .as_ref()
+        .or(qc.fallible.as_ref())
+        .or(qc.arbitrary.as_ref())
+        .or(qc.types.as_ref().map(|types| &types.id))
+        .or(qc.should_panic.as_ref().map(|(id, _)| id))
+    {
+        return Error::new_spanned(id, format!("{} can only be set on a test", id))
+            .to_compile_error()
+            .into();
+    }
+
+    quote!(::quickcheck_async::__private::inventory::submit! {
+        ::quickcheck_async::__private::Defaults(|| #qc)
+    })
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within a tokio
+/// executor.
+///
+/// quickcheck itself runs on a blocking thread, and every generated case is driven on
+/// the test's runtime, so tokio's timers, IO, and spawning functions are available
+/// within the test fn.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::tokio]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// # Attribute arguments
+///
+/// Arguments to this attribute are passed through to [tokio::test][tt].
+///
+/// ```
+/// #[quickcheck_async::tokio(core_threads = 3)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// Both the tokio 0.2 (`core_threads`, `basic_scheduler`, `threaded_scheduler`) and 1.x
+/// (`flavor`, `worker_threads`) argument names are accepted, and are translated to those
+/// of the tokio version found in the crate's manifest. `max_threads` has no attribute
+/// equivalent in tokio 1.x; use a builder instead.
+///
+/// ```
+/// #[quickcheck_async::tokio(flavor = "multi_thread", worker_threads = 2)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// Alternatively, `builder` may name a function returning a `tokio::runtime::Runtime`,
+/// which will be used to run the test. This allows full control over how the runtime
+/// is configured, and cannot be combined with other runtime arguments.
+///
+/// ```
+/// fn make_runtime() -> tokio::runtime::Runtime {
+///     tokio::runtime::Builder::new()
+///         .threaded_scheduler()
+///         .thread_name("fuzzer")
+///         .enable_all()
+///         .build()
+///         .unwrap()
+/// }
+///
+/// #[quickcheck_async::tokio(builder = "make_runtime")]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// By default, all generated cases share one runtime. With `isolate_cases`, a new
+/// runtime is built for every case instead (using `builder`, if provided), so tasks
+/// spawned by one case cannot affect another.
+///
+/// ```
+/// #[quickcheck_async::tokio(isolate_cases)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     tokio::spawn(async move { fuzz_arg != "fuzzed".to_owned() })
+///         .await
+///         .unwrap()
+/// }
+/// ```
+///
+/// With `shared`, every test in the binary marked `shared` runs its cases on one
+/// lazily built multi-threaded runtime (or one per `builder`), rather than building a
+/// runtime for each test. It cannot be combined with `isolate_cases` or `start_paused`.
+///
+/// ```
+/// #[quickcheck_async::tokio(shared)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// With `local`, every case is run within a [LocalSet][ls] on the test thread, so the
+/// test fn is not required to be `Send` and may use [spawn_local][sl]. This can also
+/// be combined with `builder` and `isolate_cases`.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// #[quickcheck_async::tokio(local)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     let rc = Rc::new(fuzz_arg);
+///     tokio::task::spawn_local(async move { *rc != "fuzzed".to_owned() })
+///         .await
+///         .unwrap()
+/// }
+/// ```
+///
+/// With `start_paused = true`, every case is run on a new runtime with its clock
+/// [paused][tp]. Time advances automatically whenever the runtime is idle, so cases that
+/// sleep complete immediately. This requires tokio's `test-util` feature.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// #[quickcheck_async::tokio(start_paused = true)]
+/// async fn fuzz_me(fuzz_arg: u16) {
+///     tokio::time::delay_for(Duration::from_secs(fuzz_arg as u64)).await;
+/// }
+/// ```
+///
+/// # Runtime handle
+///
+/// Arguments of type `tokio::runtime::Handle` are not generated; each case is passed a
+/// handle to the runtime it is running on instead. The type must be named by a path
+/// ending in `runtime::Handle`, so that other types called `Handle` are generated as usual.
+///
+/// ```
+/// use tokio::runtime;
+///
+/// #[quickcheck_async::tokio]
+/// async fn fuzz_me(fuzz_arg: String, handle: runtime::Handle) -> bool {
+///     handle
+///         .spawn(async move { fuzz_arg != "fuzzed".to_owned() })
+///         .await
+///         .unwrap()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [tt]: https://docs.rs/tokio/latest/tokio/attr.test.html
+/// [tp]: https://docs.rs/tokio/latest/tokio/time/fn.pause.html
+/// [ls]: https://docs.rs/tokio/latest/tokio/task/struct.LocalSet.html
+/// [sl]: https://docs.rs/tokio/latest/tokio/task/fn.spawn_local.html
+#[proc_macro_attribute]
+pub fn tokio(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| tokio_test(args, item))
+}
+
+fn tokio_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "tokio") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let TokioArgs {
+        builder,
+        isolate_cases,
+        local,
+        shared,
+        start_paused,
+        version,
+        attrib,
+    } = match tokio_args(parse_macro_input!(args as AttributeArgs)) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let mut call_args = Vec::new();
+    let mut injected = false;
+    let mut gen = Arguments {
+        ids: Punctuated::new(),
+        tys: Punctuated::new(),
+    };
+
+    for (id, ty) in ids.iter().zip(tys.iter()) {
+        if is_tokio_handle(ty) {
+            call_args.push(quote!(::tokio::runtime::Handle::current()));
+            injected = true;
+        } else if matches!(ty, Type::Reference(r) if is_last_ident(&r.elem, "Runtime")) {
+            return Error::new_spanned(ty, "test fn cannot borrow the runtime; take a Handle")
+                .to_compile_error()
+                .into();
+        } else {
+            call_args.push(quote!(#id));
+            gen.ids.push(id.clone());
+            gen.tys.push(ty.clone());
+        }
+    }
+
+    // the handle is only available once a case is running on the runtime
+    let call = if injected {
+        quote!(async move { #call_by(#(#call_args),*).await })
+    } else {
+        quote!(#call_by(#ids))
+    };
+
+    let Arguments { ids, tys } = gen;
+
+    let ret = &fn_item.sig.output;
+
+    if isolate_cases || local || shared || start_paused {
+        let runtime = match (&builder, version) {
+            (Some(builder), _) => quote!(#builder()),
+            // the clock can only be paused on a current_thread runtime in tokio 1.x
+            (None, Some(TokioVersion::Modern)) if start_paused => quote! {
+                ::tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("failed to build runtime")
+            },
+            (None, _) => {
+                quote!(::tokio::runtime::Runtime::new().expect("failed to build runtime"))
+            }
+        };
+
+        let mut run = if local {
+            quote!(::tokio::task::LocalSet::new().run_until(#call))
+        } else {
+            call
+        };
+
+        if start_paused {
+            run = quote!(async {
+                ::tokio::time::pause();
+                #run.await
+            });
+        }
+
+        let block_on = if shared {
+            // keyed by the builder, so tests using different builders get different runtimes
+            let key = match &builder {
+                Some(builder) => quote!(::std::concat!(
+                    ::std::module_path!(),
+                    "::",
+                    ::std::stringify!(#builder)
+                )),
+                None => quote!(""),
+            };
+
+            quote! {
+                let rt: &'static ::tokio::runtime::Runtime =
+                    ::quickcheck_async::__private::shared(#key, || #runtime);
+                rt.handle().block_on(#run)
+            }
+        } else if isolate_cases || start_paused {
+            quote! {
+                #[allow(unused_mut)]
+                let mut rt: ::tokio::runtime::Runtime = #runtime;
+                rt.block_on(#run)
+            }
+        } else {
+            quote! {
+                ::std::thread_local! {
+                    static RUNTIME: ::std::cell::RefCell<::tokio::runtime::Runtime> =
+                        ::std::cell::RefCell::new(#runtime);
+                }
+
+                RUNTIME.with(|rt| {
+                    #[allow(unused_mut)]
+                    let mut rt = rt.borrow_mut();
+                    rt.block_on(#run)
+                })
+            }
+        };
+
+        return quote! (
+            #[test]
+            fn #call_by() {
+                #fn_item
+
+                let test_fn: fn(#tys) #ret = |#ids| {
+                    #block_on
+                };
+
+                #qc.quickcheck(test_fn);
+            }
+        )
+        .into();
+    }
+
+    let body = quote! {
+        #fn_item
+
+        // shrink candidates may be run on threads outside the runtime's context
+        static HANDLE: ::std::sync::Mutex<::std::option::Option<::tokio::runtime::Handle>> =
+            ::std::sync::Mutex::new(::std::option::Option::None);
+        *HANDLE.lock().unwrap_or_else(::std::sync::PoisonError::into_inner) =
+            ::std::option::Option::Some(::tokio::runtime::Handle::current());
+
+        // the lock is released before the case runs, and nothing is named where it's called
+        let test_fn: fn(#tys) #ret = |#ids| {
+            ({
+                let guard = HANDLE.lock().unwrap_or_else(::std::sync::PoisonError::into_inner);
+                guard.clone().expect("the runtime is running")
+            })
+            .block_on(#call)
+        };
+
+        // a failure is raised as is, rather than wrapped in a JoinError
+        if let Err(err) = ::tokio::task::spawn_blocking(move || {
+            #qc.quickcheck(test_fn)
+        })
+        .await
+        {
+            ::std::panic::resume_unwind(err.into_panic())
+        }
+    };
+
+    match builder {
+        Some(builder) => quote! (
+            #[test]
+            fn #call_by() {
+                #[allow(unused_mut)]
+                let mut rt: ::tokio::runtime::Runtime = #builder();
+                rt.block_on(async { #body })
+            }
+        ),
+
+        None => quote! (
+            #[::tokio::test(#attrib)]
+            async fn #call_by() {
+                #body
+            }
+        ),
+    }
+    .into()
+}
+
+/// Mark every async function within a module that returns `bool` or `TestResult` to be
+/// fuzz-tested as if by [tokio](macro@tokio), with the same arguments.
+///
+/// Functions that already carry a test attribute are left alone, as are functions with
+/// any other return type, so helpers can live alongside the properties.
+///
+/// ```
+/// #[quickcheck_async::tokio_mod(core_threads = 2)]
+/// mod fuzz {
+///     async fn fuzz_me(fuzz_arg: String) -> bool {
+///         fuzz_arg != "fuzzed".to_owned()
+///     }
+///
+///     async fn fuzz_me_too(fuzz_arg: u8) -> quickcheck::TestResult {
+///         quickcheck::TestResult::from_bool(fuzz_arg.checked_add(0).is_some())
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn tokio_mod(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut item_mod = parse_macro_input!(item as ItemMod);
+    let args = proc_macro2::TokenStream::from(args);
+
+    let items = match &mut item_mod.content {
+        Some((_, items)) => items,
+        None => {
+            return Error::new_spanned(item_mod, "tokio_mod requires an inline module")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    for item in items.iter_mut() {
+        let fn_item = match item {
+            Item::Fn(fn_item) if is_property(fn_item) => fn_item,
+            _ => continue,
+        };
+
+        let out = tokio(args.clone().into(), fn_item.to_token_stream().into());
+        *item = Item::Verbatim(out.into());
+    }
+
+    item_mod.into_token_stream().into()
+}
+
+fn is_property(fn_item: &ItemFn) -> bool {
+    let annotated = fn_item.attrs.iter().any(|attr| {
+        let first = attr.path.segments.first().map(|s| s.ident.to_string());
+        let last = attr.path.segments.last().map(|s| s.ident.to_string());
+        attr.path.is_ident("test")
+            || (first.as_deref() == Some("quickcheck_async")
+                && !is_filter(attr)
+                && !is_examples(attr))
+            || last.as_deref() == Some("test")
+    });
+
+    let returns = match &fn_item.sig.output {
+        ReturnType::Type(_, ty) => is_last_ident(ty, "bool") || is_last_ident(ty, "TestResult"),
+        ReturnType::Default => false,
+    };
+
+    fn_item.sig.asyncness.is_some() && !annotated && returns
+}
+
+struct TokioArgs {
+    builder: Option<Path>,
+    isolate_cases: bool,
+    local: bool,
+    shared: bool,
+    start_paused: bool,
+    version: Option<TokioVersion>,
+    attrib: Punctuated<NestedMeta, Comma>,
+}
+
+fn tokio_args(args: AttributeArgs) -> Result<TokioArgs, TokenStream> {
+    let mut out = TokioArgs {
+        builder: None,
+        isolate_cases: false,
+        local: false,
+        shared: false,
+        start_paused: false,
+        version: tokio_version(),
+        attrib: Punctuated::new(),
+    };
+
+    let mut shared = None;
+
+    for arg in args {
+        match &arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("builder") => {
+                out.builder = Some(lit_path(nv)?);
+            }
+
+            arg if is_flag(arg, "isolate_cases") => out.isolate_cases = true,
+
+            arg if is_flag(arg, "local") => out.local = true,
+
+            arg if is_flag(arg, "shared") => shared = Some(arg.clone()),
+
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("start_paused") => {
+                match &nv.lit {
+                    Lit::Bool(lit) => out.start_paused = lit.value,
+                    lit => {
+                        return Err(Error::new_spanned(lit, "start_paused must be a bool")
+                            .to_compile_error()
+                            .into())
+                    }
+                }
+            }
+
+            _ => out.attrib.push(arg),
+        }
+    }
+
+    if let Some(arg) = shared {
+        if out.isolate_cases || out.start_paused {
+            return Err(Error::new_spanned(
+                arg,
+                "shared cannot be combined with isolate_cases or start_paused",
+            )
+            .to_compile_error()
+            .into());
+        }
+
+        out.shared = true;
+    }
+
+    if let Some(version) = out.version {
+        out.attrib = translate_tokio_args(out.attrib, version)?;
+    }
+
+    if let (Some(_), Some(arg)) = (&out.builder, out.attrib.first()) {
+        return Err(
+            Error::new_spanned(arg, "runtime arguments cannot be combined with a builder")
+                .to_compile_error()
+                .into(),
+        );
+    }
+
+    let custom = out.isolate_cases || out.local || out.shared || out.start_paused;
+
+    if let (true, Some(arg)) = (custom, out.attrib.first()) {
+        return Err(Error::new_spanned(
+            arg,
+            "runtime arguments cannot be combined with isolate_cases, local, shared, or \
+             start_paused; use a builder",
+        )
+        .to_compile_error()
+        .into());
+    }
+
+    Ok(out)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TokioVersion {
+    /// tokio 0.2 and earlier: `core_threads`, `max_threads`, `basic_scheduler`, and
+    /// `threaded_scheduler`.
+    Legacy,
+    /// tokio 0.3 and later: `flavor` and `worker_threads`.
+    Modern,
+}
+
+/// Determines which version of tokio the crate being compiled depends on, by reading its
+/// manifest. Returns `None` if that cannot be determined.
+fn tokio_version() -> Option<TokioVersion> {
+    let dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR")?);
+    let manifest = read_manifest(&dir)?;
+
+    let mut tables = vec![
+        manifest.get("dependencies"),
+        manifest.get("dev-dependencies"),
+    ];
+
+    if let Some(targets) = manifest.get("target").and_then(toml::Value::as_table) {
+        for target in targets.values() {
+            tables.push(target.get("dependencies"));
+            tables.push(target.get("dev-dependencies"));
+        }
+    }
+
+    let (name, dep) = tables
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_table)
+        .flatten()
+        .find(
+            |(name, dep)| match dep.get("package").and_then(toml::Value::as_str) {
+                Some(package) => package == "tokio",
+                None => name.as_str() == "tokio",
+            },
+        )?;
+
+    let req = if dep.get("workspace").and_then(toml::Value::as_bool) == Some(true) {
+        // inherited from the workspace manifest, which is the nearest ancestor declaring one
+        dir.ancestors()
+            .skip(1)
+            .filter_map(read_manifest)
+            .find_map(|root| root.get("workspace").cloned())?
+            .get("dependencies")?
+            .get(name)?
+            .clone()
+    } else {
+        dep.clone()
+    };
+
+    let req = match &req {
+        toml::Value::String(req) => req.as_str(),
+        req => req.get("version")?.as_str()?,
+    };
+
+    let mut parts = req
+        .split(',')
+        .next()?
+        .trim_start_matches(|c: char| "^~=<> ".contains(c))
+        .split('.');
+
+    match (parts.next()?.parse::<u64>().ok()?, parts.next()) {
+        (0, Some(minor)) if minor.parse::<u64>().ok()? < 3 => Some(TokioVersion::Legacy),
+        _ => Some(TokioVersion::Modern),
+    }
+}
+
+fn read_manifest(dir: &std::path::Path) -> Option<toml::Value> {
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    manifest.parse().ok()
+}
+
+/// Rewrites the runtime arguments to a tokio test into the names used by `version`, so
+/// either set of names may be used regardless of which tokio is in use.
+fn translate_tokio_args(
+    attrib: Punctuated<NestedMeta, Comma>,
+    version: TokioVersion,
+) -> Result<Punctuated<NestedMeta, Comma>, TokenStream> {
+    let err = |span: &dyn ToTokens, msg: &str| -> TokenStream {
+        Error::new_spanned(span, msg).to_compile_error().into()
+    };
+
+    let mut flavor: Option<LitStr> = None;
+    let mut workers: Option<LitInt> = None;
+    let mut out = Punctuated::new();
+
+    for arg in attrib {
+        match &arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("flavor") => match &nv.lit {
+                Lit::Str(lit)
+                    if lit.value() == "multi_thread" || lit.value() == "current_thread" =>
+                {
+                    flavor = Some(lit.clone())
+                }
+                lit => {
+                    return Err(err(
+                        lit,
+                        r#"flavor must be "multi_thread" or "current_thread""#,
+                    ))
+                }
+            },
+
+            arg if is_flag(arg, "threaded_scheduler") => {
+                flavor = Some(LitStr::new("multi_thread", Span::call_site()))
+            }
+
+            arg if is_flag(arg, "basic_scheduler") => {
+                flavor = Some(LitStr::new("current_thread", Span::call_site()))
+            }
+
+            NestedMeta::Meta(Meta::NameValue(nv))
+                if nv.path.is_ident("worker_threads") || nv.path.is_ident("core_threads") =>
+            {
+                match &nv.lit {
+                    Lit::Int(lit) => workers = Some(lit.clone()),
+                    lit => return Err(err(lit, "worker_threads must be an int")),
+                }
+            }
+
+            NestedMeta::Meta(Meta::NameValue(nv))
+                if nv.path.is_ident("max_threads") && version == TokioVersion::Modern =>
+            {
+                return Err(err(
+                    nv,
+                    "max_threads is not supported by tokio 1.x; use a builder to set \
+                     max_blocking_threads",
+                ));
+            }
+
+            _ => out.push(arg),
+        }
+    }
+
+    let current_thread = flavor.as_ref().map(|lit| lit.value() == "current_thread");
+
+    if let (Some(true), Some(lit)) = (current_thread, &workers) {
+        return Err(err(
+            lit,
+            "worker_threads cannot be set with the current_thread flavor",
+        ));
+    }
+
+    match version {
+        TokioVersion::Legacy => {
+            match current_thread {
+                Some(true) => out.push(syn::parse_quote!(basic_scheduler)),
+                Some(false) => out.push(syn::parse_quote!(threaded_scheduler)),
+                None => {}
+            }
+
+            if let Some(lit) = workers {
+                out.push(syn::parse_quote!(core_threads = #lit));
+            }
+        }
+
+        TokioVersion::Modern => {
+            // tokio 0.2 picked the basic scheduler for core_threads = 1
+            let single = match &workers {
+                Some(lit) => {
+                    lit.base10_parse::<usize>()
+                        .map_err(|e| TokenStream::from(e.to_compile_error()))?
+                        <= 1
+                }
+                None => false,
+            };
+
+            let flavor = match (flavor, &workers) {
+                (None, Some(lit)) if single => Some(LitStr::new("current_thread", lit.span())),
+                (None, Some(lit)) => Some(LitStr::new("multi_thread", lit.span())),
+                (flavor, _) => flavor,
+            };
+
+            if let Some(lit) = flavor {
+                out.push(syn::parse_quote!(flavor = #lit));
+            }
+
+            match workers {
+                Some(lit) if !single || current_thread == Some(false) => {
+                    out.push(syn::parse_quote!(worker_threads = #lit))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within an async_std
+/// executor.
+///
+/// quickcheck itself runs on a blocking thread, and every generated case is driven by
+/// [async_std::task::block_on][bo], so async-std's tasks and timers behave as they would
+/// outside of tests.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::async_std]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [bo]: https://docs.rs/async-std/latest/async_std/task/fn.block_on.html
+#[proc_macro_attribute]
+pub fn async_std(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| async_std_test(args, item))
+}
+
+fn async_std_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "async_std") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    let attrib: Punctuated<NestedMeta, Comma> = p_args.into_iter().collect();
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[::async_std::test(#attrib)]
+        async fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                ::async_std::task::block_on(#call_by(#ids))
+            };
+
+            ::async_std::task::spawn_blocking(move || {
+                #qc.quickcheck(test_fn)
+            })
+            .await
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within a smol
+/// executor.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::smol]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+#[proc_macro_attribute]
+pub fn smol(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| smol_test(args, item))
+}
+
+fn smol_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "smol") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    if let Some(arg) = p_args.first() {
+        return Error::new_spanned(arg, "smol attribute does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                ::smol::block_on(#call_by(#ids))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within an actix
+/// system.
+///
+/// Each generated case is spawned onto the system arbiter, so [System::current][sc] and
+/// actix's spawning functions are available within the test fn.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::actix_rt]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// # Attribute arguments
+///
+/// Arguments to this attribute are passed through to [actix_rt::test][at].
+///
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [sc]: https://docs.rs/actix-rt/latest/actix_rt/struct.System.html#method.current
+/// [at]: https://docs.rs/actix-rt/latest/actix_rt/attr.test.html
+#[proc_macro_attribute]
+pub fn actix_rt(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| actix_rt_test(args, item))
+}
+
+fn actix_rt_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "actix_rt") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    let attrib: Punctuated<NestedMeta, Comma> = p_args.into_iter().collect();
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[::actix_rt::test(#attrib)]
+        async fn #call_by() {
+            #fn_item
+
+            // shrink candidates may be run on threads the system isn't current on
+            static SYSTEM: ::std::sync::Mutex<::std::option::Option<::actix_rt::System>> =
+                ::std::sync::Mutex::new(::std::option::Option::None);
+            *SYSTEM.lock().unwrap_or_else(::std::sync::PoisonError::into_inner) =
+                ::std::option::Option::Some(::actix_rt::System::current());
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                let (tx, rx) = ::futures::channel::oneshot::channel();
+
+                ({
+                    let guard = SYSTEM.lock().unwrap_or_else(::std::sync::PoisonError::into_inner);
+                    guard.clone().expect("the system is running")
+                })
+                .arbiter()
+                .spawn(async move {
+                    let out = ::quickcheck_async::__private::catch_unwind(#call_by(#ids));
+                    let _ = tx.send(out.await);
+                });
+
+                // panics are carried back, so they fail (or discard) the case as usual
+                match ::futures::executor::block_on(rx).expect("test fn did not complete") {
+                    Ok(out) => out,
+                    Err(payload) => ::std::panic::resume_unwind(payload),
+                }
+            };
+
+            let system = ::actix_rt::System::current();
+
+            // a failure is raised as is, rather than wrapped in a JoinError
+            if let Err(err) = ::actix_rt::task::spawn_blocking(move || {
+                ::actix_rt::System::set_current(system);
+                #qc.quickcheck(test_fn)
+            })
+            .await
+            {
+                ::std::panic::resume_unwind(err.into_panic())
+            }
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within a glommio
+/// [LocalExecutor][le].
+///
+/// One executor is built per test, and every generated case is driven on it from the
+/// test thread. The test fn is not required to be `Send`.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::glommio]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// # Attribute arguments
+///
+/// With `isolate_cases`, a new executor is built for every generated case instead, so
+/// tasks spawned by one case cannot affect another.
+///
+/// ```
+/// #[quickcheck_async::glommio(isolate_cases)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [le]: https://docs.rs/glommio/latest/glommio/struct.LocalExecutor.html
+#[proc_macro_attribute]
+pub fn glommio(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| glommio_test(args, item))
+}
+
+fn glommio_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "glommio") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let mut isolate_cases = false;
+
+    for arg in parse_macro_input!(args as AttributeArgs) {
+        if is_flag(&arg, "isolate_cases") {
+            isolate_cases = true;
+        } else {
+            return Error::new_spanned(arg, "unknown argument; expected `isolate_cases`")
+                .to_compile_error()
+                .into();
+        }
+    }
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    if isolate_cases {
+        return quote! (
+            #[test]
+            fn #call_by() {
+                #fn_item
+
+                let test_fn: fn(#tys) #ret = |#ids| {
+                    ::glommio::LocalExecutor::default().run(#call_by(#ids))
+                };
+
+                #qc.quickcheck(test_fn);
+            }
+        )
+        .into();
+    }
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            ::std::thread_local! {
+                static EXECUTOR: ::glommio::LocalExecutor = ::glommio::LocalExecutor::default();
+            }
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                EXECUTOR.with(|ex| ex.run(#call_by(#ids)))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+struct MonoioArgs {
+    builder: proc_macro2::TokenStream,
+    isolate_cases: bool,
+}
+
+fn monoio_args(args: AttributeArgs) -> Result<MonoioArgs, TokenStream> {
+    let err = |arg: &dyn quote::ToTokens, msg: &str| -> TokenStream {
+        Error::new_spanned(arg, msg).to_compile_error().into()
+    };
+
+    let mut driver = quote!(::monoio::FusionDriver);
+    let mut entries = None;
+    let mut timer = false;
+    let mut isolate_cases = false;
+
+    for arg in args {
+        match &arg {
+            arg if is_flag(arg, "isolate_cases") => isolate_cases = true,
+
+            NestedMeta::Meta(Meta::Path(path))
+                if path.is_ident("enable_timer") || path.is_ident("timer_enabled") =>
+            {
+                timer = true;
+            }
+
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("entries") => match &nv.lit {
+                Lit::Int(lit) => entries = Some(lit.clone()),
+                lit => return Err(err(lit, "entries must be an integer")),
+            },
+
+            NestedMeta::Meta(Meta::NameValue(nv))
+                if nv.path.is_ident("enable_timer") || nv.path.is_ident("timer_enabled") =>
+            {
+                match &nv.lit {
+                    Lit::Bool(lit) => timer = lit.value,
+                    lit => return Err(err(lit, "timer_enabled must be a bool")),
+                }
+            }
+
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("driver") => {
+                driver = match &nv.lit {
+                    Lit::Str(lit) if lit.value() == "fusion" => quote!(::monoio::FusionDriver),
+                    Lit::Str(lit) if lit.value() == "uring" => quote!(::monoio::IoUringDriver),
+                    Lit::Str(lit) if lit.value() == "legacy" => quote!(::monoio::LegacyDriver),
+                    lit => {
+                        return Err(err(
+                            lit,
+                            "driver must be one of \"fusion\", \"uring\", or \"legacy\"",
+                        ))
+                    }
+                };
+            }
+
+            _ => {
+                return Err(err(
+                    &arg,
+                    "unknown argument; expected one of `entries`, `timer_enabled`, `driver`, \
+                     `isolate_cases`",
+                ))
+            }
+        }
+    }
+
+    let mut builder = quote!(::monoio::RuntimeBuilder::<#driver>::new());
+
+    if let Some(entries) = entries {
+        builder = quote!(#builder.with_entries(#entries));
+    }
+
+    if timer {
+        builder = quote!(#builder.enable_timer());
+    }
+
+    Ok(MonoioArgs {
+        builder,
+        isolate_cases,
+    })
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within a monoio
+/// runtime.
+///
+/// One runtime is built per test, and every generated case is driven on it from the
+/// test thread. The test fn is not required to be `Send`.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::monoio]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// # Attribute arguments
+///
+/// The runtime can be configured with the same arguments accepted by [monoio::test][mt]:
+///
+/// * `entries = 256`: the size of the io_uring submission queue.
+/// * `timer_enabled = true` (or just `enable_timer`): enable the timer driver.
+/// * `driver = "fusion"`: one of `"fusion"`, `"uring"`, or `"legacy"`.
+///
+/// Additionally, `isolate_cases` builds a new runtime for every generated case, so
+/// tasks spawned by one case cannot affect another.
+///
+/// ```
+/// #[quickcheck_async::monoio(entries = 64, enable_timer, driver = "legacy")]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [mt]: https://docs.rs/monoio/latest/monoio/attr.test.html
+#[proc_macro_attribute]
+pub fn monoio(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| monoio_test(args, item))
+}
+
+fn monoio_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "monoio") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    let MonoioArgs {
+        builder,
+        isolate_cases,
+    } = match monoio_args(p_args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+    let out = output_ty(&fn_item);
+
+    if isolate_cases {
+        return quote! (
+            #[test]
+            fn #call_by() {
+                #fn_item
+
+                let test_fn: fn(#tys) #ret = |#ids| {
+                    #builder
+                        .build()
+                        .expect("failed to build monoio runtime")
+                        .block_on(#call_by(#ids))
+                };
+
+                #qc.quickcheck(test_fn);
+            }
+        )
+        .into();
+    }
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            type BlockOn = dyn FnMut(
+                ::std::pin::Pin<Box<dyn ::std::future::Future<Output = #out>>>,
+            ) -> #out;
+
+            ::std::thread_local! {
+                static RUNTIME: ::std::cell::RefCell<Box<BlockOn>> = {
+                    let mut rt = #builder.build().expect("failed to build monoio runtime");
+                    ::std::cell::RefCell::new(Box::new(move |fut| rt.block_on(fut)))
+                };
+            }
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                RUNTIME.with(|rt| (rt.borrow_mut())(Box::pin(#call_by(#ids))))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within a tokio-uring
+/// runtime.
+///
+/// Every generated case is wrapped in its own call to [tokio_uring::start][ts]. The test
+/// fn is not required to be `Send`.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::tokio_uring]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [ts]: https://docs.rs/tokio-uring/latest/tokio_uring/fn.start.html
+#[proc_macro_attribute]
+pub fn tokio_uring(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| tokio_uring_test(args, item))
+}
+
+fn tokio_uring_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "tokio_uring") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    if let Some(arg) = p_args.first() {
+        return Error::new_spanned(arg, "tokio_uring attribute does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                ::tokio_uring::start(#call_by(#ids))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], on the embassy
+/// executor's [block_on][bo].
+///
+/// This is intended for host-side testing of embedded async code, and requires the
+/// `embassy` feature.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::embassy]
+/// async fn fuzz_me(fuzz_arg: u8) -> bool {
+///     fuzz_arg != 42
+/// }
+/// ```
+///
+/// Embedded targets are often slow to simulate, so `tests` is worth lowering.
+///
+/// ```
+/// #[quickcheck_async::embassy(tests = 20)]
+/// async fn fuzz_me(fuzz_arg: u8) -> bool {
+///     fuzz_arg != 42
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [bo]: https://docs.rs/embassy-futures/latest/embassy_futures/fn.block_on.html
+#[cfg(feature = "embassy")]
+#[proc_macro_attribute]
+pub fn embassy(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| embassy_test(args, item))
+}
+
+#[cfg(feature = "embassy")]
+fn embassy_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "embassy") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    if let Some(arg) = p_args.first() {
+        return Error::new_spanned(arg, "embassy attribute does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                ::embassy_futures::block_on(#call_by(#ids))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within
+/// async-global-executor.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::global_executor]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+#[proc_macro_attribute]
+pub fn global_executor(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| global_executor_test(args, item))
+}
+
+fn global_executor_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(
+        parse_macro_input!(item as ItemFn),
+        &mut qc,
+        "global_executor",
+    ) {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    if let Some(arg) = p_args.first() {
+        return Error::new_spanned(arg, "global_executor attribute does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                ::async_global_executor::block_on(#call_by(#ids))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within the
+/// [futures][fe] executor.
+///
+/// This is useful for executor-agnostic code, as no runtime is required.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::futures]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [fe]: https://docs.rs/futures/latest/futures/executor/fn.block_on.html
+#[proc_macro_attribute]
+pub fn futures(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| futures_test(args, item))
+}
+
+fn futures_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "futures") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let p_args = parse_macro_input!(args as AttributeArgs);
+    if let Some(arg) = p_args.first() {
+        return Error::new_spanned(arg, "futures attribute does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                ::futures::executor::block_on(#call_by(#ids))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], as a
+/// [wasm_bindgen_test][wt].
+///
+/// Cases are generated and awaited one after another on the test's own task, so no
+/// threads or blocking are required. This makes it suitable for browser and node
+/// targets.
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::wasm]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// # Attribute arguments
+///
+/// Arguments to this attribute are passed through to [wasm_bindgen_test][wt].
+///
+/// ```
+/// #[quickcheck_async::wasm(unsupported = tokio::test)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+/// [wt]: https://docs.rs/wasm-bindgen-test/latest/wasm_bindgen_test/attr.wasm_bindgen_test.html
+#[proc_macro_attribute]
+pub fn wasm(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "wasm") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let attrib = proc_macro2::TokenStream::from(args);
+
+    // wasm_bindgen_test handles these itself, so they need to be on the outer fn.
+    let mut fn_item = fn_item;
+    let (hoisted, attrs) = fn_item.attrs.drain(..).partition(is_test_attr);
+    let hoisted: Vec<Attribute> = hoisted;
+    fn_item.attrs = attrs;
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ids: Vec<_> = ids.into_iter().collect();
+    let tys = tys.iter();
+
+    let check = if qc.exhaustive.is_some() {
+        quote!(exhaustive_local)
+    } else {
+        quote!(quickcheck_local)
+    };
+
+    quote! (
+        #[::wasm_bindgen_test::wasm_bindgen_test(#attrib)]
+        #(#hoisted)*
+        async fn #call_by() {
+            #fn_item
+
+            let config = #qc;
+
+            ::quickcheck_async::__private::#check(config, |(#(#ids,)*): (#(#tys,)*)| {
+                #call_by(#(#ids),*)
+            })
+            .await
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within any executor
+/// that provides a `block_on` function.
+///
+/// # Usage
+///
+/// The `block_on` argument is required, and should be a path to a function with a
+/// signature like `fn<F: Future>(F) -> F::Output`.
+///
+/// ```
+/// #[quickcheck_async::custom(block_on = "futures::executor::block_on")]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+#[proc_macro_attribute]
+pub fn custom(args: TokenStream, item: TokenStream) -> TokenStream {
+    hoist_test_attrs(item, |item| custom_test(args, item))
+}
+
+fn custom_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (mut qc, args) = match qc_args(args) {
+        Err(e) => return e,
+        Ok(args) => args,
+    };
+
+    let fn_item = match check_fn(parse_macro_input!(item as ItemFn), &mut qc, "custom") {
+        Err(e) => return e,
+        Ok(fn_item) => fn_item,
+    };
+
+    let mut block_on = None;
+
+    for arg in parse_macro_input!(args as AttributeArgs) {
+        match &arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("block_on") => {
+                match lit_path(nv) {
+                    Ok(path) => block_on = Some(path),
+                    Err(e) => return e,
+                }
+            }
+
+            _ => {
+                return Error::new_spanned(arg, "unknown argument; expected `block_on`")
+                    .to_compile_error()
+                    .into()
+            }
+        }
+    }
+
+    let block_on = match block_on {
+        None => {
+            return Error::new(Span::call_site(), "missing `block_on` argument")
+                .to_compile_error()
+                .into()
+        }
+        Some(path) => path,
+    };
+
+    let call_by = format_ident!("{}", fn_item.sig.ident);
+
+    let Arguments { ids, tys } = match parse_args(&fn_item) {
+        Err(e) => return e,
+        Ok(ts) => ts,
+    };
+
+    let ret = &fn_item.sig.output;
+
+    quote! (
+        #[test]
+        fn #call_by() {
+            #fn_item
+
+            let test_fn: fn(#tys) #ret = |#ids| {
+                #block_on(#call_by(#ids))
+            };
+
+            #qc.quickcheck(test_fn);
+        }
+    )
+    .into()
+}
+
+/// Mark an async function to be fuzz-tested using [quickcheck][qc], within the runtime
+/// selected by the `runtime` argument.
+///
+/// This is equivalent to using the attribute of the same name as the runtime, and any
+/// other arguments are passed through to it. Supported runtimes are `"tokio"`,
+/// `"async_std"`, `"smol"`, `"actix_rt"`, `"glommio"`, `"monoio"`, `"tokio_uring"`,
+/// `"global_executor"`, `"futures"`, `"wasm"`, `"custom"`, and `"embassy"` (with the
+/// `embassy` feature).
+///
+/// # Usage
+///
+/// ```
+/// #[quickcheck_async::test(runtime = "tokio", core_threads = 3)]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+///
+/// The runtime can be switched crate-wide by selecting it with `cfg_attr`:
+///
+/// ```
+/// #[cfg_attr(feature = "smol", quickcheck_async::test(runtime = "smol"))]
+/// #[cfg_attr(not(feature = "smol"), quickcheck_async::test(runtime = "async_std"))]
+/// async fn fuzz_me(fuzz_arg: String) -> bool {
+///     fuzz_arg != "fuzzed".to_owned()
+/// }
+/// ```
+/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut runtime = None;
+    let mut rest = Vec::new();
+
+    for arg in split_args(args.into()) {
+        let mut tts = arg.clone().into_iter();
+
+        match (tts.next(), tts.next(), tts.next(), tts.next()) {
+            (Some(TokenTree::Ident(id)), Some(TokenTree::Punct(eq)), Some(lit), None)
+                if id == "runtime" && eq.as_char() == '=' =>
+            {
+                match syn::parse2::<LitStr>(lit.into()) {
+                    Ok(lit) => runtime = Some(lit),
+                    Err(_) => {
+                        return Error::new_spanned(arg, "runtime must be a string")
+                            .to_compile_error()
+                            .into()
+                    }
+                }
+            }
+
+            _ => rest.push(arg),
+        }
+    }
+
+    let runtime = match runtime {
+        None => {
+            return Error::new(Span::call_site(), "missing `runtime` argument")
+                .to_compile_error()
+                .into()
+        }
+        Some(lit) => lit,
+    };
+
+    let args = quote!(#(#rest),*).into();
+
+    match runtime.value().as_str() {
+        "tokio" => tokio(args, item),
+        "async_std" => async_std(args, item),
+        "smol" => smol(args, item),
+        "actix_rt" => actix_rt(args, item),
+        "glommio" => glommio(args, item),
+        "monoio" => monoio(args, item),
+        "tokio_uring" => tokio_uring(args, item),
+        "global_executor" => global_executor(args, item),
+        "futures" => futures(args, item),
+        "wasm" => wasm(args, item),
+        "custom" => custom(args, item),
+        #[cfg(feature = "embassy")]
+        "embassy" => embassy(args, item),
+        _ => Error::new_spanned(runtime, "unknown runtime")
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// Split attribute arguments on top-level commas, without interpreting them.
+fn split_args(args: proc_macro2::TokenStream) -> Vec<proc_macro2::TokenStream> {
+    let mut out = vec![];
+    let mut cur = proc_macro2::TokenStream::new();
+
+    for tt in args {
+        match tt {
+            TokenTree::Punct(ref p) if p.as_char() == ',' => {
+                out.push(std::mem::take(&mut cur));
+            }
+            tt => cur.extend(Some(tt)),
+        }
+    }
+
+    if !cur.is_empty() {
+        out.push(cur);
+    }
+
+    out
+}
@@ -0,0 +1,69 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use quickcheck::Gen;
+
+/// The chars a string is generated from, for `#[alphabet(..)]` arguments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alphabet {
+    /// Any ASCII char.
+    Ascii,
+    /// ASCII letters and digits.
+    Alphanumeric,
+    /// Any char, mostly those encoded in several bytes.
+    Multibyte,
+    /// Arbitrary bytes, with those which aren't valid UTF-8 replaced by U+FFFD.
+    Lossy,
+}
+
+impl Alphabet {
+    /// Generate a string of up to the generator's size in chars, or bytes for
+    /// [Lossy](Alphabet::Lossy).
+    pub fn string<G: Gen>(self, g: &mut G) -> String {
+        let len = (g.next_u64() % (g.size() as u64 + 1)) as usize;
+
+        match self {
+            Alphabet::Ascii => (0..len)
+                .map(|_| (g.next_u32() % 0x80) as u8 as char)
+                .collect(),
+            Alphabet::Alphanumeric => {
+                const CHARS: &[u8] =
+                    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+                (0..len)
+                    .map(|_| CHARS[g.next_u32() as usize % CHARS.len()] as char)
+                    .collect()
+            }
+            Alphabet::Multibyte => (0..len).map(|_| multibyte(g)).collect(),
+            Alphabet::Lossy => {
+                let mut bytes = vec![0; len];
+                g.fill_bytes(&mut bytes);
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+        }
+    }
+
+    /// Whether every char of `s` could have been generated.
+    pub fn contains(self, s: &str) -> bool {
+        match self {
+            Alphabet::Ascii => s.is_ascii(),
+            Alphabet::Alphanumeric => s.bytes().all(|b| b.is_ascii_alphanumeric()),
+            Alphabet::Multibyte | Alphabet::Lossy => true,
+        }
+    }
+}
+
+/// A char encoded in 2, 3 or 4 bytes as often as each other, or occasionally in 1.
+fn multibyte<G: Gen>(g: &mut G) -> char {
+    let (lo, hi) = match g.next_u32() % 7 {
+        0 => (0, 0x7F),
+        1 | 2 => (0x80, 0x7FF),
+        3 | 4 => (0x800, 0xFFFF),
+        _ => (0x1_0000, 0x10_FFFF),
+    };
+
+    // surrogates aren't chars, so are replaced by the start of the range
+    char::from_u32(lo + g.next_u32() % (hi - lo + 1)).unwrap_or('\u{800}')
+}
@@ -0,0 +1,95 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use crate::{
+    choices::{Case, Lineage},
+    runner::Arguments,
+};
+use std::{any, fs, path::Path};
+
+impl Lineage {
+    /// The lines of a shrink checkpoint, led by the type of the arguments it holds.
+    fn save<A>(&self) -> String {
+        let join = |xs: Vec<String>| xs.join(" ");
+        format!(
+            "{}\nsize {}\nchoices {}\npath {}\n",
+            any::type_name::<A>(),
+            self.size,
+            join(self.choices.iter().map(u64::to_string).collect()),
+            join(self.path.iter().map(usize::to_string).collect()),
+        )
+    }
+
+    /// Read a shrink checkpoint made by [Lineage::save], if it holds arguments of type `A`.
+    fn load<A>(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != any::type_name::<A>() {
+            return None;
+        }
+        let mut field = |name: &str| {
+            let line = lines.next()?.strip_prefix(name)?;
+            Some(
+                line.split_whitespace()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let size = field("size")?.first()?.parse().ok()?;
+        let choices = field("choices")?
+            .iter()
+            .map(|c| c.parse().ok())
+            .collect::<Option<_>>()?;
+        let path = field("path")?
+            .iter()
+            .map(|i| i.parse().ok())
+            .collect::<Option<_>>()?;
+
+        Some(Lineage {
+            size,
+            choices,
+            path,
+        })
+    }
+}
+
+/// Save `case` to the shrink checkpoint at `path`, if one is set and the case was
+/// generated.
+pub fn checkpoint<A>(path: Option<&Path>, case: &Case<A>) {
+    let (path, lineage) = match (path, &case.lineage) {
+        (Some(path), Some(lineage)) => (path, lineage),
+        _ => return,
+    };
+
+    // written beside it first, so that being interrupted mid-write keeps the last one
+    let tmp = path.with_extension("tmp");
+    let saved = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(&tmp, lineage.save::<A>()))
+        .and_then(|()| fs::rename(&tmp, path));
+    if let Err(e) = saved {
+        eprintln!(
+            "[quickcheck_async] failed to save the shrink checkpoint {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// The case saved in the shrink checkpoint at `path`, if it holds arguments of type `A`.
+pub fn resume<A: Arguments>(path: &Path) -> Option<Case<A>> {
+    let case = fs::read_to_string(path)
+        .ok()
+        .and_then(|text| Lineage::load::<A>(&text))
+        .and_then(Case::replay);
+    if case.is_none() {
+        eprintln!(
+            "[quickcheck_async] no shrink checkpoint of these arguments to resume in {}",
+            path.display()
+        );
+    }
+    case
+}
@@ -0,0 +1,200 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use crate::runner::{shrink_args, Arguments, Generator};
+use rand_core::RngCore;
+use std::{
+    iter, mem,
+    sync::{Arc, Mutex},
+};
+
+/// An [RngCore] noting each value it draws from `rng`, as a choice made while
+/// generating. Bytes are drawn 8 at a time, so that each is one choice.
+struct Record {
+    rng: Box<dyn RngCore + Send>,
+    choices: Arc<Mutex<Vec<u64>>>,
+}
+
+impl Record {
+    fn note(&self, choice: u64) -> u64 {
+        let mut choices = self.choices.lock().unwrap_or_else(|e| e.into_inner());
+        choices.push(choice);
+        choice
+    }
+}
+
+impl RngCore for Record {
+    fn next_u32(&mut self) -> u32 {
+        let choice = self.rng.next_u32();
+        self.note(u64::from(choice)) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let choice = self.rng.next_u64();
+        self.note(choice)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let choice = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&choice[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// An [RngCore] making the given choices in turn, then zeros once they run out.
+struct Replay(std::vec::IntoIter<u64>);
+
+impl RngCore for Replay {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next().unwrap_or(0)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Where a generated case came from: the choices it was generated from at a size, then
+/// the candidates taken in turn while shrinking it by value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lineage {
+    pub size: usize,
+    pub choices: Vec<u64>,
+    pub path: Vec<usize>,
+}
+
+/// A case to shrink, with its lineage if it was generated rather than given as an example.
+#[derive(Clone)]
+pub struct Case<A> {
+    pub args: A,
+    pub lineage: Option<Lineage>,
+}
+
+impl<A: Arguments> Case<A> {
+    /// Generate arguments of size `size` from `rng`, noting the choices made.
+    pub fn generate(rng: Box<dyn RngCore + Send>, size: usize) -> Self {
+        let choices = Arc::new(Mutex::new(Vec::new()));
+        let mut g = Generator {
+            rng: Box::new(Record {
+                rng,
+                choices: choices.clone(),
+            }),
+            size,
+        };
+        let args = A::arbitrary(&mut g);
+        drop(g);
+
+        let choices = mem::take(&mut *choices.lock().unwrap_or_else(|e| e.into_inner()));
+        Case {
+            args,
+            lineage: Some(Lineage {
+                size,
+                choices,
+                path: Vec::new(),
+            }),
+        }
+    }
+
+    /// The case `lineage` leads to, generated again from its choices and shrunk along its
+    /// path, or `None` if the path leads nowhere, as the arguments shrink differently.
+    pub fn replay(lineage: Lineage) -> Option<Self> {
+        let replay = Box::new(Replay(lineage.choices.clone().into_iter()));
+        let mut args = Case::<A>::generate(replay, lineage.size).args;
+        for &i in &lineage.path {
+            args = shrink_args(&args).nth(i)?;
+        }
+
+        Some(Case {
+            args,
+            lineage: Some(lineage),
+        })
+    }
+
+    /// The arguments shrunk by value, as [shrink_args] shrinks them.
+    pub fn by_values(&self) -> Box<dyn Iterator<Item = Self>> {
+        let lineage = self.lineage.clone();
+        Box::new(shrink_args(&self.args).enumerate().map(move |(i, args)| {
+            let lineage = lineage.clone().map(|mut lineage| {
+                lineage.path.push(i);
+                lineage
+            });
+            Case { args, lineage }
+        }))
+    }
+
+    /// Arguments generated again from simpler choices than these. A candidate is only
+    /// made if the choices it consumed are fewer, or as many but smaller, so shrinking
+    /// always ends. Cases without choices, or already shrunk by value, shrink by value.
+    pub fn by_choices(&self) -> Box<dyn Iterator<Item = Self>> {
+        let lineage = match &self.lineage {
+            Some(lineage) if lineage.path.is_empty() => lineage.clone(),
+            _ => return self.by_values(),
+        };
+        Box::new(
+            simpler_choices(&lineage.choices).filter_map(move |choices| {
+                let case = Case::generate(Box::new(Replay(choices.into_iter())), lineage.size);
+                let new = &case.lineage.as_ref()?.choices;
+                let shorter = (new.len(), new) < (lineage.choices.len(), &lineage.choices);
+                shorter.then_some(case)
+            }),
+        )
+    }
+}
+
+/// Simpler sequences of choices than `choices`, with runs of them deleted, then runs set
+/// to zero, then single choices made smaller, from zero up.
+fn simpler_choices(choices: &[u64]) -> impl Iterator<Item = Vec<u64>> {
+    let choices = choices.to_vec();
+    let runs = |choices: Vec<u64>| {
+        [8, 4, 2, 1].iter().flat_map(move |&len| {
+            let choices = choices.clone();
+            (0..choices.len().saturating_sub(len - 1)).map(move |at| (choices.clone(), at, len))
+        })
+    };
+
+    let deleted = runs(choices.clone()).map(|(mut choices, at, len)| {
+        choices.drain(at..at + len);
+        choices
+    });
+    let zeroed = runs(choices.clone()).filter_map(|(mut choices, at, len)| {
+        let run = &mut choices[at..at + len];
+        if run.iter().all(|&c| c == 0) {
+            return None;
+        }
+        run.iter_mut().for_each(|c| *c = 0);
+        Some(choices)
+    });
+    let smaller = (0..choices.len()).flat_map(move |at| {
+        let (choices, c) = (choices.clone(), choices[at]);
+        // zero, then closer and closer to the choice, as a binary search would try
+        let values = iter::once(0).chain((1..64).map(move |k| c - (c >> k)));
+        let mut last = None;
+        values
+            .filter(move |&v| v < c && last.replace(v) != Some(v))
+            .map(move |v| {
+                let mut choices = choices.clone();
+                choices[at] = v;
+                choices
+            })
+    });
+
+    deleted.chain(zeroed).chain(smaller)
+}
@@ -0,0 +1,80 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use quickcheck::{Arbitrary, Gen};
+use std::iter;
+
+/// Types into which interesting values can be spliced while generating them, fuzzer
+/// style, for `#[dictionary(..)]` arguments.
+pub trait Dictionary: Arbitrary {
+    /// An interesting value, such as a magic number or a keyword.
+    type Entry;
+
+    /// Generate a value, which is often one of `entries` or made from several of them.
+    fn with_dictionary<G: Gen>(g: &mut G, entries: &[Self::Entry]) -> Self;
+}
+
+/// Pick one of `entries`, if any, one time in `n`.
+fn pick<'a, T, G: Gen>(g: &mut G, entries: &'a [T], n: u64) -> Option<&'a T> {
+    if entries.is_empty() || !g.next_u64().is_multiple_of(n) {
+        return None;
+    }
+    entries.get((g.next_u64() % entries.len() as u64) as usize)
+}
+
+macro_rules! dictionary_int {
+    ($($ty: ty),*) => {
+        $(impl Dictionary for $ty {
+            type Entry = $ty;
+
+            fn with_dictionary<G: Gen>(g: &mut G, entries: &[$ty]) -> Self {
+                match pick(g, entries, 4) {
+                    Some(&entry) => entry,
+                    None => <$ty>::arbitrary(g),
+                }
+            }
+        })*
+    };
+}
+
+dictionary_int!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+
+impl Dictionary for String {
+    type Entry = &'static str;
+
+    fn with_dictionary<G: Gen>(g: &mut G, entries: &[&'static str]) -> Self {
+        let mut out = String::arbitrary(g);
+
+        // each entry is spliced in at a char boundary, with a chance of another after it
+        while let Some(entry) = pick(g, entries, 2) {
+            let boundaries = out.char_indices().count() + 1;
+            let at = out
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(iter::once(out.len()))
+                .nth((g.next_u64() % boundaries as u64) as usize)
+                .unwrap_or(out.len());
+            out.insert_str(at, entry);
+        }
+
+        out
+    }
+}
+
+impl Dictionary for Vec<u8> {
+    type Entry = &'static [u8];
+
+    fn with_dictionary<G: Gen>(g: &mut G, entries: &[&'static [u8]]) -> Self {
+        let mut out = Vec::<u8>::arbitrary(g);
+
+        while let Some(entry) = pick(g, entries, 2) {
+            let at = (g.next_u64() % (out.len() as u64 + 1)) as usize;
+            out.splice(at..at, entry.iter().copied());
+        }
+
+        out
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use quickcheck::{Arbitrary, Gen};
+
+/// The special values a float may be generated as, for `#[floats(..)]` arguments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Floats {
+    /// Whether NaN may be generated.
+    pub nan: bool,
+    /// Whether positive and negative infinity may be generated.
+    pub infinite: bool,
+    /// Whether subnormal values may be generated.
+    pub subnormal: bool,
+}
+
+impl Floats {
+    /// Generate a float, which is one of the allowed special values one time in 10
+    /// for each of them.
+    pub fn float<F: Float, G: Gen>(self, g: &mut G) -> F {
+        match g.next_u32() % 10 {
+            0 if self.nan => F::NAN,
+            1 if self.infinite && g.next_u32().is_multiple_of(2) => F::INFINITY,
+            1 if self.infinite => F::NEG_INFINITY,
+            2 if self.subnormal => F::subnormal(g),
+            _ => F::arbitrary(g),
+        }
+    }
+
+    /// Whether `x` is either not special or one of the allowed special values.
+    pub fn allows<F: Float>(self, x: F) -> bool {
+        (self.nan || !x.is_nan())
+            && (self.infinite || !x.is_infinite())
+            && (self.subnormal || !x.is_subnormal())
+    }
+}
+
+/// `f32` and `f64`, for [Floats].
+pub trait Float: Arbitrary + Copy {
+    /// Not a number.
+    const NAN: Self;
+    /// Positive infinity.
+    const INFINITY: Self;
+    /// Negative infinity.
+    const NEG_INFINITY: Self;
+
+    /// Generate a subnormal value of either sign.
+    fn subnormal<G: Gen>(g: &mut G) -> Self;
+    /// Whether the value is NaN.
+    fn is_nan(self) -> bool;
+    /// Whether the value is positive or negative infinity.
+    fn is_infinite(self) -> bool;
+    /// Whether the value is subnormal.
+    fn is_subnormal(self) -> bool;
+}
+
+macro_rules! float {
+    ($($ty: ty: $bits: ty),*) => {
+        $(impl Float for $ty {
+            const NAN: Self = <$ty>::NAN;
+            const INFINITY: Self = <$ty>::INFINITY;
+            const NEG_INFINITY: Self = <$ty>::NEG_INFINITY;
+
+            fn subnormal<G: Gen>(g: &mut G) -> Self {
+                // a zero exponent with a nonzero mantissa
+                let mantissa = (1 << (<$ty>::MANTISSA_DIGITS - 1)) - 1;
+                let bits = (g.next_u64() as $bits) & mantissa;
+                let sign = (g.next_u64() as $bits) & !(<$bits>::MAX >> 1);
+                <$ty>::from_bits(sign | bits.max(1))
+            }
+
+            fn is_nan(self) -> bool {
+                <$ty>::is_nan(self)
+            }
+
+            fn is_infinite(self) -> bool {
+                <$ty>::is_infinite(self)
+            }
+
+            fn is_subnormal(self) -> bool {
+                <$ty>::is_subnormal(self)
+            }
+        })*
+    };
+}
+
+float!(f32: u32, f64: u64);
@@ -0,0 +1,205 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#[allow(unused_imports)]
+use quickcheck::{Arbitrary, Gen};
+
+/// Types from other crates which quickcheck_async can generate, as the orphan rule
+/// keeps them from implementing `Arbitrary`, for `#[foreign]` arguments.
+pub trait Foreign: Sized {
+    /// Generate a value.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self;
+    /// Simpler values than this one.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>>;
+}
+
+#[cfg(feature = "bytes")]
+impl Foreign for bytes::Bytes {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Vec::<u8>::arbitrary(g).into()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.to_vec().shrink().map(Self::from))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Foreign for bytes::BytesMut {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Vec::<u8>::arbitrary(g).as_slice().into()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.to_vec().shrink().map(|v| v.as_slice().into()))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Foreign for uuid::Uuid {
+    /// Generate the nil or max UUID one time in 8 each, or else random bits.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        match g.next_u32() % 8 {
+            0 => uuid::Uuid::nil(),
+            1 => uuid::Uuid::max(),
+            _ => uuid::Uuid::from_u64_pair(g.next_u64(), g.next_u64()),
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.as_u128().shrink().map(uuid::Uuid::from_u128))
+    }
+}
+
+#[cfg(feature = "http")]
+const HEADERS: &[&str] = &[
+    "accept",
+    "authorization",
+    "content-length",
+    "content-type",
+    "cookie",
+    "host",
+    "user-agent",
+    "x-request-id",
+];
+
+#[cfg(feature = "http")]
+const TOKEN: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789-._~";
+
+/// Up to `max` chars valid in a URI path segment and a header name.
+#[cfg(feature = "http")]
+fn token<G: Gen>(g: &mut G, max: usize) -> String {
+    let len = (g.next_u64() % (max as u64 + 1)) as usize;
+    (0..len)
+        .map(|_| TOKEN[g.next_u32() as usize % TOKEN.len()] as char)
+        .collect()
+}
+
+#[cfg(feature = "http")]
+impl Foreign for http::Uri {
+    /// Generate an absolute URI half the time, or else just a path, with a query one
+    /// time in 4.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let segments = (g.next_u64() % (g.size() as u64 / 10 + 1)) as usize;
+        let mut uri = String::new();
+
+        if g.next_u32().is_multiple_of(2) {
+            let scheme = if g.next_u32().is_multiple_of(2) {
+                "http"
+            } else {
+                "https"
+            };
+            uri = format!("{}://{}.example.com", scheme, token(g, 8).replace('.', "-"));
+            if g.next_u32().is_multiple_of(4) {
+                uri.push_str(&format!(":{}", g.next_u32() as u16));
+            }
+        }
+        for _ in 0..segments {
+            uri.push('/');
+            uri.push_str(&token(g, 8));
+        }
+        if segments == 0 {
+            uri.push('/');
+        }
+        if g.next_u32().is_multiple_of(4) {
+            uri.push_str(&format!("?{}={}", token(g, 8), token(g, 8)));
+        }
+
+        uri.parse().expect("generated URI is valid")
+    }
+
+    /// Drop the query, then the authority or just its port, then each path segment.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let path = self.path();
+        let origin = match (self.scheme_str(), self.authority()) {
+            (Some(scheme), Some(authority)) => format!("{}://{}", scheme, authority),
+            _ => String::new(),
+        };
+        let query = self.query().map(|q| format!("?{}", q)).unwrap_or_default();
+        let mut candidates = Vec::new();
+
+        if !query.is_empty() {
+            candidates.push(format!("{}{}", origin, path));
+        }
+        if let (Some(scheme), Some(authority)) = (self.scheme_str(), self.authority()) {
+            candidates.push(format!("{}{}", path, query));
+            if authority.port().is_some() {
+                let host = authority.host();
+                candidates.push(format!("{}://{}{}{}", scheme, host, path, query));
+            }
+        }
+        let segments: Vec<&str> = path.split('/').skip(1).collect();
+        for i in 0..segments.len() {
+            let mut rest = segments.clone();
+            rest.remove(i);
+            let shorter = format!("/{}", rest.join("/"));
+            if shorter != path {
+                candidates.push(format!("{}{}{}", origin, shorter, query));
+            }
+        }
+
+        Box::new(candidates.into_iter().filter_map(|uri| uri.parse().ok()))
+    }
+}
+
+#[cfg(feature = "http")]
+impl Foreign for http::HeaderMap {
+    /// Generate common headers, and others named `x-..`, with visible ASCII values.
+    /// A name may repeat.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let mut headers = http::HeaderMap::new();
+
+        for _ in 0..g.next_u64() % (g.size() as u64 / 10 + 1) {
+            let name = match g.next_u32() as usize % (HEADERS.len() + 1) {
+                i if i < HEADERS.len() => HEADERS[i].to_string(),
+                _ => format!("x-{}", token(g, 8)),
+            };
+            let len = g.next_u64() % (g.size() as u64 + 1);
+            let value: String = (0..len)
+                .map(|_| (b' ' + (g.next_u32() % 95) as u8) as char)
+                .collect();
+
+            if let (Ok(name), Ok(value)) = (
+                http::HeaderName::from_bytes(name.as_bytes()),
+                http::HeaderValue::from_str(value.trim()),
+            ) {
+                headers.append(name, value);
+            }
+        }
+
+        headers
+    }
+
+    /// Drop each header, then shorten each value.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let entries: Vec<_> = self.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        let rebuild = |entries: Vec<(http::HeaderName, http::HeaderValue)>| {
+            let mut headers = http::HeaderMap::new();
+            for (name, value) in entries {
+                headers.append(name, value);
+            }
+            headers
+        };
+
+        let mut candidates = Vec::new();
+        for i in 0..entries.len() {
+            let mut fewer = entries.clone();
+            fewer.remove(i);
+            candidates.push(rebuild(fewer));
+        }
+        for i in 0..entries.len() {
+            let value = entries[i].1.as_bytes();
+            if !value.is_empty() {
+                let mut shorter = entries.clone();
+                let half = value[..value.len() / 2].trim_ascii_end();
+                shorter[i].1 = http::HeaderValue::from_bytes(half).expect("prefix is valid");
+                candidates.push(rebuild(shorter));
+            }
+        }
+
+        Box::new(candidates.into_iter())
+    }
+}
@@ -0,0 +1,535 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use crate::net::Net;
+use quickcheck::{Arbitrary, Gen};
+use serde_json::{Map, Number, Value};
+use std::{
+    borrow::{Borrow, Cow},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+/// The schema every value conforms to.
+const ANY: &Value = &Value::Bool(true);
+
+/// How deeply `$ref`s may be followed, so recursive schemas terminate.
+const MAX_DEPTH: usize = 32;
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Generate a value conforming to the JSON Schema `schema`, for `#[schema(..)]`
+/// arguments.
+pub fn from_schema<S: Borrow<Value>, G: Gen>(schema: S, g: &mut G) -> Value {
+    let schema = schema.borrow();
+    let size = g.size();
+    generate(schema, schema, g, size, 0)
+}
+
+/// The candidates to shrink `from` to, which all conform to `schema`.
+pub fn shrink_schema<S: Borrow<Value>>(schema: S, from: &Value) -> impl Iterator<Item = Value> {
+    let schema = schema.borrow().clone();
+    candidates(&schema, &schema, from, 0)
+        .into_iter()
+        .filter(move |candidate| conforms(&schema, &schema, candidate, 0))
+}
+
+fn below<G: Gen>(g: &mut G, n: u64) -> u64 {
+    g.next_u64() % n.max(1)
+}
+
+/// `schema` with a `$ref` to a location in `root` followed, and the subschemas of an
+/// `allOf` merged into it.
+fn resolve<'a>(root: &'a Value, schema: &'a Value, depth: usize) -> Cow<'a, Value> {
+    let schema = match schema.get("$ref").and_then(Value::as_str) {
+        Some(path) if depth < MAX_DEPTH => match path.strip_prefix('#') {
+            Some(pointer) => root.pointer(pointer).unwrap_or(ANY),
+            None => ANY,
+        },
+        _ => schema,
+    };
+
+    let subschemas = match schema.get("allOf").and_then(Value::as_array) {
+        Some(subschemas) => subschemas,
+        None => return Cow::Borrowed(schema),
+    };
+
+    let mut merged = schema.as_object().cloned().unwrap_or_default();
+    merged.remove("allOf");
+    for subschema in subschemas {
+        let subschema = resolve(root, subschema, depth + 1);
+        for (key, value) in subschema.as_object().into_iter().flatten() {
+            match (merged.get_mut(key), value) {
+                (Some(Value::Object(into)), Value::Object(from)) if key == "properties" => {
+                    into.extend(from.clone());
+                }
+                (Some(Value::Array(into)), Value::Array(from)) if key == "required" => {
+                    into.extend(from.iter().cloned());
+                }
+                (Some(_), _) => {}
+                (None, _) => {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    Cow::Owned(Value::Object(merged))
+}
+
+/// The types `schema` allows, which are inferred from its keywords if not given.
+fn types(schema: &Value) -> Vec<&str> {
+    let has = |keys: &[&str]| keys.iter().any(|key| schema.get(key).is_some());
+
+    match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty],
+        Some(Value::Array(tys)) => tys.iter().filter_map(Value::as_str).collect(),
+        _ if has(&["properties", "required", "additionalProperties"]) => vec!["object"],
+        _ if has(&["items", "prefixItems", "minItems", "maxItems"]) => vec!["array"],
+        _ if has(&["minLength", "maxLength", "pattern", "format"]) => vec!["string"],
+        _ if has(&["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"]) => {
+            vec!["number"]
+        }
+        _ => vec![
+            "null", "boolean", "integer", "number", "string", "array", "object",
+        ],
+    }
+}
+
+fn generate<G: Gen>(root: &Value, schema: &Value, g: &mut G, size: usize, depth: usize) -> Value {
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+    let schema = resolve(root, schema, depth);
+    let schema = schema.as_ref();
+
+    if let Value::Bool(_) = schema {
+        return generate(root, &Value::Object(Map::new()), g, size.min(4), depth + 1);
+    }
+    if let Some(value) = schema.get("const") {
+        return value.clone();
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if !values.is_empty() {
+            return values[below(g, values.len() as u64) as usize].clone();
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(subschemas)) = schema.get(key) {
+            if !subschemas.is_empty() {
+                let i = below(g, subschemas.len() as u64) as usize;
+                return generate(root, &subschemas[i], g, size, depth + 1);
+            }
+        }
+    }
+
+    let types = types(schema);
+    if types.is_empty() {
+        return Value::Null;
+    }
+    match types[below(g, types.len() as u64) as usize] {
+        "boolean" => Value::Bool(g.next_u32().is_multiple_of(2)),
+        "integer" => integer(schema, g, size),
+        "number" => number(schema, g, size),
+        "string" => string(schema, g, size),
+        "array" => array(root, schema, g, size, depth),
+        "object" => object(root, schema, g, size, depth),
+        _ => Value::Null,
+    }
+}
+
+/// A bound on a number, and whether it's exclusive.
+type Bound = Option<(f64, bool)>;
+
+/// The lower and upper bounds of a number.
+fn bounds(schema: &Value) -> (Bound, Bound) {
+    let bound = |inclusive: &str, exclusive: &str| {
+        let at = schema.get(inclusive).and_then(Value::as_f64);
+        match schema.get(exclusive) {
+            Some(Value::Number(n)) => n.as_f64().map(|n| (n, true)),
+            // draft 4 marks the inclusive bound exclusive
+            Some(Value::Bool(true)) => at.map(|at| (at, true)),
+            _ => at.map(|at| (at, false)),
+        }
+    };
+
+    (
+        bound("minimum", "exclusiveMinimum"),
+        bound("maximum", "exclusiveMaximum"),
+    )
+}
+
+fn integer<G: Gen>(schema: &Value, g: &mut G, size: usize) -> Value {
+    let (lo, hi) = bounds(schema);
+    let lo = lo.map(|(lo, ex)| if ex { lo.floor() + 1. } else { lo.ceil() });
+    let hi = hi.map(|(hi, ex)| if ex { hi.ceil() - 1. } else { hi.floor() });
+    let size = size as f64;
+    let (lo, hi) = match (lo, hi) {
+        (Some(lo), Some(hi)) => (lo, hi),
+        (Some(lo), None) => (lo, lo + size),
+        (None, Some(hi)) => (hi - size, hi),
+        (None, None) => (-size, size),
+    };
+
+    // multiples are counted instead, so every one in the bounds is as likely
+    let step = match schema.get("multipleOf").and_then(Value::as_f64) {
+        Some(step) if step >= 1. && step.fract() == 0. => step,
+        _ => 1.,
+    };
+    let (lo, hi) = ((lo / step).ceil() as i64, (hi / step).floor() as i64);
+    if hi < lo {
+        return Value::from(lo.saturating_mul(step as i64));
+    }
+    let span = (hi as i128 - lo as i128 + 1).min(u64::MAX as i128) as u64;
+    let k = lo.saturating_add(below(g, span) as i64);
+    Value::from(k.saturating_mul(step as i64))
+}
+
+fn number<G: Gen>(schema: &Value, g: &mut G, size: usize) -> Value {
+    let (lo, hi) = bounds(schema);
+    let size = size as f64;
+    let (lo, hi) = match (lo, hi) {
+        (Some((lo, _)), Some((hi, _))) => (lo, hi),
+        (Some((lo, _)), None) => (lo, lo + size),
+        (None, Some((hi, _))) => (hi - size, hi),
+        (None, None) => (-size, size),
+    };
+
+    let unit = g.next_u64() as f64 / u64::MAX as f64;
+    let mut x = lo + (hi - lo) * unit;
+    if let Some(step) = schema.get("multipleOf").and_then(Value::as_f64) {
+        if step > 0. {
+            x = (x / step).round() * step;
+        }
+    }
+    // landing on an exclusive bound is vanishingly rare, so the midpoint stands in
+    if !conforms(schema, schema, &Value::from(x), 0) {
+        x = lo + (hi - lo) / 2.;
+    }
+
+    Number::from_f64(x).map_or(Value::Null, Value::Number)
+}
+
+fn word<G: Gen>(g: &mut G, len: usize) -> String {
+    (0..len)
+        .map(|_| ALPHANUMERIC[below(g, ALPHANUMERIC.len() as u64) as usize] as char)
+        .collect()
+}
+
+fn string<G: Gen>(schema: &Value, g: &mut G, size: usize) -> Value {
+    let format = schema.get("format").and_then(Value::as_str);
+    let mut n = |max: u64| below(g, max);
+    let formatted = match format {
+        Some("date-time") => Some(format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            1970 + n(100),
+            1 + n(12),
+            1 + n(28),
+            n(24),
+            n(60),
+            n(60),
+        )),
+        Some("date") => Some(format!(
+            "{:04}-{:02}-{:02}",
+            1970 + n(100),
+            1 + n(12),
+            1 + n(28)
+        )),
+        Some("time") => Some(format!("{:02}:{:02}:{:02}Z", n(24), n(60), n(60))),
+        Some("uuid") => Some(format!(
+            "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+            n(1 << 32),
+            n(1 << 16),
+            n(1 << 12),
+            0x8000 | n(1 << 14),
+            n(1 << 48),
+        )),
+        Some("email") => Some(format!("{}@example.com", word(g, 1 + size.min(16)))),
+        Some("hostname") => Some(format!("{}.example.com", word(g, 1 + size.min(16)))),
+        Some("uri") => Some(format!("https://example.com/{}", word(g, size.min(16)))),
+        Some("ipv4") => Some(Ipv4Addr::net(g).to_string()),
+        Some("ipv6") => Some(Ipv6Addr::net(g).to_string()),
+        _ => None,
+    };
+    if let Some(formatted) = formatted {
+        return Value::String(formatted);
+    }
+
+    #[cfg(feature = "regex")]
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        return Value::String(crate::runner::from_regex(pattern, g).into_inner());
+    }
+
+    let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0);
+    let max = schema.get("maxLength").and_then(Value::as_u64);
+    let max = max.unwrap_or(u64::MAX).min(min + size as u64).max(min);
+    let len = min + below(g, max - min + 1);
+    Value::String(word(g, len as usize))
+}
+
+/// The schema of the item at `i` of an array.
+fn item(schema: &Value, i: usize) -> &Value {
+    let prefix = schema
+        .get("prefixItems")
+        .or_else(|| schema.get("items").filter(|items| items.is_array()))
+        .and_then(Value::as_array);
+
+    match prefix {
+        Some(prefix) if i < prefix.len() => &prefix[i],
+        Some(_) => schema
+            .get("additionalItems")
+            .or_else(|| schema.get("items").filter(|items| !items.is_array()))
+            .unwrap_or(ANY),
+        None => schema.get("items").unwrap_or(ANY),
+    }
+}
+
+fn array<G: Gen>(root: &Value, schema: &Value, g: &mut G, size: usize, depth: usize) -> Value {
+    let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+    let max = schema.get("maxItems").and_then(Value::as_u64);
+    let max = max.unwrap_or(u64::MAX).min(min + size as u64).max(min);
+    let len = min + below(g, max - min + 1);
+    let unique = schema.get("uniqueItems") == Some(&Value::Bool(true));
+
+    let mut items = Vec::new();
+    'items: for i in 0..len as usize {
+        // a few tries are made at an item not yet in a unique array
+        for _ in 0..10 {
+            let value = generate(root, item(schema, i), g, size / 2, depth + 1);
+            if !unique || !items.contains(&value) {
+                items.push(value);
+                continue 'items;
+            }
+        }
+        break;
+    }
+
+    Value::Array(items)
+}
+
+fn object<G: Gen>(root: &Value, schema: &Value, g: &mut G, size: usize, depth: usize) -> Value {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let required: Vec<&str> = match schema.get("required") {
+        Some(Value::Array(keys)) => keys.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    // optional properties are each present half the time
+    let mut object = Map::new();
+    for (key, subschema) in properties.into_iter().flatten() {
+        if required.contains(&key.as_str()) || g.next_u32().is_multiple_of(2) {
+            let value = generate(root, subschema, g, size / 2, depth + 1);
+            object.insert(key.clone(), value);
+        }
+    }
+    for key in required {
+        if !object.contains_key(key) {
+            object.insert(key.into(), generate(root, ANY, g, size / 2, depth + 1));
+        }
+    }
+
+    if let Some(additional @ Value::Object(_)) = schema.get("additionalProperties") {
+        for _ in 0..below(g, size.min(3) as u64 + 1) {
+            let key = word(g, 1 + size.min(8));
+            if properties.is_none_or(|properties| !properties.contains_key(&key)) {
+                let value = generate(root, additional, g, size / 2, depth + 1);
+                object.insert(key, value);
+            }
+        }
+    }
+
+    Value::Object(object)
+}
+
+/// Whether `value` conforms to `schema`, as far as the keywords generated from are
+/// concerned. Formats and patterns are taken to match.
+fn conforms(root: &Value, schema: &Value, value: &Value, depth: usize) -> bool {
+    if depth > MAX_DEPTH {
+        return true;
+    }
+    let schema = resolve(root, schema, depth);
+    let schema = schema.as_ref();
+    let conforms = |schema, value| conforms(root, schema, value, depth + 1);
+
+    if let Value::Bool(any) = schema {
+        return *any;
+    }
+    if let Some(expected) = schema.get("const") {
+        return value == expected;
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return values.contains(value);
+    }
+    if let Some(Value::Array(subschemas)) = schema.get("anyOf") {
+        if !subschemas
+            .iter()
+            .any(|subschema| conforms(subschema, value))
+        {
+            return false;
+        }
+    }
+    if let Some(Value::Array(subschemas)) = schema.get("oneOf") {
+        if subschemas.iter().filter(|s| conforms(s, value)).count() != 1 {
+            return false;
+        }
+    }
+
+    let typed = |ty: &str| match (ty, value) {
+        ("null", Value::Null) | ("boolean", Value::Bool(_)) => true,
+        ("number", Value::Number(_)) | ("string", Value::String(_)) => true,
+        ("array", Value::Array(_)) | ("object", Value::Object(_)) => true,
+        ("integer", Value::Number(n)) => n.as_f64().is_some_and(|n| n.fract() == 0.),
+        _ => false,
+    };
+    if schema.get("type").is_some() && !types(schema).into_iter().any(typed) {
+        return false;
+    }
+
+    match value {
+        Value::Number(n) => {
+            let x = n.as_f64().unwrap_or(0.);
+            let (lo, hi) = bounds(schema);
+            let above = lo.is_none_or(|(lo, ex)| if ex { x > lo } else { x >= lo });
+            let below = hi.is_none_or(|(hi, ex)| if ex { x < hi } else { x <= hi });
+            let step = schema.get("multipleOf").and_then(Value::as_f64);
+            let multiple = step.is_none_or(|step| {
+                let k = x / step;
+                (k - k.round()).abs() < 1e-9
+            });
+            above && below && multiple
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0);
+            let max = schema.get("maxLength").and_then(Value::as_u64);
+            len >= min && max.is_none_or(|max| len <= max)
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let max = schema.get("maxItems").and_then(Value::as_u64);
+            let unique = schema.get("uniqueItems") != Some(&Value::Bool(true))
+                || items
+                    .iter()
+                    .enumerate()
+                    .all(|(i, item)| !items[..i].contains(item));
+            len >= min
+                && max.is_none_or(|max| len <= max)
+                && unique
+                && items
+                    .iter()
+                    .enumerate()
+                    .all(|(i, value)| conforms(item(schema, i), value))
+        }
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties").unwrap_or(ANY);
+            let required = match schema.get("required") {
+                Some(Value::Array(keys)) => keys.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            required.into_iter().all(|key| object.contains_key(key))
+                && object.iter().all(|(key, value)| {
+                    match properties.and_then(|properties| properties.get(key)) {
+                        Some(subschema) => conforms(subschema, value),
+                        None => conforms(additional, value),
+                    }
+                })
+        }
+        Value::Null | Value::Bool(_) => true,
+    }
+}
+
+/// Simpler values than `value`, which may not conform to `schema`.
+fn candidates(root: &Value, schema: &Value, value: &Value, depth: usize) -> Vec<Value> {
+    if depth > MAX_DEPTH {
+        return Vec::new();
+    }
+    let schema = resolve(root, schema, depth);
+    let schema = schema.as_ref();
+    let candidates = |schema, value| candidates(root, schema, value, depth + 1);
+
+    if schema.get("const").is_some() {
+        return Vec::new();
+    }
+    // earlier values of an enum are taken to be simpler
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        let i = values.iter().position(|v| v == value).unwrap_or(0);
+        return values[..i].to_vec();
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(subschemas)) = schema.get(key) {
+            return subschemas
+                .iter()
+                .filter(|subschema| conforms(root, subschema, value, depth + 1))
+                .flat_map(|subschema| candidates(subschema, value))
+                .collect();
+        }
+    }
+
+    match value {
+        Value::Null | Value::Bool(false) => Vec::new(),
+        Value::Bool(true) => vec![Value::Bool(false)],
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(x), _) => x.shrink().map(Value::from).collect(),
+            (None, Some(x)) => x
+                .shrink()
+                .filter_map(Number::from_f64)
+                .map(Value::Number)
+                .collect(),
+            (None, None) => Vec::new(),
+        },
+        // formatted strings and matches of a pattern would break if taken apart
+        Value::String(_) if schema.get("format").is_some() || schema.get("pattern").is_some() => {
+            Vec::new()
+        }
+        Value::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            (0..chars.len())
+                .map(|i| {
+                    let (before, after) = (&chars[..i], &chars[i + 1..]);
+                    Value::String(before.iter().chain(after).collect())
+                })
+                .collect()
+        }
+        Value::Array(items) => {
+            let removed = (0..items.len()).map(|i| {
+                let mut items = items.clone();
+                items.remove(i);
+                Value::Array(items)
+            });
+            let shrunk = items.iter().enumerate().flat_map(|(i, value)| {
+                candidates(item(schema, i), value)
+                    .into_iter()
+                    .map(move |v| {
+                        let mut items = items.clone();
+                        items[i] = v;
+                        Value::Array(items)
+                    })
+            });
+            removed.chain(shrunk).collect()
+        }
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties").unwrap_or(ANY);
+            let removed = object.keys().map(|key| {
+                let mut object = object.clone();
+                object.remove(key);
+                Value::Object(object)
+            });
+            let shrunk = object.iter().flat_map(|(key, value)| {
+                let subschema = properties
+                    .and_then(|properties| properties.get(key))
+                    .unwrap_or(additional);
+                candidates(subschema, value).into_iter().map(move |v| {
+                    let mut object = object.clone();
+                    object.insert(key.clone(), v);
+                    Value::Object(object)
+                })
+            });
+            removed.chain(shrunk).collect()
+        }
+    }
+}
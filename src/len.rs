@@ -0,0 +1,120 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use quickcheck::{Arbitrary, Gen};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+/// Collections that can be generated with a given length, for `#[len(..)]` arguments.
+pub trait WithLen: Arbitrary {
+    /// Generate a value of `len` elements, or fewer if a set or map runs out of distinct
+    /// keys to draw.
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self;
+
+    /// The number of elements in `self`.
+    fn length(&self) -> usize;
+}
+
+impl<T: Arbitrary> WithLen for Vec<T> {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        (0..len).map(|_| T::arbitrary(g)).collect()
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Arbitrary> WithLen for VecDeque<T> {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        (0..len).map(|_| T::arbitrary(g)).collect()
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl WithLen for String {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        (0..len).map(|_| char::arbitrary(g)).collect()
+    }
+
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+// keys may repeat, so a few draws are allowed for each one kept
+impl<T: Arbitrary + Eq + Hash> WithLen for HashSet<T> {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        let mut out = Self::new();
+        for _ in 0..len.saturating_mul(10) {
+            if out.len() == len {
+                break;
+            }
+            out.insert(T::arbitrary(g));
+        }
+        out
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Arbitrary + Ord> WithLen for BTreeSet<T> {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        let mut out = Self::new();
+        for _ in 0..len.saturating_mul(10) {
+            if out.len() == len {
+                break;
+            }
+            out.insert(T::arbitrary(g));
+        }
+        out
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K: Arbitrary + Eq + Hash, V: Arbitrary> WithLen for HashMap<K, V> {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        let mut out = Self::new();
+        for _ in 0..len.saturating_mul(10) {
+            if out.len() == len {
+                break;
+            }
+            out.insert(K::arbitrary(g), V::arbitrary(g));
+        }
+        out
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K: Arbitrary + Ord, V: Arbitrary> WithLen for BTreeMap<K, V> {
+    fn with_len<G: Gen>(g: &mut G, len: usize) -> Self {
+        let mut out = Self::new();
+        for _ in 0..len.saturating_mul(10) {
+            if out.len() == len {
+                break;
+            }
+            out.insert(K::arbitrary(g), V::arbitrary(g));
+        }
+        out
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
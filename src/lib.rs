@@ -5,172 +5,622 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 //! Runtime-agnostic attribute macros to use quickcheck with async tests.
+//!
+//! Test fns are usually async, but may also be plain fns returning `impl Future`, for
+//! properties built from combinators.
+//!
+//! Test fns may return `bool`, `()`, [quickcheck::TestResult], an `Option` of any of these
+//! where `None` discards the case, or a `Result` of any of these whose error implements
+//! [Display](std::fmt::Display), as well as any type
+//! implementing [AsyncTestable]. An `Err` fails the case as a runtime error, and is shown
+//! alongside its arguments, so fallible async code can use `?` throughout. For a
+//! `Result<bool, E>`, `Ok(true)` passes, `Ok(false)` falsifies the property, and `Err`
+//! reports the error. A panic fails the case too, and is reported along with where it
+//! was raised, and a backtrace if `RUST_BACKTRACE` is set. For failures reported with the
+//! failing expression and context, see [prop_assert!].
+//! With the `anyhow` or `eyre` features, errors from those crates are shown with their
+//! full chain of causes and any backtrace they captured.
+//!
+//! ```
+//! #[quickcheck_async::tokio]
+//! async fn round_trip(x: u16) -> Result<bool, std::num::ParseIntError> {
+//!     Ok(x.to_string().parse::<u16>()? == x)
+//! }
+//! ```
+//!
+//! A test fn generic over one type can be tested with each of several, given by
+//! `types(..)` on its attribute. This expands to a module named for the fn, holding a
+//! test for each type, such as `reverse::vec_u32` for `Vec<u32>`. Arguments of an
+//! `impl Trait` type are treated as type parameters, in order, and a fn with more than
+//! one is given a tuple of types for each test.
+//!
+//! ```
+//! use quickcheck::Arbitrary;
+//! use std::fmt::Debug;
+//!
+//! #[quickcheck_async::tokio(types(u8, String, Vec<u32>))]
+//! async fn reverse<T: Arbitrary + Debug + PartialEq>(v: Vec<T>) -> bool {
+//!     let mut w: Vec<_> = v.iter().rev().collect();
+//!     w.reverse();
+//!     w.into_iter().eq(v.iter())
+//! }
+//! ```
+//!
+//! Any number of arguments may be taken. Beyond eight, the rest are generated as a tuple,
+//! so they are shown nested in a failing case's arguments.
+//!
+//! Arguments taken by reference are generated as owned values and borrowed, so a `&str`
+//! is borrowed from a generated `String`, a `&[T]` from a `Vec<T>`, and any other `&T`
+//! from a `T`.
+//!
+//! ```
+//! #[quickcheck_async::tokio]
+//! async fn lines(s: &str, bytes: &[u8]) -> bool {
+//!     s.lines().count() <= s.len() && bytes.iter().count() == bytes.len()
+//! }
+//! ```
+//!
+//! An argument marked `#[gen(path)]` is generated by calling `path` with the
+//! [quickcheck::Gen] in use, rather than through its [Arbitrary](quickcheck::Arbitrary)
+//! impl, which its type needn't have. This allows properties over foreign types, such
+//! as those from `chrono` or `uuid`, without a newtype to get around the orphan rule; they
+//! need only be `Clone`, `Debug` and `Send`. Values made this way are not shrunk.
+//!
+//! ```
+//! use quickcheck::{Arbitrary, Gen};
+//!
+//! fn even<G: Gen>(g: &mut G) -> u32 {
+//!     u32::arbitrary(g) & !1
+//! }
+//!
+//! #[quickcheck_async::tokio]
+//! async fn halves(#[gen(even)] x: u32) -> bool {
+//!     x / 2 * 2 == x
+//! }
+//! ```
+//!
+//! Similarly, an integer or float argument marked `#[range(lo..hi)]` is generated
+//! uniformly within the range given, rather than discarding the cases outside it. These
+//! are shrunk as usual, but only to values within the range.
+//!
+//! ```
+//! #[quickcheck_async::tokio]
+//! async fn percentages(#[range(0..=100)] p: u8, #[range(0.0..1.0)] x: f64) -> bool {
+//!     p <= 100 && (0.0..1.0).contains(&x)
+//! }
+//! ```
+//!
+//! Any of these may be paired with `#[shrink(path)]`, or it may be used alone, to shrink an
+//! argument by calling `path` with a reference to it rather than as its type would. This
+//! lets a shrinker know the domain, such as deleting whole transactions from a command
+//! log. It returns the candidates to try, as anything iterable. An argument marked
+//! `#[no_shrink]` instead keeps the value it failed with, such as a seed determining an
+//! external fixture, while the other arguments shrink as usual. [register_shrinker]
+//! shrinks every argument of a type this way, without marking each.
+//!
+//! An argument marked `#[shrink_toward(target)]` shrinks towards `target` rather than
+//! zero, so a counterexample holds the simplest value that makes sense in the domain,
+//! such as a count of 1 or a timeout of a millisecond. This works for integers, floats
+//! and `Duration`s.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! #[quickcheck_async::tokio]
+//! async fn backoff(
+//!     #[shrink_toward(1)] retries: u8,
+//!     #[shrink_toward(Duration::from_millis(1))] delay: Duration,
+//! ) -> bool {
+//!     retries == 0 || delay.saturating_mul(u32::from(retries)) >= delay
+//! }
+//! ```
+//!
+//! ```
+//! fn drop_each(log: &Vec<u8>) -> Vec<Vec<u8>> {
+//!     (0..log.len())
+//!         .map(|i| [&log[..i], &log[i + 1..]].concat())
+//!         .collect()
+//! }
+//!
+//! #[quickcheck_async::tokio]
+//! async fn replay(#[shrink(drop_each)] log: Vec<u8>) -> bool {
+//!     log.iter().map(|&x| u64::from(x)).sum::<u64>() < 256 * 1000
+//! }
+//! ```
+//!
+//! An argument marked `#[size(n)]` is generated with a size of `n` rather than the
+//! `gen_size` the others use, alone or along with any other generator, so a property
+//! can take a tiny config value beside a large payload.
+//!
+//! ```
+//! #[quickcheck_async::tokio(gen_size = 1000)]
+//! async fn split(#[size(4)] n: usize, payload: Vec<u8>) -> bool {
+//!     n <= 4 && payload.chunks(n.max(1)).map(|c| c.len()).sum::<usize>() == payload.len()
+//! }
+//! ```
+//!
+//! An argument marked `#[dependent(f)]` is generated after the others, by calling `f`
+//! with a [Generator], so it may depend on their values. Cases which would otherwise be
+//! discarded, such as an index beyond the end of a list, needn't be generated at all.
+//! Such arguments are reported as the seed of their generator, and aren't passed to
+//! filters, but follow the other arguments as they shrink.
+//!
+//! ```
+//! use quickcheck::Arbitrary;
+//!
+//! #[quickcheck_async::tokio]
+//! async fn index(
+//!     #[len(1..100)] items: Vec<u8>,
+//!     #[dependent(|g| usize::arbitrary(g) % items.len())] i: usize,
+//! ) -> bool {
+//!     items.get(i).is_some()
+//! }
+//! ```
+//!
+//! A `Vec`, `VecDeque`, `String`, set or map argument marked `#[len(0..=16)]` has a
+//! length drawn from the range given, independent of the generator's size, so a small
+//! control structure can be mixed with a large payload. Its elements are generated at the
+//! usual size, and it only shrinks to lengths within the range.
+//!
+//! A `String` argument marked `#[alphabet(ascii)]`, `#[alphabet(alphanumeric)]`,
+//! `#[alphabet(multibyte)]` or `#[alphabet(lossy)]` is generated from ASCII, from ASCII
+//! letters and digits, mostly from chars encoded in several bytes, or from random bytes
+//! converted lossily to UTF-8. Text protocols often need a restricted alphabet to get
+//! past their framing, while their decoders need multibyte and invalid input.
+//!
+//! An `f32` or `f64` argument is only ever a finite, normal value (or zero) by
+//! default. Marking it `#[floats(nan, infinite, subnormal)]`, or with any of those,
+//! opts in to NaN, infinities or subnormal values, each one case in 10. Marking it
+//! `#[floats()]` makes ruling them out explicit, for pipelines which can't take them.
+//!
+//! A `Duration` or `Instant` argument marked `#[time]` is generated in a unit picked
+//! at random, from nanoseconds to days, so both tiny and long timeouts turn up, and
+//! shrinks towards zero or the first instant generated. With the `chrono` or `time`
+//! features, their durations, dates and date times (in UTC or naive) can be marked
+//! `#[time]` too, and are generated within a century or so of 2000.
+//!
+//! An `IpAddr`, `Ipv4Addr`, `Ipv6Addr` or socket address argument marked `#[net]` is
+//! in a special purpose block, such as loopback, private or multicast, about half the
+//! time, and its port is often well known or on a boundary. [Cidr] blocks can be
+//! generated as they are, and a [FreePort] fixture gives each case a localhost port of
+//! its own.
+//!
+//! An integer, `String` or `Vec<u8>` argument marked `#[dictionary(["GET", "\r\n"])]`
+//! often has values from the dictionary given spliced in, or, for an integer, is often
+//! one of them. Magic numbers, reserved keywords and protocol tokens this way reach
+//! deeper into a parser than random values would. The dictionary may be any expression
+//! borrowing as a slice, such as a `const` array.
+//!
+//! An enum argument marked `#[weights(A(u8) = 80, B = 15, C { len: u16 } = 5)]` is
+//! generated as each variant named in proportion to its weight, and never as a variant
+//! left out, so rare but important variants appear at a controlled rate. A weight of zero
+//! is rejected, since leaving the variant out says the same. Each variant is given with
+//! the types of its fields, and built from their `Arbitrary` impls, so the enum needs
+//! none of its own. It shrinks by shrinking those fields, keeping its variant.
+//!
+//! With the `regex` feature, a `String` argument marked
+//! `#[matches("[a-z]{1,8}@example\\.com")]` is generated to match the regex given, so
+//! parsers and protocol handlers see input they accept rather than rejecting random
+//! unicode outright. It shrinks towards shorter and simpler matches. Anchors and word
+//! boundaries are ignored.
+//!
+//! With the `json-schema` feature, a `serde_json::Value` argument marked
+//! `#[schema(s)]` conforms to the JSON Schema `s`, such as a `json!({..})` literal, so
+//! HTTP handlers see realistic request bodies. Types, `const`, `enum`, `anyOf`, `oneOf`,
+//! `allOf`, local `$ref`s, numeric bounds, lengths, item and property schemas, and
+//! common formats are followed, as are patterns with the `regex` feature. Values shrink
+//! by dropping optional properties and items and simplifying what's left, while still
+//! conforming.
+//!
+//! With the `prost` feature, a protobuf message argument marked `#[message]` is
+//! generated by walking its descriptor, so gRPC and messaging services can be fed
+//! structurally valid messages end to end. Its type must implement
+//! `prost_reflect::ReflectMessage`, as types generated with `prost-reflect-build` do.
+//! Each field is set three times in four, each oneof is set to one of its fields or
+//! none, and enums take their declared values. Messages shrink by clearing and
+//! simplifying fields.
+//!
+//! With the `proptest` feature, an argument marked `#[strategy(s)]` is generated by the
+//! proptest strategy `s`, such as `0..100u32` or `my_strategy()`, and shrunk the way that
+//! strategy would shrink it, while the test fn still runs on the chosen runtime. As with
+//! `#[gen]`, its type needn't implement `Arbitrary`.
+//!
+//! With the `bytes`, `uuid` or `http` features, arguments of type `bytes::Bytes`,
+//! `bytes::BytesMut`, `uuid::Uuid`, `http::Uri` or `http::HeaderMap` are generated
+//! without a wrapper type, though the orphan rule keeps them from implementing
+//! `Arbitrary`. They're recognised by their path, so one imported by its bare name, or
+//! an alias of one, must be marked `#[foreign]`, while types of the same name from
+//! elsewhere are generated as usual. URIs are absolute or just a path, header maps have
+//! common and `x-..` headers, and UUIDs are often nil or max.
+//!
+//! With the `stream` feature, an argument of type `impl Stream<Item = T>`, with any
+//! other bounds such as `Send`, is an `ArbStream` of generated items which is pending
+//! for a while before some of them, so stream consumers are tested against irregular
+//! pacing. An `ArbStream<T>` can also be taken directly, to be cloned.
+//!
+//! An argument marked `#[map(f)]` is generated as whatever type `f` takes, then passed
+//! through `f`, so a fn sorting a `Vec` or canonicalizing a path makes valid values from
+//! raw ones declaratively. It shrinks as the raw value does, passing each candidate
+//! through `f` again.
+//!
+//! An argument marked `#[flat_map(f)]` is made by `f` from a generated value of the
+//! first type it takes and a [Generator], like proptest's `prop_flat_map`, so a length
+//! can be generated and then a matrix of that size. It shrinks as the generated value
+//! does, and then by giving `f` smaller generators.
+//!
+//! ```
+//! use quickcheck::Arbitrary;
+//! use quickcheck_async::Generator;
+//!
+//! fn square(n: u8, g: &mut Generator) -> Vec<Vec<u8>> {
+//!     let n = usize::from(n % 8);
+//!     (0..n).map(|_| (0..n).map(|_| u8::arbitrary(g)).collect()).collect()
+//! }
+//!
+//! #[quickcheck_async::tokio]
+//! async fn transpose_twice(#[flat_map(square)] m: Vec<Vec<u8>>) -> bool {
+//!     let t = |m: &Vec<Vec<u8>>| -> Vec<Vec<u8>> {
+//!         (0..m.len()).map(|j| m.iter().map(|row| row[j]).collect()).collect()
+//!     };
+//!     t(&t(&m)) == m
+//! }
+//! ```
+//!
+//! An argument marked `#[fixture(path)]` isn't generated at all, but is the result of
+//! calling `path` before each case, or of its type's `Default` impl for a bare
+//! `#[fixture]`. This mixes generated values with real dependencies, such as a client
+//! or connection pool, which can be shared by returning clones of one value.
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! fn greetings() -> HashMap<u8, String> {
+//!     (0..=255).map(|i| (i, format!("hello {}", i))).collect()
+//! }
+//!
+//! #[quickcheck_async::tokio]
+//! async fn greet(#[fixture(greetings)] table: HashMap<u8, String>, i: u8) -> bool {
+//!     table[&i].ends_with(&i.to_string())
+//! }
+//! ```
+//!
+//! Values which need IO to make, such as a reserved port or a temporary database schema,
+//! can be generated by the test itself: an argument marked `#[async_arbitrary]` is made
+//! by awaiting its [AsyncArbitrary] impl at the start of each case.
+//!
+//! Cases can also be discarded before the body of a test runs, with
+//! [filter](macro@filter) placed beneath its attribute. How many were filtered is
+//! reported alongside the number discarded, so an over-constrained filter is noticed.
+//! Known edge cases can be given with [examples](macro@examples), which run before any
+//! generated cases on every run.
+//!
+//! # Configuration
+//!
+//! Besides their runtime-specific arguments, every attribute accepts:
+//!
+//! * `tests = N` to set the number of passing cases required, as `QUICKCHECK_TESTS` would.
+//! * `debug_tests = N` and `release_tests = N` to set the number of passing cases
+//!   required in builds with and without debug assertions, in place of `tests`. Async
+//!   properties are often many times slower unoptimized.
+//! * `ci_scale = N` to require `N` times as many passing cases when the `CI` variable is
+//!   set, so local runs stay fast while CI tests deeply. `ci_var = "NAME"` marks CI runs
+//!   with another variable. `QUICKCHECK_TESTS` is never scaled. This is most useful
+//!   set once with [config!].
+//! * `confidence = C` to stop early, once enough distinct cases have passed to be `C`
+//!   sure (between 0 and 1) that fewer than 1% of all cases would fail, or fewer than
+//!   `failure_rate = P` of them. Repeated cases don't count, so properties with little
+//!   input diversity run longer. At most `tests` cases still run.
+//! * `duration = "10s"` to run cases until a wall-clock budget has elapsed, instead of
+//!   until `tests` have passed. Fast properties get more coverage, and slow ones fewer
+//!   cases. The budget is a whole number of `ms`, `s`, `m` or `h`.
+//! * `gen_size = N` to set the size of generated values, as `QUICKCHECK_GENERATOR_SIZE`
+//!   would.
+//! * `growth = "linear"` or `growth = "log"` to grow the size of generated values from
+//!   small up to `gen_size` over the run, rather than using `gen_size` for every case.
+//!   Logarithmic growth reaches large sizes quickly, spending most cases there.
+//! * `seed = N` to generate values from a fixed seed, so every run tests the same cases.
+//! * `rng = "chacha8"` or `rng = "os"` to choose the algorithm generating values. ChaCha8
+//!   is portable and reproducible from a seed; the operating system's entropy source is
+//!   not. `QUICKCHECK_ASYNC_RNG` sets the algorithm for tests that don't choose one.
+//! * `max_discard_ratio = R` to fail once more than `R` cases have been discarded per
+//!   required passing case, instead of stopping quietly after `QUICKCHECK_MAX_TESTS`
+//!   cases.
+//! * `max_depth = N` to limit how deeply generated values may nest, as counted by
+//!   [recurse] in the `Arbitrary` impls of recursive types such as trees and ASTs.
+//! * `retries = N` to run a failing case up to `N` more times, only counting it as failed
+//!   if every attempt fails. This keeps rare noise from sockets or timing out of results,
+//!   where that noise isn't what's being tested.
+//! * `expect = "fail"` to pass only if a counterexample is found within the cases run,
+//!   which is then reported without shrinking. This shows that generated values can
+//!   reach a region of interest, or records a property known not to hold.
+//! * `should_panic`, or `should_panic = "message"`, to expect every case to panic, with a
+//!   message containing the one given. A case which completes, or panics otherwise, is a
+//!   failure. This differs from `#[should_panic]`, which passes if any case panics.
+//! * `shrink = false` to report a failing case as generated, rather than searching for a
+//!   smaller one. This is useful when cases are slow or stateful enough that shrinking
+//!   takes too long to be worth it. Otherwise, each candidate tried while shrinking runs
+//!   on the runtime just as generated cases do, so it has the same timers and IO.
+//!   Arguments are shrunk one at a time, and once none of those candidates fail, two at
+//!   a time, so that correlated arguments such as a list and an index into it shrink
+//!   together.
+//! * `max_shrink_iters = N` to stop shrinking after `N` candidates have been tried,
+//!   reporting the smallest failing case found by then.
+//! * `shrink_timeout = "30s"` to stop shrinking once a wall-clock budget has elapsed,
+//!   reporting the smallest failing case found by then. The budget is given as for
+//!   `duration`.
+//! * `shrink_parallelism = N` to try up to `N` shrink candidates at once, taking the
+//!   first of them to fail in the order they were made, so the result is the same as
+//!   trying them in turn. This speeds up shrinking slow properties. Each candidate runs
+//!   on a thread of its own, or with `wasm` they're awaited together, so the test fn
+//!   may be running several times at once.
+//! * `progress = N` or `progress = "5s"` to print how many cases have run after every
+//!   `N` cases, or every so often, so that long runs don't look hung in CI logs.
+//! * `verbose = true` to print the arguments of every case, including shrink candidates,
+//!   before it runs. This shows which input a hanging property is stuck on.
+//! * `shrink_progress = true` to print each step taken while shrinking, naming the
+//!   arguments that changed and how many chars they're shown in, along with the
+//!   candidates tried and time taken so far, so a long shrink is seen to be progressing.
+//! * `shrink_by = "choices"` to shrink the random choices a failing case was generated
+//!   from, rather than its arguments, and generate it again from simpler ones. Every
+//!   candidate is then one the generators could have made, so invariants they keep,
+//!   such as an index being within a list, hold while shrinking. `shrink_by = "values"`
+//!   is the default.
+//! * `shrink_checkpoint = "target/prop.shrink"` to save each step taken while shrinking
+//!   to a file, so that a shrink which was interrupted or timed out can be resumed by
+//!   running the test again with `QUICKCHECK_ASYNC_RESUME_SHRINK=true`, rather than
+//!   starting over from the first counterexample. The test binary takes no flags of its
+//!   own, so it is resumed through the environment. Examples aren't saved.
+//! * `report = "quiet"` to print nothing when every case passes, or `report = "summary"`
+//!   to print a single `key=value` line for CI tools to parse. By default, the number of
+//!   discarded cases is printed if any were. `QUICKCHECK_ASYNC_REPORT` sets the report for
+//!   tests that don't choose one.
+//! * `ignore_env = true` to ignore the `QUICKCHECK_*` environment variables, so a test
+//!   runs as configured in code wherever it runs. This suits slow properties that a
+//!   global `QUICKCHECK_TESTS` would make far too slow.
+//! * `fail_fast = true` to stop early once any property in the test binary has found a
+//!   counterexample, so a long suite doesn't keep running after the interesting failure.
+//!   `QUICKCHECK_ASYNC_FAIL_FAST=true` sets this for tests that don't choose.
+//! * `exhaustive` to test every combination of arguments once, in place of generating
+//!   them, for arguments implementing [Exhaustive]. This guarantees full coverage of
+//!   small domains such as `bool`, `u8` or a fieldless enum. A failing case is reported
+//!   as found, since the simplest values are tried first. Only `max_discard_ratio`,
+//!   `expect`, `retries`, `fail_fast`, `progress`, `verbose` and `report` apply.
+//! * `arbitrary`, with the `arbitrary` feature, to build arguments with
+//!   `arbitrary::Arbitrary` from generated bytes, rather than with quickcheck, so the same
+//!   property can also serve as a `cargo fuzz` target. Failing cases are shrunk by
+//!   shrinking those bytes. Arguments with generators of their own, such as those
+//!   marked `#[gen]` or `#[range]`, are left alone.
+//! * `fallible` to let errors propagate from the body of a test fn with `?`, without
+//!   changing its return type. Any error which converts into a
+//!   `Box<dyn Error + Send + Sync>` fails the case, shown as an `Err` returned from it
+//!   would be.
+//!
+//! Settings can also be built in code, by naming a fn returning a [Config] with
+//! `config = "path::to::fn"`. Settings made on the attribute take precedence over those
+//! made by the fn. Cases run on this crate's own runner, and a `quickcheck::QuickCheck`
+//! keeps its settings, such as its generator, to itself, so one cannot be used in place of
+//! a [Config].
+//!
+//! ```
+//! use quickcheck_async::Config;
+//!
+//! fn slow() -> Config {
+//!     Config::new().tests(10).shrink(false)
+//! }
+//!
+//! #[quickcheck_async::tokio(config = "slow", gen_size = 8)]
+//! async fn fuzz_me(fuzz_arg: Vec<u8>) -> bool {
+//!     fuzz_arg.len() <= 8
+//! }
+//! ```
+//!
+//! Without a `seed`, ChaCha8 is seeded from `QUICKCHECK_ASYNC_SEED` if set (in decimal,
+//! or hex with a leading `0x`), or else at random. With `deterministic = true`, usually
+//! set once with [config!], a fixed seed is used instead of a random one, so every run
+//! generates the same cases and failures can be bisected across commits. The seed is
+//! printed when a test fails, so the failing run can be replayed by setting
+//! `QUICKCHECK_ASYNC_SEED`.
+//!
+//! ```
+//! #[quickcheck_async::tokio(gen_size = 512, seed = 0xDEADBEEF)]
+//! async fn fuzz_me(fuzz_arg: Vec<u8>) -> bool {
+//!     fuzz_arg.len() <= 512
+//! }
+//! ```
+//!
+//! Defaults for every test in a crate can be set once with [config!], which accepts the
+//! same settings. They can also be set in a `quickcheck_async.toml` file, found by
+//! searching from the crate's manifest directory up to the workspace root, so fuzzing can
+//! be tuned for many crates in one place. Settings in [config!] take precedence over
+//! those in the file. Changes to the file rebuild the tests using it, but a newly created
+//! file is only read once they are rebuilt for another reason, such as after
+//! `cargo clean` or an edit to their source.
+//!
+//! ```toml
+//! tests = 500
+//! max_shrink_iters = 1000
+//! shrink_timeout = "30s"
+//! deterministic = true
+//! ```
 #![warn(rust_2018_idioms, missing_docs)]
 
-use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{
-    parse_macro_input, punctuated::Punctuated, token::Comma, AttributeArgs, Error, FnArg, ItemFn,
-    NestedMeta, Pat, Type,
-};
+mod alphabet;
+mod checkpoint;
+mod choices;
+mod dictionary;
+mod floats;
+mod foreign;
+#[cfg(feature = "json-schema")]
+mod json;
+mod len;
+mod net;
+#[cfg(feature = "prost")]
+mod protobuf;
+mod runner;
+mod shared;
+mod shrinkers;
+#[cfg(feature = "stream")]
+mod stream;
+mod time;
 
-struct Arguments {
-    ids: Punctuated<Pat, Comma>,
-    tys: Punctuated<Type, Comma>,
-}
+pub use net::{free_port, Cidr, FreePort};
+#[cfg(feature = "embassy")]
+pub use quickcheck_async_macros::embassy;
+pub use quickcheck_async_macros::{
+    actix_rt, async_std, config, custom, examples, filter, futures, global_executor, glommio,
+    monoio, smol, test, tokio, tokio_mod, tokio_uring, wasm, AsyncArbitrary,
+};
+pub use runner::{
+    recurse, Arguments, AsyncArbitrary, AsyncTestable, Config, Exhaustive, Expect, Generator,
+    Growth, Progress, PropContext, PropError, Report, Rng, ShrinkBy, Status,
+};
+pub use shrinkers::register_shrinker;
+#[cfg(feature = "stream")]
+pub use stream::ArbStream;
 
-fn parse_args(fn_item: &ItemFn) -> Result<Arguments, TokenStream> {
-    let mut args = Arguments {
-        ids: Punctuated::new(),
-        tys: Punctuated::new(),
+/// Stop the running case, marking it as discarded, from anywhere in the body of a test.
+///
+/// This works by unwinding, so it must be reached from the test fn itself rather than from
+/// a task it spawns, where it would be a panic like any other.
+///
+/// ```
+/// #[quickcheck_async::tokio]
+/// async fn divide(x: i32) -> bool {
+///     if x == 0 {
+///         quickcheck_async::discard!();
+///     }
+///     (100 / x).abs() <= 100
+/// }
+/// ```
+#[macro_export]
+macro_rules! discard {
+    () => {
+        $crate::__private::discard()
     };
-
-    for pt in fn_item.sig.inputs.iter() {
-        match pt {
-            FnArg::Receiver(_) => {
-                return Err(
-                    Error::new_spanned(&fn_item, "test fn cannot take a receiver")
-                        .to_compile_error()
-                        .into(),
-                )
-            }
-
-            FnArg::Typed(pt) => {
-                args.ids.push(*pt.pat.clone());
-                args.tys.push(*pt.ty.clone());
-            }
-        }
-    }
-
-    Ok(args)
 }
 
-/// Mark an async function to be fuzz-tested using [quickcheck][qc], within a tokio
-/// executor.
-///
-/// # Usage
+/// Stop the running case, marking it as discarded, if `cond` holds. See [discard!].
 ///
 /// ```
 /// #[quickcheck_async::tokio]
-/// async fn fuzz_me(fuzz_arg: String) -> bool {
-///     fuzz_arg != "fuzzed".to_owned()
+/// async fn division(x: i32, y: i32) -> bool {
+///     quickcheck_async::reject_if!(y == 0 || (x == i32::MIN && y == -1));
+///     (x / y) * y + x % y == x
 /// }
 /// ```
+#[macro_export]
+macro_rules! reject_if {
+    ($cond: expr) => {
+        if $cond {
+            $crate::discard!();
+        }
+    };
+}
+
+/// Fail the case if `cond` doesn't hold, by returning a [PropError] from the test fn.
 ///
-/// # Attribute arguments
-///
-/// Arguments to this attribute are passed through to [tokio::test][tt].
+/// Unlike `assert!`, the failure is reported with the expression that failed and where,
+/// along with any context attached through [PropContext]. The test fn must return a
+/// `Result` whose error can be built from a [PropError], such as `anyhow::Error`. A
+/// message may be given, as with `assert!`.
 ///
 /// ```
-/// #[quickcheck_async::tokio(core_threads = 3)]
-/// async fn fuzz_me(fuzz_arg: String) -> bool {
-///     fuzz_arg != "fuzzed".to_owned()
+/// use quickcheck_async::{prop_assert, PropError};
+///
+/// #[quickcheck_async::tokio]
+/// async fn halve(x: u32) -> Result<(), PropError> {
+///     prop_assert!(x / 2 <= x);
+///     prop_assert!(x / 2 * 2 + x % 2 == x, "halving {} lost a bit", x);
+///     Ok(())
 /// }
 /// ```
-/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
-/// [tt]: https://docs.rs/tokio/latest/tokio/attr.test.html
-#[proc_macro_attribute]
-pub fn tokio(args: TokenStream, item: TokenStream) -> TokenStream {
-    let fn_item = parse_macro_input!(item as ItemFn);
-
-    for attr in &fn_item.attrs {
-        if attr.path.is_ident("test") {
-            return Error::new_spanned(&fn_item, "multiple #[test] attributes were supplied")
-                .to_compile_error()
-                .into();
+#[macro_export]
+macro_rules! prop_assert {
+    ($cond: expr $(,)?) => {
+        if !$cond {
+            return ::core::result::Result::Err(::core::convert::Into::into(
+                $crate::PropError::new(::core::concat!(
+                    "assertion failed: ",
+                    ::core::stringify!($cond)
+                )),
+            ));
         }
-    }
-
-    if fn_item.sig.asyncness.is_none() {
-        return Error::new_spanned(&fn_item, "test fn must be async")
-            .to_compile_error()
-            .into();
-    }
-
-    let p_args = parse_macro_input!(args as AttributeArgs);
-    let attrib: Punctuated<NestedMeta, Comma> = p_args.into_iter().collect();
-
-    let call_by = format_ident!("{}", fn_item.sig.ident);
-
-    let Arguments { ids, tys } = match parse_args(&fn_item) {
-        Err(e) => return e,
-        Ok(ts) => ts,
     };
-
-    let ret = &fn_item.sig.output;
-
-    quote! (
-        #[::tokio::test(#attrib)]
-        async fn #call_by() {
-            #fn_item
-
-            let test_fn: fn(#tys) #ret = |#ids| {
-                ::futures::executor::block_on(#call_by(#ids))
-            };
-
-            ::tokio::task::spawn_blocking(move || {
-                ::quickcheck::quickcheck(test_fn)
-            })
-            .await
-            .unwrap()
+    ($cond: expr, $($arg: tt)+) => {
+        if !$cond {
+            return ::core::result::Result::Err(::core::convert::Into::into(
+                $crate::PropError::new(::std::format!(
+                    "{} (assertion failed: {})",
+                    ::std::format_args!($($arg)+),
+                    ::core::stringify!($cond)
+                )),
+            ));
         }
-    )
-    .into()
+    };
 }
 
-/// Mark an async function to be fuzz-tested using [quickcheck][qc], within an async_std
-/// executor.
-///
-/// # Usage
+/// Fail the case if two expressions aren't equal, showing both. See [prop_assert!].
 ///
 /// ```
-/// #[quickcheck_async::async_std]
-/// async fn fuzz_me(fuzz_arg: String) -> bool {
-///     fuzz_arg != "fuzzed".to_owned()
+/// use quickcheck_async::{prop_assert_eq, PropError};
+///
+/// #[quickcheck_async::tokio]
+/// async fn round_trip(x: u16) -> Result<(), PropError> {
+///     prop_assert_eq!(x.to_string().parse::<u16>(), Ok(x));
+///     Ok(())
 /// }
 /// ```
-/// [qc]: https://docs.rs/quickcheck/latest/quickcheck/fn.quickcheck.html
-#[proc_macro_attribute]
-pub fn async_std(args: TokenStream, item: TokenStream) -> TokenStream {
-    let fn_item = parse_macro_input!(item as ItemFn);
-
-    for attr in &fn_item.attrs {
-        if attr.path.is_ident("test") {
-            return Error::new_spanned(&fn_item, "multiple #[test] attributes were supplied")
-                .to_compile_error()
-                .into();
+#[macro_export]
+macro_rules! prop_assert_eq {
+    ($left: expr, $right: expr $(,)?) => {
+        $crate::prop_assert_eq!($left, $right, "values differ")
+    };
+    ($left: expr, $right: expr, $($arg: tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    return ::core::result::Result::Err(::core::convert::Into::into(
+                        $crate::PropError::new(::std::format!(
+                            "{} (assertion failed: {} == {})\n  left: {:?}\n right: {:?}",
+                            ::std::format_args!($($arg)+),
+                            ::core::stringify!($left),
+                            ::core::stringify!($right),
+                            left,
+                            right
+                        )),
+                    ));
+                }
+            }
         }
-    }
-
-    if fn_item.sig.asyncness.is_none() {
-        return Error::new_spanned(&fn_item, "test fn must be async")
-            .to_compile_error()
-            .into();
-    }
-
-    let p_args = parse_macro_input!(args as AttributeArgs);
-    let attrib: Punctuated<NestedMeta, Comma> = p_args.into_iter().collect();
-
-    let call_by = format_ident!("{}", fn_item.sig.ident);
-
-    let Arguments { ids, tys } = match parse_args(&fn_item) {
-        Err(e) => return e,
-        Ok(ts) => ts,
     };
+}
 
-    let ret = &fn_item.sig.output;
-
-    quote! (
-        #[::async_std::test(#attrib)]
-        async fn #call_by() {
-            #fn_item
-
-            let test_fn: fn(#tys) #ret = |#ids| {
-                ::futures::executor::block_on(#call_by(#ids))
-            };
-
-            ::quickcheck::quickcheck(test_fn);
-        }
-    )
-    .into()
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::alphabet::Alphabet;
+    pub use crate::dictionary::Dictionary;
+    pub use crate::floats::Floats;
+    pub use crate::foreign::Foreign;
+    #[cfg(feature = "json-schema")]
+    pub use crate::json::{from_schema, shrink_schema};
+    pub use crate::len::WithLen;
+    pub use crate::net::Net;
+    #[cfg(feature = "prost")]
+    pub use crate::protobuf::{from_message, shrink_message};
+    #[cfg(feature = "arbitrary")]
+    pub use crate::runner::FromBytes;
+    pub use crate::runner::{
+        catch_unwind, dependent, discard, exhaustive_local, filtered, flat_mapped, mapped,
+        quickcheck_local, resized, variant, weighted, AsyncTestable, Config, Defaults, Enumerate,
+        Expect, Fork, Generator, Growth, InRange, Mapped, Progress, Property, Report, Rng,
+        ShrinkBy, Source, Status, Toward,
+    };
+    #[cfg(feature = "regex")]
+    pub use crate::runner::{from_regex, shrink_regex, Matching};
+    #[cfg(feature = "proptest")]
+    pub use crate::runner::{from_strategy, shrink_strategy, Shrinking};
+    pub use crate::shared::shared;
+    pub use crate::time::{shrink_time, time, Temporal};
+    pub use inventory;
+    pub use quickcheck;
 }
@@ -0,0 +1,294 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use quickcheck::{Arbitrary, Gen};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, TcpListener},
+    sync::Mutex,
+};
+
+/// Address types with generators favouring addresses with special meanings, such as
+/// loopback, private and multicast ones, for `#[net]` arguments.
+pub trait Net: Sized {
+    /// Generate an address, which is in a special purpose block about half the time.
+    fn net<G: Gen>(g: &mut G) -> Self;
+    /// Shrink an address towards the unspecified one, and a port towards 0.
+    fn shrink_net(&self) -> Box<dyn Iterator<Item = Self>>;
+}
+
+const V4_BLOCKS: &[(u32, u8)] = &[
+    (0x0000_0000, 32), // unspecified
+    (0x7F00_0000, 8),  // loopback
+    (0xFFFF_FFFF, 32), // broadcast
+    (0x0A00_0000, 8),  // private
+    (0xAC10_0000, 12), // private
+    (0xC0A8_0000, 16), // private
+    (0xA9FE_0000, 16), // link local
+    (0x6440_0000, 10), // shared
+    (0xE000_0000, 4),  // multicast
+];
+
+const V6_BLOCKS: &[(u128, u8)] = &[
+    (0, 128),                // unspecified
+    (1, 128),                // loopback
+    (0xFE80 << 112, 10),     // link local
+    (0xFC00 << 112, 7),      // unique local
+    (0xFF00 << 112, 8),      // multicast
+    (0xFFFF << 32, 96),      // ipv4 mapped
+    (0x2001_0DB8 << 96, 32), // documentation
+];
+
+const PORTS: &[u16] = &[0, 1, 22, 53, 80, 443, 1023, 1024, 8080, 49152, 65535];
+
+/// A random value in the block `(network, prefix)` half the time, or else any value.
+macro_rules! in_blocks {
+    ($g: expr, $ty: ty, $blocks: expr) => {{
+        let bits = <$ty>::BITS as u8;
+        let mut host = 0;
+        for _ in 0..bits / 32 {
+            host = (host << 16 << 16) | <$ty>::from($g.next_u32());
+        }
+        match $g.next_u32() as usize % ($blocks.len() * 2) {
+            i if i < $blocks.len() => {
+                let (network, prefix) = $blocks[i];
+                network | (host & mask(prefix, bits) as $ty)
+            }
+            _ => host,
+        }
+    }};
+}
+
+/// The bits of a host within a block of the given prefix, as a `u128` of which the low
+/// `bits` are used.
+fn mask(prefix: u8, bits: u8) -> u128 {
+    let all = u128::MAX >> (128 - bits as u32);
+    all.checked_shr(prefix as u32).unwrap_or(0)
+}
+
+impl Net for Ipv4Addr {
+    fn net<G: Gen>(g: &mut G) -> Self {
+        Ipv4Addr::from(in_blocks!(g, u32, V4_BLOCKS))
+    }
+
+    fn shrink_net(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(u32::from(*self).shrink().map(Ipv4Addr::from))
+    }
+}
+
+impl Net for Ipv6Addr {
+    fn net<G: Gen>(g: &mut G) -> Self {
+        Ipv6Addr::from(in_blocks!(g, u128, V6_BLOCKS))
+    }
+
+    fn shrink_net(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(u128::from(*self).shrink().map(Ipv6Addr::from))
+    }
+}
+
+impl Net for IpAddr {
+    fn net<G: Gen>(g: &mut G) -> Self {
+        match g.next_u32().is_multiple_of(2) {
+            true => IpAddr::V4(Ipv4Addr::net(g)),
+            false => IpAddr::V6(Ipv6Addr::net(g)),
+        }
+    }
+
+    fn shrink_net(&self) -> Box<dyn Iterator<Item = Self>> {
+        match *self {
+            IpAddr::V4(ip) => Box::new(ip.shrink_net().map(IpAddr::V4)),
+            IpAddr::V6(ip) => Box::new(ip.shrink_net().map(IpAddr::V6)),
+        }
+    }
+}
+
+/// A well known or boundary port half the time, or else any port.
+fn port<G: Gen>(g: &mut G) -> u16 {
+    match g.next_u32() as usize % (PORTS.len() * 2) {
+        i if i < PORTS.len() => PORTS[i],
+        _ => g.next_u32() as u16,
+    }
+}
+
+macro_rules! net_socket {
+    ($($ty: ident($ip: ty)),*) => {
+        $(impl Net for $ty {
+            fn net<G: Gen>(g: &mut G) -> Self {
+                let mut addr = $ty::arbitrary(g);
+                addr.set_ip(<$ip>::net(g));
+                addr.set_port(port(g));
+                addr
+            }
+
+            fn shrink_net(&self) -> Box<dyn Iterator<Item = Self>> {
+                let (addr, port) = (*self, self.port());
+                let ips = self.ip().shrink_net().map(move |ip| {
+                    let mut addr = addr;
+                    addr.set_ip(ip);
+                    addr
+                });
+                let ports = port.shrink().map(move |port| {
+                    let mut addr = addr;
+                    addr.set_port(port);
+                    addr
+                });
+                Box::new(ips.chain(ports))
+            }
+        })*
+    };
+}
+
+net_socket!(
+    SocketAddrV4(Ipv4Addr),
+    SocketAddrV6(Ipv6Addr),
+    SocketAddr(IpAddr)
+);
+
+/// A block of IP addresses sharing a prefix, such as `10.0.0.0/8`.
+///
+/// Generated blocks are of any length, and are often special purpose blocks or within
+/// them. They shrink towards shorter prefixes of lower addresses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// The block of `addr` with a prefix of `prefix` bits, or `None` if the prefix is
+    /// longer than the address. Bits of `addr` past the prefix are cleared.
+    pub fn new(addr: IpAddr, prefix: u8) -> Option<Self> {
+        let network = match addr {
+            IpAddr::V4(ip) if prefix <= 32 => {
+                IpAddr::V4(Ipv4Addr::from(u32::from(ip) & !(mask(prefix, 32) as u32)))
+            }
+            IpAddr::V6(ip) if prefix <= 128 => {
+                IpAddr::V6(Ipv6Addr::from(u128::from(ip) & !mask(prefix, 128)))
+            }
+            _ => return None,
+        };
+
+        Some(Cidr { network, prefix })
+    }
+
+    /// The first address of the block.
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    /// How many leading bits the addresses of the block share.
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Whether `addr` is in the block. Addresses of the other family never are.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        Cidr::new(addr, self.prefix) == Some(*self)
+    }
+}
+
+impl Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl Arbitrary for Cidr {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let addr = IpAddr::net(g);
+        let bits = if addr.is_ipv4() { 33 } else { 129 };
+        Cidr::new(addr, (g.next_u32() % bits) as u8).expect("prefix fits the address")
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let prefix = self.prefix;
+        let networks = self
+            .network
+            .shrink_net()
+            .filter_map(move |n| Cidr::new(n, prefix));
+        let network = self.network;
+        let prefixes = prefix.shrink().filter_map(move |p| Cidr::new(network, p));
+        Box::new(prefixes.chain(networks).filter({
+            let this = *self;
+            move |c| *c != this
+        }))
+    }
+}
+
+/// Ports handed out by [free_port] which are still held.
+static HELD: Mutex<BTreeSet<u16>> = Mutex::new(BTreeSet::new());
+
+/// A localhost address with a port no other [FreePort] in the process holds, which
+/// was free when it was found. The port is held until the value is dropped.
+///
+/// As a [Default] impl finds one, a test can take a fresh port per case with
+/// `#[fixture]`, so that cases run many times or in parallel don't collide on a
+/// hard-coded port:
+///
+/// ```
+/// use quickcheck_async::FreePort;
+/// use tokio::net::TcpListener;
+///
+/// #[quickcheck_async::tokio]
+/// async fn binds(#[fixture] port: FreePort) -> bool {
+///     let listener = TcpListener::bind(port.addr()).await.unwrap();
+///     listener.local_addr().unwrap() == port.addr()
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct FreePort {
+    addr: SocketAddr,
+}
+
+impl FreePort {
+    /// The address to bind.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The port to bind.
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+}
+
+impl Default for FreePort {
+    fn default() -> Self {
+        free_port()
+    }
+}
+
+impl Drop for FreePort {
+    fn drop(&mut self) {
+        let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+        held.remove(&self.addr.port());
+    }
+}
+
+/// Find a free port on 127.0.0.1, by asking the OS for one which isn't already held.
+///
+/// Another process may still take the port before it's bound, though the OS hands
+/// out ephemeral ports in turn, so this is rare.
+///
+/// # Panics
+///
+/// If no port could be bound, such as on targets without networking.
+pub fn free_port() -> FreePort {
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+
+    loop {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap_or_else(|e| panic!("failed to find a free port: {}", e));
+        let addr = listener
+            .local_addr()
+            .unwrap_or_else(|e| panic!("failed to find a free port: {}", e));
+
+        if held.insert(addr.port()) {
+            return FreePort { addr };
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use prost_reflect::{
+    prost::bytes::Bytes, DynamicMessage, FieldDescriptor, Kind, MapKey, MessageDescriptor,
+    ReflectMessage, Value,
+};
+use quickcheck::{Arbitrary, Gen};
+use std::{collections::HashMap, iter};
+
+/// How deeply messages may nest, so recursive message types terminate.
+const MAX_DEPTH: usize = 8;
+
+/// Generate a message by walking the descriptor of its type, for `#[message]`
+/// arguments.
+pub fn from_message<T: ReflectMessage + Default, G: Gen>(g: &mut G) -> T {
+    let size = g.size();
+    message(&T::default().descriptor(), g, size, 0)
+        .transcode_to()
+        .expect("generated message matches its descriptor")
+}
+
+/// The candidates to shrink `from` to.
+pub fn shrink_message<T: ReflectMessage + Default>(from: &T) -> impl Iterator<Item = T> {
+    candidates(&from.transcode_to_dynamic()).filter_map(|candidate| candidate.transcode_to().ok())
+}
+
+fn below<G: Gen>(g: &mut G, n: usize) -> usize {
+    (g.next_u64() % n.max(1) as u64) as usize
+}
+
+fn message<G: Gen>(
+    desc: &MessageDescriptor,
+    g: &mut G,
+    size: usize,
+    depth: usize,
+) -> DynamicMessage {
+    let mut message = DynamicMessage::new(desc.clone());
+
+    // each real oneof is set to one of its fields, or left unset
+    for oneof in desc.oneofs().filter(|oneof| !oneof.is_synthetic()) {
+        let fields: Vec<_> = oneof.fields().collect();
+        if let Some(field) = fields.get(below(g, fields.len() + 1)) {
+            if let Some(value) = field_value(field, g, size, depth) {
+                message.set_field(field, value);
+            }
+        }
+    }
+
+    // other fields are each set three times in four
+    for field in desc.fields() {
+        let in_oneof = field.containing_oneof().is_some_and(|o| !o.is_synthetic());
+        if in_oneof || below(g, 4) == 0 {
+            continue;
+        }
+        if let Some(value) = field_value(&field, g, size, depth) {
+            message.set_field(&field, value);
+        }
+    }
+
+    message
+}
+
+/// A value of `field`, which is `None` for a message nested too deeply.
+fn field_value<G: Gen>(
+    field: &FieldDescriptor,
+    g: &mut G,
+    size: usize,
+    depth: usize,
+) -> Option<Value> {
+    if field.is_map() {
+        let entry = match field.kind() {
+            Kind::Message(entry) => entry,
+            _ => return None,
+        };
+        let (key, value) = (entry.map_entry_key_field(), entry.map_entry_value_field());
+        let mut map = HashMap::new();
+        for _ in 0..below(g, size / 2 + 1) {
+            let key = map_key(single(&key.kind(), g, size, depth)?)?;
+            map.insert(key, single(&value.kind(), g, size / 2, depth + 1)?);
+        }
+        return Some(Value::Map(map));
+    }
+
+    if field.is_list() {
+        let len = below(g, size / 2 + 1);
+        let items = (0..len).map(|_| single(&field.kind(), g, size / 2, depth + 1));
+        return items.collect::<Option<_>>().map(Value::List);
+    }
+
+    single(&field.kind(), g, size, depth)
+}
+
+fn single<G: Gen>(kind: &Kind, g: &mut G, size: usize, depth: usize) -> Option<Value> {
+    Some(match kind {
+        Kind::Double => Value::F64(f64::arbitrary(g)),
+        Kind::Float => Value::F32(f32::arbitrary(g)),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => Value::I32(i32::arbitrary(g)),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => Value::I64(i64::arbitrary(g)),
+        Kind::Uint32 | Kind::Fixed32 => Value::U32(u32::arbitrary(g)),
+        Kind::Uint64 | Kind::Fixed64 => Value::U64(u64::arbitrary(g)),
+        Kind::Bool => Value::Bool(bool::arbitrary(g)),
+        Kind::String => Value::String(String::arbitrary(g)),
+        Kind::Bytes => Value::Bytes(Bytes::from(Vec::<u8>::arbitrary(g))),
+        Kind::Enum(desc) => {
+            let numbers: Vec<_> = desc.values().map(|value| value.number()).collect();
+            Value::EnumNumber(numbers.get(below(g, numbers.len())).copied().unwrap_or(0))
+        }
+        Kind::Message(_) if depth >= MAX_DEPTH => return None,
+        Kind::Message(desc) => Value::Message(message(desc, g, size / 2, depth + 1)),
+    })
+}
+
+fn map_key(value: Value) -> Option<MapKey> {
+    Some(match value {
+        Value::Bool(b) => MapKey::Bool(b),
+        Value::I32(x) => MapKey::I32(x),
+        Value::I64(x) => MapKey::I64(x),
+        Value::U32(x) => MapKey::U32(x),
+        Value::U64(x) => MapKey::U64(x),
+        Value::String(s) => MapKey::String(s),
+        _ => return None,
+    })
+}
+
+/// Simpler messages than `message`, with a field cleared or simplified. They're made
+/// lazily, as a message with long lists has very many.
+fn candidates(message: &DynamicMessage) -> Box<dyn Iterator<Item = DynamicMessage>> {
+    let fields: Vec<_> = message
+        .fields()
+        .map(|(field, value)| (field, value.clone()))
+        .collect();
+    let message = message.clone();
+
+    Box::new(fields.into_iter().flat_map(move |(field, value)| {
+        let mut cleared = message.clone();
+        cleared.clear_field(&field);
+        let message = message.clone();
+        iter::once(cleared).chain(simpler(&value).map(move |value| {
+            let mut candidate = message.clone();
+            candidate.set_field(&field, value);
+            candidate
+        }))
+    }))
+}
+
+fn simpler(value: &Value) -> Box<dyn Iterator<Item = Value>> {
+    fn shrunk<T: Arbitrary>(x: &T, f: fn(T) -> Value) -> Box<dyn Iterator<Item = Value>> {
+        Box::new(x.shrink().map(f))
+    }
+
+    match value {
+        Value::Bool(b) => shrunk(b, Value::Bool),
+        Value::I32(x) => shrunk(x, Value::I32),
+        Value::I64(x) => shrunk(x, Value::I64),
+        Value::U32(x) => shrunk(x, Value::U32),
+        Value::U64(x) => shrunk(x, Value::U64),
+        Value::F32(x) => shrunk(x, Value::F32),
+        Value::F64(x) => shrunk(x, Value::F64),
+        Value::String(s) => shrunk(s, Value::String),
+        Value::Bytes(b) => shrunk(&b.to_vec(), |b| Value::Bytes(Bytes::from(b))),
+        // the first value declared is the default, so the simplest
+        Value::EnumNumber(0) => Box::new(iter::empty()),
+        Value::EnumNumber(_) => Box::new(iter::once(Value::EnumNumber(0))),
+        Value::Message(message) => Box::new(candidates(message).map(Value::Message)),
+        Value::List(items) => {
+            let (len, items) = (items.len(), items.clone());
+            let removed = (0..len).map({
+                let items = items.clone();
+                move |i| {
+                    let mut items = items.clone();
+                    items.remove(i);
+                    Value::List(items)
+                }
+            });
+            let shrunk = (0..len).flat_map(move |i| {
+                let items = items.clone();
+                simpler(&items[i]).map(move |item| {
+                    let mut items = items.clone();
+                    items[i] = item;
+                    Value::List(items)
+                })
+            });
+            Box::new(removed.chain(shrunk))
+        }
+        Value::Map(map) => {
+            let (keys, map) = (map.keys().cloned().collect::<Vec<_>>(), map.clone());
+            let removed = keys.clone().into_iter().map({
+                let map = map.clone();
+                move |key| {
+                    let mut map = map.clone();
+                    map.remove(&key);
+                    Value::Map(map)
+                }
+            });
+            let shrunk = keys.into_iter().flat_map(move |key| {
+                let map = map.clone();
+                simpler(&map[&key]).map(move |value| {
+                    let mut map = map.clone();
+                    map.insert(key.clone(), value);
+                    Value::Map(map)
+                })
+            });
+            Box::new(removed.chain(shrunk))
+        }
+    }
+}
@@ -0,0 +1,2738 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use crate::{
+    checkpoint::{checkpoint, resume},
+    choices::Case,
+    shrinkers::registered,
+};
+use quickcheck::{Arbitrary, Gen, TestResult};
+use rand_chacha::ChaCha8Rng;
+use rand_core::{OsRng, RngCore, SeedableRng};
+use std::{
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::{Cell, RefCell},
+    cmp,
+    collections::{hash_map::DefaultHasher, HashSet},
+    env,
+    fmt::{self, Debug, Display},
+    future::{self, Future},
+    hash::{Hash, Hasher},
+    iter,
+    num::ParseIntError,
+    ops::{Bound, RangeBounds},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Once, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The verdict of a single generated case.
+#[derive(Debug)]
+pub enum Status {
+    /// The case passed.
+    Pass,
+    /// The case failed, optionally with an error message.
+    Fail(Option<String>),
+    /// The case should be ignored.
+    Discard,
+}
+
+/// Types that can be interpreted as the result of a generated case.
+///
+/// This mirrors [quickcheck::Testable] for the value types it supports, but is
+/// consumed directly rather than through a fn pointer. Implement it for a domain type to
+/// return that from test fns, choosing how its failures are rendered:
+///
+/// ```
+/// use quickcheck_async::{AsyncTestable, Status};
+///
+/// struct Simulation {
+///     steps: usize,
+///     violations: Vec<String>,
+/// }
+///
+/// impl AsyncTestable for Simulation {
+///     fn outcome(self) -> Status {
+///         match (self.steps, self.violations.as_slice()) {
+///             (0, _) => Status::Discard,
+///             (_, []) => Status::Pass,
+///             (_, violations) => Status::Fail(Some(violations.join("\n"))),
+///         }
+///     }
+/// }
+///
+/// #[quickcheck_async::tokio]
+/// async fn simulate(ops: Vec<u8>) -> Simulation {
+///     Simulation {
+///         steps: ops.len(),
+///         violations: vec![],
+///     }
+/// }
+/// ```
+pub trait AsyncTestable {
+    /// Interpret `self` as a verdict.
+    fn outcome(self) -> Status;
+}
+
+impl AsyncTestable for bool {
+    fn outcome(self) -> Status {
+        if self {
+            Status::Pass
+        } else {
+            Status::Fail(None)
+        }
+    }
+}
+
+impl AsyncTestable for () {
+    fn outcome(self) -> Status {
+        Status::Pass
+    }
+}
+
+impl AsyncTestable for TestResult {
+    fn outcome(self) -> Status {
+        // quickcheck exposes neither whether a result was discarded nor the message of an
+        // error, so both are read from its Debug output
+        let debug = format!("{:?}", self);
+        if self.is_error() {
+            Status::Fail(debug_error(&debug))
+        } else if self.is_failure() {
+            Status::Fail(None)
+        } else if debug.starts_with("TestResult { status: Discard,") {
+            Status::Discard
+        } else {
+            Status::Pass
+        }
+    }
+}
+
+/// The message given to `TestResult::error`, read back from the result's Debug output.
+fn debug_error(debug: &str) -> Option<String> {
+    // quotes within the arguments and message are escaped, so the first unescaped one
+    // after `err: Some(` opens the message
+    let (_, quoted) = debug.split_once("err: Some(\"")?;
+    let quoted = quoted.strip_suffix("\") }")?;
+
+    let mut msg = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            msg.push(c);
+            continue;
+        }
+        msg.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'u' => {
+                let hex: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+            }
+            c => c,
+        });
+    }
+    Some(msg)
+}
+
+/// A `None` discards the case, so `?` can be used to reject arguments that don't parse.
+impl<T: AsyncTestable> AsyncTestable for Option<T> {
+    fn outcome(self) -> Status {
+        match self {
+            Some(t) => t.outcome(),
+            None => Status::Discard,
+        }
+    }
+}
+
+/// An `Err` is a failure, reported with the error's [Display] output. The alternate form
+/// is used, which some error types extend with the errors that caused them.
+impl<T: AsyncTestable, E: Display + 'static> AsyncTestable for Result<T, E> {
+    fn outcome(self) -> Status {
+        match self {
+            Ok(t) => t.outcome(),
+            Err(e) => Status::Fail(Some(render_error(&e))),
+        }
+    }
+}
+
+/// Render an error returned by a case. Error reports from `anyhow` and `eyre` are shown
+/// in full, with their causes and any captured backtrace.
+fn render_error<E: Display + 'static>(e: &E) -> String {
+    #[allow(unused_variables)]
+    let any = e as &dyn Any;
+
+    #[cfg(feature = "anyhow")]
+    {
+        if let Some(report) = any.downcast_ref::<anyhow::Error>() {
+            return format!("{:?}", report);
+        }
+    }
+
+    #[cfg(feature = "eyre")]
+    {
+        if let Some(report) = any.downcast_ref::<eyre::Report>() {
+            return format!("{:?}", report);
+        }
+    }
+
+    format!("{:#}", e)
+}
+
+/// A failed [prop_assert!] or [prop_assert_eq!], with where it failed and any context
+/// attached to it on the way out of the test fn.
+///
+/// [prop_assert!]: crate::prop_assert
+/// [prop_assert_eq!]: crate::prop_assert_eq
+#[derive(Debug)]
+pub struct PropError {
+    msg: String,
+    location: &'static panic::Location<'static>,
+    context: Vec<String>,
+}
+
+impl PropError {
+    /// A failure with the given message, located where this is called.
+    #[track_caller]
+    pub fn new<M: Display>(msg: M) -> Self {
+        PropError {
+            msg: msg.to_string(),
+            location: panic::Location::caller(),
+            context: vec![],
+        }
+    }
+
+    /// Attach a note on what was being done when the failure occurred.
+    pub fn context<C: Display>(mut self, context: C) -> Self {
+        self.context.push(context.to_string());
+        self
+    }
+}
+
+impl Display for PropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n  at {}", self.msg, self.location)?;
+        for context in &self.context {
+            write!(f, "\n  while {}", context)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PropError {}
+
+/// Attach context to a failed [prop_assert!] as it is propagated with `?`.
+///
+/// ```
+/// use quickcheck_async::{prop_assert_eq, PropContext, PropError};
+///
+/// async fn step(x: u8) -> Result<u8, PropError> {
+///     prop_assert_eq!(x.wrapping_add(1).wrapping_sub(1), x);
+///     Ok(x)
+/// }
+///
+/// #[quickcheck_async::tokio]
+/// async fn steps(xs: Vec<u8>) -> Result<(), PropError> {
+///     for (i, &x) in xs.iter().enumerate() {
+///         step(x).await.with_context(|| format!("running step {}", i))?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// [prop_assert!]: crate::prop_assert
+pub trait PropContext<T> {
+    /// Attach the context returned by `f`, if `self` is an error.
+    fn with_context<C: Display, F: FnOnce() -> C>(self, f: F) -> Result<T, PropError>;
+}
+
+impl<T> PropContext<T> for Result<T, PropError> {
+    fn with_context<C: Display, F: FnOnce() -> C>(self, f: F) -> Result<T, PropError> {
+        self.map_err(|e| e.context(f()))
+    }
+}
+
+/// Set once any property in the process has failed.
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+/// The seed used by deterministic runs that don't set one.
+const DETERMINISTIC_SEED: u64 = 0x5eed;
+
+/// The number of cases a run passes, unless `tests` says otherwise.
+const DEFAULT_TESTS: u64 = 100;
+
+/// The size of generated values, unless `gen_size` says otherwise.
+const DEFAULT_GEN_SIZE: usize = 100;
+
+/// Settings for a run of quickcheck.
+///
+/// Every setting accepted by the attributes has a builder method here, so a fn returning
+/// a `Config` can be shared between tests with `config = "path::to::fn"`.
+///
+/// Settings made on a test take precedence over the `QUICKCHECK_*` environment
+/// variables, which take precedence over the crate's defaults from [config!], which take
+/// precedence over those in `quickcheck_async.toml`.
+///
+/// [config!]: crate::config
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    tests: Option<u64>,
+    duration: Option<Duration>,
+    gen_size: Option<usize>,
+    seed: Option<u64>,
+    rng: Option<Rng>,
+    max_discard_ratio: Option<f64>,
+    shrink: Option<bool>,
+    max_shrink_iters: Option<u64>,
+    shrink_timeout: Option<Duration>,
+    shrink_parallelism: Option<usize>,
+    shrink_progress: Option<bool>,
+    shrink_by: Option<ShrinkBy>,
+    shrink_checkpoint: Option<PathBuf>,
+    resume_shrink: Option<bool>,
+    progress: Option<Progress>,
+    verbose: Option<bool>,
+    report: Option<Report>,
+    growth: Option<Growth>,
+    ignore_env: Option<bool>,
+    retries: Option<u32>,
+    max_depth: Option<u32>,
+    ci_scale: Option<u64>,
+    ci_var: Option<&'static str>,
+    deterministic: Option<bool>,
+    fail_fast: Option<bool>,
+    confidence: Option<f64>,
+    failure_rate: Option<f64>,
+    expect: Option<Expect>,
+    should_panic: Option<Option<&'static str>>,
+    examples: Option<Examples>,
+    file: Option<Box<Config>>,
+}
+
+/// The cases a test gives to run before generated ones, as a `Vec` of its arguments.
+#[derive(Clone)]
+struct Examples(Arc<dyn Fn() -> Box<dyn Any> + Send + Sync>);
+
+impl Debug for Examples {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Examples")
+    }
+}
+
+impl Config {
+    /// A configuration that defers entirely to the environment and crate defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The settings made by environment variables.
+    fn from_env() -> Self {
+        Config {
+            tests: env_var("QUICKCHECK_TESTS"),
+            duration: None,
+            gen_size: env_var("QUICKCHECK_GENERATOR_SIZE"),
+            seed: env_var("QUICKCHECK_ASYNC_SEED").map(|Seed(seed)| seed),
+            rng: env_var("QUICKCHECK_ASYNC_RNG"),
+            max_discard_ratio: None,
+            shrink: None,
+            max_shrink_iters: None,
+            shrink_timeout: None,
+            shrink_parallelism: None,
+            shrink_progress: None,
+            shrink_by: None,
+            shrink_checkpoint: None,
+            resume_shrink: env_var("QUICKCHECK_ASYNC_RESUME_SHRINK"),
+            progress: None,
+            verbose: None,
+            report: env_var("QUICKCHECK_ASYNC_REPORT"),
+            growth: None,
+            ignore_env: None,
+            retries: None,
+            max_depth: None,
+            ci_scale: None,
+            ci_var: None,
+            deterministic: None,
+            fail_fast: env_var("QUICKCHECK_ASYNC_FAIL_FAST"),
+            confidence: None,
+            failure_rate: None,
+            expect: None,
+            should_panic: None,
+            examples: None,
+            file: None,
+        }
+    }
+
+    /// Fill any settings not made in `self` from `other`.
+    fn or(self, other: Config) -> Self {
+        Config {
+            tests: self.tests.or(other.tests),
+            duration: self.duration.or(other.duration),
+            gen_size: self.gen_size.or(other.gen_size),
+            seed: self.seed.or(other.seed),
+            // a seed only makes sense for ChaCha8, so it implies that choice
+            rng: self.rng.or(self.seed.map(|_| Rng::ChaCha8)).or(other.rng),
+            max_discard_ratio: self.max_discard_ratio.or(other.max_discard_ratio),
+            shrink: self.shrink.or(other.shrink),
+            max_shrink_iters: self.max_shrink_iters.or(other.max_shrink_iters),
+            shrink_timeout: self.shrink_timeout.or(other.shrink_timeout),
+            shrink_parallelism: self.shrink_parallelism.or(other.shrink_parallelism),
+            shrink_progress: self.shrink_progress.or(other.shrink_progress),
+            shrink_by: self.shrink_by.or(other.shrink_by),
+            shrink_checkpoint: self.shrink_checkpoint.or(other.shrink_checkpoint),
+            resume_shrink: self.resume_shrink.or(other.resume_shrink),
+            progress: self.progress.or(other.progress),
+            verbose: self.verbose.or(other.verbose),
+            report: self.report.or(other.report),
+            growth: self.growth.or(other.growth),
+            ignore_env: self.ignore_env.or(other.ignore_env),
+            retries: self.retries.or(other.retries),
+            max_depth: self.max_depth.or(other.max_depth),
+            ci_scale: self.ci_scale.or(other.ci_scale),
+            ci_var: self.ci_var.or(other.ci_var),
+            deterministic: self.deterministic.or(other.deterministic),
+            fail_fast: self.fail_fast.or(other.fail_fast),
+            confidence: self.confidence.or(other.confidence),
+            failure_rate: self.failure_rate.or(other.failure_rate),
+            expect: self.expect.or(other.expect),
+            should_panic: self.should_panic.or(other.should_panic),
+            examples: self.examples.or(other.examples),
+            file: self.file.or(other.file),
+        }
+    }
+
+    /// Fill any settings not made in `self` from the environment, then crate defaults.
+    fn resolve(mut self) -> Self {
+        let file = self.file.take().map_or_else(Config::new, |file| *file);
+        let defaults = defaults().clone().or(file);
+
+        if self.ignore_env.or(defaults.ignore_env).unwrap_or(false) {
+            return self.or(defaults);
+        }
+
+        let env = Config::from_env();
+        // QUICKCHECK_TESTS already says exactly how many cases to run
+        let scale = self.tests.is_some() || env.tests.is_none();
+        let mut config = self.or(env).or(defaults);
+
+        if let Some(factor) = config.ci_scale.filter(|_| scale && config.in_ci()) {
+            config.tests = Some(config.tests.unwrap_or(DEFAULT_TESTS).saturating_mul(factor));
+        }
+
+        config
+    }
+
+    /// Whether the variable marking a CI run is set to anything but `false` or `0`.
+    fn in_ci(&self) -> bool {
+        match env::var(self.ci_var.unwrap_or("CI")) {
+            Ok(v) => !matches!(v.trim(), "" | "false" | "0"),
+            Err(_) => false,
+        }
+    }
+
+    /// Read `var`, unless the environment is ignored.
+    fn env_or<T: FromStr>(&self, var: &str, default: T) -> T {
+        if self.ignore_env.unwrap_or(false) {
+            default
+        } else {
+            env_or(var, default)
+        }
+    }
+
+    /// Choose whether to ignore the `QUICKCHECK_*` environment variables, so that only
+    /// settings made in code apply.
+    pub fn ignore_env(mut self, ignore: bool) -> Self {
+        self.ignore_env = Some(ignore);
+        self
+    }
+
+    /// Set the number of passing cases required.
+    pub fn tests(mut self, n: u64) -> Self {
+        self.tests = Some(n);
+        self
+    }
+
+    /// Multiply the number of passing cases required by `factor` when running in CI.
+    pub fn ci_scale(mut self, factor: u64) -> Self {
+        self.ci_scale = Some(factor);
+        self
+    }
+
+    /// Set the variable marking a CI run for [ci_scale](Config::ci_scale), rather than
+    /// `CI`.
+    pub fn ci_var(mut self, var: &'static str) -> Self {
+        self.ci_var = Some(var);
+        self
+    }
+
+    /// Stop early once enough distinct cases have passed to be `confidence` sure that
+    /// fewer than the [failure_rate](Config::failure_rate) of all cases would fail.
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    /// Set the rate of failing cases that [confidence](Config::confidence) rules out,
+    /// rather than 1%.
+    pub fn failure_rate(mut self, rate: f64) -> Self {
+        self.failure_rate = Some(rate);
+        self
+    }
+
+    /// The number of distinct passing cases needed to reach the configured confidence.
+    fn samples_needed(&self) -> Option<usize> {
+        let confidence = self.confidence?;
+        let rate = self.failure_rate.unwrap_or(0.01);
+
+        // each passing case has at most a 1 - rate chance if the true rate were higher
+        Some(((1.0 - confidence).ln() / (1.0 - rate).ln()).ceil() as usize)
+    }
+
+    /// Run cases until `duration` has elapsed, rather than until a number of cases have
+    /// passed.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Set the size of generated values.
+    pub fn gen_size(mut self, size: usize) -> Self {
+        self.gen_size = Some(size);
+        self
+    }
+
+    /// Choose how the size of generated values grows over a run, up to the configured
+    /// size.
+    pub fn growth(mut self, growth: Growth) -> Self {
+        self.growth = Some(growth);
+        self
+    }
+
+    /// Generate values from a fixed seed, so every run sees the same cases.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Choose whether ChaCha8 is seeded with a fixed seed, rather than at random, when no
+    /// seed is set. Every run then generates the same cases.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = Some(deterministic);
+        self
+    }
+
+    /// Choose the algorithm generating values.
+    pub fn rng(mut self, rng: Rng) -> Self {
+        self.rng = Some(rng);
+        self
+    }
+
+    /// Decide where values will come from for one run of a resolved configuration,
+    /// choosing a random seed if none was set.
+    fn source(&self) -> Source {
+        match self.rng.unwrap_or(Rng::ChaCha8) {
+            Rng::ChaCha8 => Source::Seeded(self.seed.unwrap_or_else(|| {
+                if self.deterministic.unwrap_or(false) {
+                    DETERMINISTIC_SEED
+                } else {
+                    OsRng.next_u64()
+                }
+            })),
+            Rng::Os => Source::Os,
+        }
+    }
+
+    /// Build a generator drawing from `source`, with the configured size.
+    fn gen(&self, source: Source) -> Generator {
+        let rng: Box<dyn RngCore + Send> = match source {
+            Source::Seeded(seed) => Box::new(ChaCha8Rng::seed_from_u64(seed)),
+            Source::Os => Box::new(OsRng),
+        };
+
+        let size = self.gen_size.unwrap_or(DEFAULT_GEN_SIZE);
+
+        Generator { rng, size }
+    }
+
+    /// The size of values to generate for case `n` of the run, counting from 0.
+    fn size_at(&self, n: u64) -> usize {
+        let max = self.gen_size.unwrap_or(DEFAULT_GEN_SIZE);
+        let tests = cmp::max(self.tests.unwrap_or(DEFAULT_TESTS), 1);
+
+        // cases beyond the number required, such as those run in place of discarded
+        // ones, are generated at full size
+        let frac = match self.growth.unwrap_or(Growth::Constant) {
+            Growth::Constant => 1.0,
+            Growth::Linear => (n + 1) as f64 / tests as f64,
+            Growth::Log => ((n + 2) as f64).ln() / ((tests + 1) as f64).ln(),
+        };
+
+        (max as f64 * frac.min(1.0)).round() as usize
+    }
+
+    /// Give up once more than `ratio` cases have been discarded per required passing
+    /// case, rather than after a fixed number of cases.
+    pub fn max_discard_ratio(mut self, ratio: f64) -> Self {
+        self.max_discard_ratio = Some(ratio);
+        self
+    }
+
+    /// Choose whether the property is expected to hold, or to have a counterexample.
+    pub fn expect(mut self, expect: Expect) -> Self {
+        self.expect = Some(expect);
+        self
+    }
+
+    /// Whether a run passes by finding a counterexample, rather than by not finding one.
+    fn expects_failure(&self) -> bool {
+        self.expect == Some(Expect::Fail)
+    }
+
+    /// Expect every case to panic, with a message containing `expected` if given. A case
+    /// which completes, or panics with another message, fails.
+    pub fn should_panic(mut self, expected: Option<&'static str>) -> Self {
+        self.should_panic = Some(expected);
+        self
+    }
+
+    /// Choose whether a failing case is shrunk before being reported.
+    pub fn shrink(mut self, shrink: bool) -> Self {
+        self.shrink = Some(shrink);
+        self
+    }
+
+    /// Limit how deeply generated values may nest, as counted by [recurse].
+    pub fn max_depth(mut self, depth: u32) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Run a failing case up to `retries` more times, only counting it as failed if every
+    /// attempt fails.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Fill settings made neither here, in the environment nor by [config!] from `file`,
+    /// which holds those read from `quickcheck_async.toml`.
+    ///
+    /// [config!]: crate::config
+    #[doc(hidden)]
+    pub fn file_defaults(mut self, file: Config) -> Self {
+        self.file = Some(Box::new(file));
+        self
+    }
+
+    /// Stop shrinking after `iters` candidates have been tried, reporting the smallest
+    /// failing case found so far.
+    pub fn max_shrink_iters(mut self, iters: u64) -> Self {
+        self.max_shrink_iters = Some(iters);
+        self
+    }
+
+    /// Stop shrinking once `timeout` has elapsed, reporting the smallest failing case
+    /// found so far. A candidate already running is allowed to finish.
+    pub fn shrink_timeout(mut self, timeout: Duration) -> Self {
+        self.shrink_timeout = Some(timeout);
+        self
+    }
+
+    /// Try up to `n` shrink candidates at once, taking the first of them that fails, in
+    /// the order they were made. Tests run by [quickcheck](Config::quickcheck) try them on
+    /// threads of their own, and those run by [quickcheck_local] await them together.
+    pub fn shrink_parallelism(mut self, n: usize) -> Self {
+        self.shrink_parallelism = Some(n);
+        self
+    }
+
+    /// Choose whether to print each step taken while shrinking, with which arguments
+    /// changed, how large they are shown, and how long it has taken.
+    pub fn shrink_progress(mut self, shrink_progress: bool) -> Self {
+        self.shrink_progress = Some(shrink_progress);
+        self
+    }
+
+    /// Choose what is shrunk to find a smaller failing case: the arguments themselves,
+    /// or the random choices they were generated from.
+    pub fn shrink_by(mut self, shrink_by: ShrinkBy) -> Self {
+        self.shrink_by = Some(shrink_by);
+        self
+    }
+
+    /// Save each step taken while shrinking to the file at `path`, so that a shrink which
+    /// was interrupted or timed out can be resumed with [resume_shrink].
+    ///
+    /// [resume_shrink]: Config::resume_shrink
+    pub fn shrink_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.shrink_checkpoint = Some(path.into());
+        self
+    }
+
+    /// Choose whether to carry on shrinking from the case saved by [shrink_checkpoint],
+    /// rather than testing anew. A saved case which passes now is ignored.
+    ///
+    /// [shrink_checkpoint]: Config::shrink_checkpoint
+    pub fn resume_shrink(mut self, resume_shrink: bool) -> Self {
+        self.resume_shrink = Some(resume_shrink);
+        self
+    }
+
+    /// Print how many cases have run at intervals while testing, so long runs can be told
+    /// apart from hung ones.
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Choose whether to stop early once any property in the process has failed.
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = Some(fail_fast);
+        self
+    }
+
+    /// Whether to stop early, because another property has failed.
+    fn bail(&self) -> bool {
+        let bail = self.fail_fast.unwrap_or(false) && FAILED.load(Ordering::SeqCst);
+
+        if bail {
+            eprintln!("[quickcheck_async] stopped early, as another property failed");
+        }
+
+        bail
+    }
+
+    /// Choose whether to print each case's arguments before running it.
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = Some(verbose);
+        self
+    }
+
+    /// Choose what is printed when every case passes.
+    pub fn report(mut self, report: Report) -> Self {
+        self.report = Some(report);
+        self
+    }
+
+    /// Run the cases made by `examples` before any generated ones, whatever the number of
+    /// tests. They count towards it, and are shrunk like any other case if they fail.
+    pub fn examples<A: 'static>(mut self, examples: fn() -> Vec<A>) -> Self {
+        self.examples = Some(Examples(Arc::new(move || Box::new(examples()))));
+        self
+    }
+
+    /// The examples to run, which must be of the arguments' type.
+    fn examples_of<A: 'static>(&self) -> Vec<A> {
+        match &self.examples {
+            Some(Examples(examples)) => *examples()
+                .downcast()
+                .expect("examples must be of the same type as the arguments"),
+            None => vec![],
+        }
+    }
+
+    /// Run quickcheck against `p`, blocking the current thread. Panics within a case are
+    /// treated as failures.
+    pub fn quickcheck<P: Property>(self, p: P) {
+        let should_panic = self.should_panic;
+        let call = |args| call_caught(&p, args, should_panic);
+        complete(run(self, |args| future::ready(call(args)), Some(&call)))
+    }
+
+    /// Test every possible combination of arguments, instead of generated ones.
+    pub fn exhaustive(self) -> Enumerate {
+        Enumerate(self)
+    }
+}
+
+/// How often to report progress while testing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Progress {
+    /// After every given number of cases.
+    Cases(u64),
+    /// After the given time has elapsed since the last report.
+    Every(Duration),
+}
+
+/// Reports progress through a run as configured.
+struct Reporter {
+    progress: Option<Progress>,
+    // only read when reporting by time, as not every target has a clock
+    last: Option<Instant>,
+}
+
+impl Reporter {
+    fn new(progress: Option<Progress>) -> Self {
+        let last = match progress {
+            Some(Progress::Every(_)) => Some(Instant::now()),
+            _ => None,
+        };
+
+        Reporter { progress, last }
+    }
+
+    /// Called after each case, with the totals so far.
+    fn case(&mut self, n_tests_passed: u64, n_discarded: u64) {
+        let due = match (self.progress, &mut self.last) {
+            (Some(Progress::Cases(n)), _) => {
+                n > 0 && (n_tests_passed + n_discarded).is_multiple_of(n)
+            }
+            (Some(Progress::Every(every)), Some(last)) if last.elapsed() >= every => {
+                *last = Instant::now();
+                true
+            }
+            _ => false,
+        };
+
+        if due {
+            eprintln!(
+                "[quickcheck_async] progress: {} passed, {} discarded",
+                n_tests_passed, n_discarded
+            );
+        }
+    }
+}
+
+/// A [Config] which tests every combination of arguments, rather than generating them.
+#[derive(Clone, Debug)]
+pub struct Enumerate(Config);
+
+impl Enumerate {
+    /// Run `p` against every combination of arguments, blocking the current thread.
+    /// Panics within a case are treated as failures.
+    pub fn quickcheck<P: Property>(self, p: P)
+    where
+        P::Args: Exhaustive,
+    {
+        let should_panic = self.0.should_panic;
+        complete(run_exhaustive(self, |args| {
+            future::ready(call_caught(&p, args, should_panic))
+        }))
+    }
+}
+
+/// Types with a domain small enough to test every value of.
+///
+/// This is implemented for `bool`, the 8 and 16 bit integers, and options, results and
+/// tuples of such types. Implement it for small enums to test them exhaustively:
+///
+/// ```
+/// use quickcheck_async::Exhaustive;
+///
+/// #[derive(Clone, Copy, Debug)]
+/// enum Light {
+///     Red,
+///     Amber,
+///     Green,
+/// }
+///
+/// impl Exhaustive for Light {
+///     fn all() -> Box<dyn Iterator<Item = Self>> {
+///         Box::new(vec![Light::Red, Light::Amber, Light::Green].into_iter())
+///     }
+/// }
+/// ```
+pub trait Exhaustive: Clone + 'static {
+    /// Every value of the type, each exactly once.
+    fn all() -> Box<dyn Iterator<Item = Self>>;
+}
+
+impl Exhaustive for () {
+    fn all() -> Box<dyn Iterator<Item = Self>> {
+        Box::new(iter::once(()))
+    }
+}
+
+impl Exhaustive for bool {
+    fn all() -> Box<dyn Iterator<Item = Self>> {
+        Box::new(vec![false, true].into_iter())
+    }
+}
+
+impl Exhaustive for cmp::Ordering {
+    fn all() -> Box<dyn Iterator<Item = Self>> {
+        use cmp::Ordering::*;
+        Box::new(vec![Less, Equal, Greater].into_iter())
+    }
+}
+
+macro_rules! exhaustive_int {
+    ($($ty: ty),*) => {
+        $(impl Exhaustive for $ty {
+            fn all() -> Box<dyn Iterator<Item = Self>> {
+                Box::new(<$ty>::MIN..=<$ty>::MAX)
+            }
+        })*
+    };
+}
+
+exhaustive_int!(u8, i8, u16, i16);
+
+impl<T: Exhaustive> Exhaustive for Option<T> {
+    fn all() -> Box<dyn Iterator<Item = Self>> {
+        Box::new(iter::once(None).chain(T::all().map(Some)))
+    }
+}
+
+impl<T: Exhaustive, E: Exhaustive> Exhaustive for Result<T, E> {
+    fn all() -> Box<dyn Iterator<Item = Self>> {
+        Box::new(T::all().map(Ok).chain(E::all().map(Err)))
+    }
+}
+
+macro_rules! exhaustive_tuple {
+    ($($name: ident),*) => {
+        impl<$($name: Exhaustive),*> Exhaustive for ($($name,)*) {
+            #[allow(non_snake_case, unused_assignments)]
+            fn all() -> Box<dyn Iterator<Item = Self>> {
+                $(let $name: Vec<$name> = $name::all().collect();)*
+                let lens = [$($name.len()),*];
+                let mut idx = [0; 8];
+                let mut done = lens.contains(&0);
+
+                // counts through every combination, with the last element changing fastest
+                Box::new(iter::from_fn(move || {
+                    if done {
+                        return None;
+                    }
+
+                    let mut i = 0;
+                    let value = ($({
+                        let v = $name[idx[i]].clone();
+                        i += 1;
+                        v
+                    },)*);
+
+                    done = true;
+                    for (i, &len) in lens.iter().enumerate().rev() {
+                        idx[i] += 1;
+                        if idx[i] < len {
+                            done = false;
+                            break;
+                        }
+                        idx[i] = 0;
+                    }
+
+                    Some(value)
+                }))
+            }
+        }
+    };
+}
+
+exhaustive_tuple!(A);
+exhaustive_tuple!(A, B);
+exhaustive_tuple!(A, B, C);
+exhaustive_tuple!(A, B, C, D);
+exhaustive_tuple!(A, B, C, D, E);
+exhaustive_tuple!(A, B, C, D, E, F);
+exhaustive_tuple!(A, B, C, D, E, F, G);
+exhaustive_tuple!(A, B, C, D, E, F, G, H);
+
+/// A value built with [arbitrary::Arbitrary] from generated bytes, for tests using the
+/// `arbitrary` setting. It is shrunk by shrinking those bytes, as a fuzzer would.
+#[cfg(feature = "arbitrary")]
+#[derive(Clone)]
+pub struct FromBytes<T> {
+    bytes: Vec<u8>,
+    value: T,
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T: for<'a> arbitrary::Arbitrary<'a>> FromBytes<T> {
+    fn parse(bytes: Vec<u8>) -> Option<Self> {
+        let value = arbitrary::Unstructured::new(&bytes).arbitrary().ok()?;
+        Some(FromBytes { bytes, value })
+    }
+
+    /// The value built.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T> FromBytes<T> {
+    /// A value given by an example, rather than built, so it has no bytes to shrink.
+    pub fn example(value: T) -> Self {
+        FromBytes {
+            bytes: vec![],
+            value,
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T> Arbitrary for FromBytes<T>
+where
+    T: for<'a> arbitrary::Arbitrary<'a> + Clone + Send + 'static,
+{
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        // bytes which don't describe a value are rare, so a few attempts suffice
+        (0..100)
+            .find_map(|_| Self::parse(Vec::arbitrary(g)))
+            .expect("arbitrary could not build a value from 100 sets of bytes")
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.bytes.shrink().filter_map(Self::parse))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T: Debug> Debug for FromBytes<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// A value generated by a proptest strategy, for `#[strategy(..)]` arguments, along with
+/// how to generate it again. Shrinking replays the strategy's own search: each candidate
+/// simplifies the last failing value, then complicates it as often as those before it
+/// passed.
+#[cfg(feature = "proptest")]
+#[derive(Clone)]
+pub struct Shrinking<T> {
+    seed: [u8; 32],
+    // whether each step from the generated value simplified it, or else complicated it
+    path: Vec<bool>,
+    value: T,
+}
+
+#[cfg(feature = "proptest")]
+impl<T> Shrinking<T> {
+    /// The value generated.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: Debug> Debug for Shrinking<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// Build the value tree for `seed`, then take each step of `path` through it.
+#[cfg(feature = "proptest")]
+fn replay<S: proptest::strategy::Strategy>(
+    strategy: &S,
+    seed: &[u8; 32],
+    path: &[bool],
+) -> Option<S::Tree> {
+    use proptest::{
+        strategy::ValueTree,
+        test_runner::{RngAlgorithm, TestRng, TestRunner},
+    };
+
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, seed);
+    let mut runner = TestRunner::new_with_rng(Default::default(), rng);
+    let mut tree = strategy.new_tree(&mut runner).ok()?;
+
+    for &simplify in path {
+        let moved = if simplify {
+            tree.simplify()
+        } else {
+            tree.complicate()
+        };
+        if !moved {
+            return None;
+        }
+    }
+
+    Some(tree)
+}
+
+/// Generate a value with `strategy`, seeded from `g`.
+#[cfg(feature = "proptest")]
+pub fn from_strategy<S, G>(strategy: &S, g: &mut G) -> Shrinking<S::Value>
+where
+    S: proptest::strategy::Strategy,
+    G: Gen,
+{
+    use proptest::strategy::ValueTree;
+
+    // a strategy may reject the values it draws, so a few seeds are tried
+    for _ in 0..100 {
+        let mut seed = [0; 32];
+        g.fill_bytes(&mut seed);
+
+        if let Some(tree) = replay(strategy, &seed, &[]) {
+            let value = tree.current();
+            return Shrinking {
+                seed,
+                path: vec![],
+                value,
+            };
+        }
+    }
+
+    panic!("strategy rejected every value drawn from 100 seeds")
+}
+
+/// The candidates to shrink `from` to, with the strategy which generated it.
+#[cfg(feature = "proptest")]
+pub fn shrink_strategy<S>(
+    strategy: S,
+    from: &Shrinking<S::Value>,
+) -> impl Iterator<Item = Shrinking<S::Value>>
+where
+    S: proptest::strategy::Strategy,
+{
+    use proptest::strategy::ValueTree;
+
+    let (seed, mut path) = (from.seed, from.path.clone());
+    path.push(true);
+
+    iter::from_fn(move || {
+        let tree = replay(&strategy, &seed, &path)?;
+        let candidate = Shrinking {
+            seed,
+            path: path.clone(),
+            value: tree.current(),
+        };
+        path.push(false);
+        Some(candidate)
+    })
+}
+
+/// A string generated to match a regex, for `#[matches(..)]` arguments, along with the
+/// choices made to generate it. Shrinking shrinks those choices towards the first
+/// alternative, the lowest character and the fewest repetitions, so every candidate also
+/// matches.
+#[cfg(feature = "regex")]
+#[derive(Clone)]
+pub struct Matching {
+    size: u32,
+    choices: Vec<u32>,
+    value: String,
+}
+
+#[cfg(feature = "regex")]
+impl Matching {
+    /// The string generated.
+    pub fn into_inner(self) -> String {
+        self.value
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Debug for Matching {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.value, f)
+    }
+}
+
+/// Append a string matching `hir` to `out`, calling `choose(n)` for each choice among
+/// `n` options. Unbounded repetitions repeat at most `size` more times than required.
+/// Anchors and word boundaries are ignored.
+#[cfg(feature = "regex")]
+fn emit(
+    hir: &regex_syntax::hir::Hir,
+    size: u32,
+    choose: &mut dyn FnMut(u32) -> u32,
+    out: &mut Vec<u8>,
+) {
+    use regex_syntax::hir::{Class, HirKind};
+
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => {}
+        HirKind::Literal(lit) => out.extend_from_slice(&lit.0),
+        HirKind::Class(Class::Unicode(class)) => {
+            let ranges = class.ranges();
+            let total = ranges
+                .iter()
+                .map(|r| u32::from(r.end()) - u32::from(r.start()) + 1)
+                .fold(0, u32::saturating_add);
+            assert!(total > 0, "a class in the regex matches nothing");
+
+            let mut pick = choose(total);
+            for r in ranges {
+                let len = u32::from(r.end()) - u32::from(r.start()) + 1;
+                if pick < len {
+                    // a range may span the surrogates, which aren't chars
+                    let c = char::from_u32(u32::from(r.start()) + pick).unwrap_or(r.start());
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    return;
+                }
+                pick -= len;
+            }
+        }
+        HirKind::Class(Class::Bytes(class)) => {
+            let ranges = class.ranges();
+            let total: u32 = ranges
+                .iter()
+                .map(|r| u32::from(r.end()) - u32::from(r.start()) + 1)
+                .sum();
+            assert!(total > 0, "a class in the regex matches nothing");
+
+            let mut pick = choose(total);
+            for r in ranges {
+                let len = u32::from(r.end()) - u32::from(r.start()) + 1;
+                if pick < len {
+                    out.push(r.start() + pick as u8);
+                    return;
+                }
+                pick -= len;
+            }
+        }
+        HirKind::Repetition(rep) => {
+            let max = rep.max.unwrap_or_else(|| rep.min.saturating_add(size));
+            let n = rep.min + choose((max - rep.min).saturating_add(1));
+            for _ in 0..n {
+                emit(&rep.sub, size, choose, out);
+            }
+        }
+        HirKind::Capture(cap) => emit(&cap.sub, size, choose, out),
+        HirKind::Concat(hirs) => {
+            for hir in hirs {
+                emit(hir, size, choose, out);
+            }
+        }
+        HirKind::Alternation(hirs) => {
+            let i = choose(hirs.len() as u32);
+            emit(&hirs[i as usize], size, choose, out);
+        }
+    }
+}
+
+/// Generate a string matching `pattern`, replaying `choices` and then taking each further
+/// choice from `draw`.
+#[cfg(feature = "regex")]
+fn matching(pattern: &str, size: u32, choices: &[u32], draw: &mut dyn FnMut() -> u32) -> Matching {
+    let hir = regex_syntax::parse(pattern)
+        .unwrap_or_else(|err| panic!("invalid regex {:?}: {}", pattern, err));
+
+    let mut made = Vec::new();
+    let mut replay = choices.iter();
+    let mut choose = |n: u32| {
+        // a single option isn't a choice, so isn't recorded
+        if n <= 1 {
+            return 0;
+        }
+        let choice = replay.next().copied().unwrap_or_else(&mut *draw) % n;
+        made.push(choice);
+        choice
+    };
+
+    let mut out = Vec::new();
+    emit(&hir, size, &mut choose, &mut out);
+
+    Matching {
+        size,
+        choices: made,
+        value: String::from_utf8(out).expect("regex matched invalid utf-8"),
+    }
+}
+
+/// Generate a string matching `pattern`, drawing choices from `g`.
+#[cfg(feature = "regex")]
+pub fn from_regex<G: Gen>(pattern: &str, g: &mut G) -> Matching {
+    let size = g.size() as u32;
+    matching(pattern, size, &[], &mut || g.next_u32())
+}
+
+/// The candidates to shrink `from` to, which was generated to match `pattern`.
+#[cfg(feature = "regex")]
+pub fn shrink_regex(pattern: &'static str, from: &Matching) -> impl Iterator<Item = Matching> {
+    let (size, value) = (from.size, from.value.clone());
+    from.choices
+        .shrink()
+        // choices past those given are the simplest
+        .map(move |choices| matching(pattern, size, &choices, &mut || 0))
+        .filter(move |candidate| candidate.value != value)
+}
+
+/// A value made by passing a generated one through a fn, for `#[map(f)]` and
+/// `#[flat_map(f)]` arguments. It shrinks as the generated value does, passing each
+/// candidate through the fn again, so the fn can make any value valid.
+pub struct Mapped<T> {
+    value: T,
+    raw: Box<dyn Remap<T>>,
+}
+
+impl<T> Mapped<T> {
+    /// The value made.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// The values made from each candidate the generated value shrinks to.
+    pub fn shrink(&self) -> Box<dyn Iterator<Item = Mapped<T>>> {
+        self.raw.shrink()
+    }
+}
+
+impl<T: Clone> Clone for Mapped<T> {
+    fn clone(&self) -> Self {
+        Mapped {
+            value: self.value.clone(),
+            raw: self.raw.clone_box(),
+        }
+    }
+}
+
+impl<T: Debug> Debug for Mapped<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// The generated value of a [Mapped], along with the fn it was passed through.
+trait Remap<T>: Send {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Mapped<T>>>;
+    fn clone_box(&self) -> Box<dyn Remap<T>>;
+}
+
+struct Raw<A, F> {
+    raw: A,
+    f: F,
+}
+
+impl<A, T, F> Remap<T> for Raw<A, F>
+where
+    A: Arbitrary,
+    T: 'static,
+    F: Fn(A) -> T + Clone + Send + 'static,
+{
+    fn shrink(&self) -> Box<dyn Iterator<Item = Mapped<T>>> {
+        let f = self.f.clone();
+        Box::new(self.raw.shrink().map(move |raw| remap(raw, f.clone())))
+    }
+
+    fn clone_box(&self) -> Box<dyn Remap<T>> {
+        Box::new(Raw {
+            raw: self.raw.clone(),
+            f: self.f.clone(),
+        })
+    }
+}
+
+fn remap<A, T, F>(raw: A, f: F) -> Mapped<T>
+where
+    A: Arbitrary,
+    T: 'static,
+    F: Fn(A) -> T + Clone + Send + 'static,
+{
+    Mapped {
+        value: f(raw.clone()),
+        raw: Box::new(Raw { raw, f }),
+    }
+}
+
+/// Generate a value of the type `f` takes, and pass it through `f`.
+pub fn mapped<A, T, F, G>(g: &mut G, f: F) -> Mapped<T>
+where
+    A: Arbitrary,
+    T: 'static,
+    F: Fn(A) -> T + Clone + Send + 'static,
+    G: Gen,
+{
+    remap(A::arbitrary(g), f)
+}
+
+/// The generated value of a [Mapped] made by `#[flat_map(f)]`, along with the seed of
+/// the generator `f` was given.
+struct FlatRaw<A, F> {
+    raw: A,
+    fork: Fork,
+    f: F,
+}
+
+impl<A, T, F> Remap<T> for FlatRaw<A, F>
+where
+    A: Arbitrary,
+    T: 'static,
+    F: Fn(A, &mut Generator) -> T + Clone + Send + 'static,
+{
+    // the generated value shrinks first, then the generator it parameterizes is given
+    // ever smaller sizes, so what it makes is simpler
+    fn shrink(&self) -> Box<dyn Iterator<Item = Mapped<T>>> {
+        let (f, fork, raw) = (self.f.clone(), self.fork.clone(), self.raw.clone());
+        let raws = {
+            let (f, fork) = (f.clone(), fork.clone());
+            self.raw
+                .shrink()
+                .map(move |raw| flat_remap(raw, fork.clone(), f.clone()))
+        };
+        let size = fork.size;
+        let sizes = (1..usize::BITS)
+            .map(move |i| size >> i)
+            .take_while(|&size| size > 0)
+            .map(move |size| {
+                let fork = Fork {
+                    size,
+                    ..fork.clone()
+                };
+                flat_remap(raw.clone(), fork, f.clone())
+            });
+        Box::new(raws.chain(sizes))
+    }
+
+    fn clone_box(&self) -> Box<dyn Remap<T>> {
+        Box::new(FlatRaw {
+            raw: self.raw.clone(),
+            fork: self.fork.clone(),
+            f: self.f.clone(),
+        })
+    }
+}
+
+fn flat_remap<A, T, F>(raw: A, fork: Fork, f: F) -> Mapped<T>
+where
+    A: Arbitrary,
+    T: 'static,
+    F: Fn(A, &mut Generator) -> T + Clone + Send + 'static,
+{
+    Mapped {
+        value: f(raw.clone(), &mut fork.generator()),
+        raw: Box::new(FlatRaw { raw, fork, f }),
+    }
+}
+
+/// Generate a value of the type `f` takes, and pass it to `f` along with a generator.
+pub fn flat_mapped<A, T, F, G>(g: &mut G, f: F) -> Mapped<T>
+where
+    A: Arbitrary,
+    T: 'static,
+    F: Fn(A, &mut Generator) -> T + Clone + Send + 'static,
+    G: Gen,
+{
+    let raw = A::arbitrary(g);
+    flat_remap(raw, Fork::arbitrary(g), f)
+}
+
+/// Numeric types that can be generated within bounds, for `#[range(..)]` arguments.
+pub trait InRange: Sized {
+    /// Generate a value in `range`, uniformly.
+    fn in_range<G: Gen, R: RangeBounds<Self>>(g: &mut G, range: R) -> Self;
+}
+
+macro_rules! in_range_int {
+    ($($ty: ty),*) => {
+        $(impl InRange for $ty {
+            fn in_range<G: Gen, R: RangeBounds<Self>>(g: &mut G, range: R) -> Self {
+                // every supported type fits in an i128, along with the width of its domain
+                let lo = match range.start_bound() {
+                    Bound::Included(&lo) => lo as i128,
+                    Bound::Excluded(&lo) => lo as i128 + 1,
+                    Bound::Unbounded => <$ty>::MIN as i128,
+                };
+                let hi = match range.end_bound() {
+                    Bound::Included(&hi) => hi as i128,
+                    Bound::Excluded(&hi) => hi as i128 - 1,
+                    Bound::Unbounded => <$ty>::MAX as i128,
+                };
+                assert!(lo <= hi, "cannot generate a value in an empty range");
+
+                let width = (hi - lo) as u128 + 1;
+                let r = (u128::from(g.next_u64()) << 64 | u128::from(g.next_u64())) % width;
+                (lo + r as i128) as $ty
+            }
+        })*
+    };
+}
+
+in_range_int!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+
+macro_rules! in_range_float {
+    ($($ty: ty),*) => {
+        $(impl InRange for $ty {
+            fn in_range<G: Gen, R: RangeBounds<Self>>(g: &mut G, range: R) -> Self {
+                let (lo, hi) = match (range.start_bound(), range.end_bound()) {
+                    (Bound::Included(&lo) | Bound::Excluded(&lo), Bound::Included(&hi)) => {
+                        (lo, hi)
+                    }
+                    (Bound::Included(&lo) | Bound::Excluded(&lo), Bound::Excluded(&hi)) => {
+                        assert!(lo < hi, "cannot generate a value in an empty range");
+                        (lo, hi)
+                    }
+                    _ => panic!("a range of floats must be bounded"),
+                };
+                assert!(lo <= hi, "cannot generate a value in an empty range");
+
+                // a uniform value in [0, 1), with as many bits as an f64 holds
+                let unit = (g.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+                let x = lo + (hi - lo) * unit as $ty;
+
+                // rounding may reach an excluded end, which is replaced by the start
+                if range.contains(&x) {
+                    x
+                } else {
+                    lo
+                }
+            }
+        })*
+    };
+}
+
+in_range_float!(f32, f64);
+
+/// Types which can shrink towards a value other than their default, for
+/// `#[shrink_toward(target)]` arguments.
+pub trait Toward: Sized {
+    /// Values from `target` towards `self`: `target` itself, then halfway there, then
+    /// closer and closer to `self`, as a binary search would try.
+    fn toward(&self, target: Self) -> Box<dyn Iterator<Item = Self>>;
+}
+
+/// The offsets from a target to try for a value `distance` away from it.
+fn halfway(distance: i128) -> impl Iterator<Item = i128> {
+    let halves = iter::successors(Some(distance / 2), |h| Some(h / 2)).take_while(|&h| h != 0);
+    let offsets = iter::once(0).chain(halves.map(move |h| distance - h));
+    offsets.filter(move |_| distance != 0)
+}
+
+macro_rules! toward_int {
+    ($($ty: ty),*) => {
+        $(impl Toward for $ty {
+            fn toward(&self, target: Self) -> Box<dyn Iterator<Item = Self>> {
+                // every supported type fits in an i128, along with the distance between values
+                let (x, target) = (*self as i128, target as i128);
+                Box::new(halfway(x - target).map(move |offset| (target + offset) as $ty))
+            }
+        })*
+    };
+}
+
+toward_int!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+
+macro_rules! toward_float {
+    ($($ty: ty),*) => {
+        $(impl Toward for $ty {
+            fn toward(&self, target: Self) -> Box<dyn Iterator<Item = Self>> {
+                let x = *self;
+                // anything infinite or NaN shrinks straight to the target
+                let distance = x - target;
+                let halves = (1..24).map(move |k| x - distance / (1u32 << k) as $ty);
+                let mut last = None;
+                let candidates = iter::once(target)
+                    .chain(halves.filter(move |_| distance.is_finite()))
+                    .filter(move |&y| y != x && last.replace(y) != Some(y));
+                Box::new(candidates.filter(move |_| x != target))
+            }
+        })*
+    };
+}
+
+toward_float!(f32, f64);
+
+impl Toward for Duration {
+    fn toward(&self, target: Self) -> Box<dyn Iterator<Item = Self>> {
+        let (x, target) = (self.as_nanos() as i128, target.as_nanos() as i128);
+        Box::new(halfway(x - target).map(move |offset| {
+            let nanos = (target + offset) as u128;
+            Duration::new(
+                (nanos / 1_000_000_000) as u64,
+                (nanos % 1_000_000_000) as u32,
+            )
+        }))
+    }
+}
+
+/// An algorithm generating random values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rng {
+    /// ChaCha8, which is portable and reproducible from a seed.
+    ChaCha8,
+    /// The operating system's entropy source, which cannot be replayed.
+    Os,
+}
+
+impl FromStr for Rng {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "chacha8" => Ok(Rng::ChaCha8),
+            "os" => Ok(Rng::Os),
+            s => Err(format!(
+                "unknown rng {:?}; expected \"chacha8\" or \"os\"",
+                s
+            )),
+        }
+    }
+}
+
+/// The result a property is expected to have.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Expect {
+    /// Every case passes.
+    Pass,
+    /// Some case fails, within the number of cases run.
+    Fail,
+}
+
+/// What to print when every case passes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Report {
+    /// The number of discarded cases, if any were.
+    Default,
+    /// Nothing.
+    Quiet,
+    /// One line of `key=value` pairs, for parsing by CI tools.
+    Summary,
+}
+
+impl FromStr for Report {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "default" => Ok(Report::Default),
+            "quiet" => Ok(Report::Quiet),
+            "summary" => Ok(Report::Summary),
+            s => Err(format!(
+                "unknown report {:?}; expected \"default\", \"quiet\" or \"summary\"",
+                s
+            )),
+        }
+    }
+}
+
+/// Where the values generated during one run come from.
+#[derive(Clone, Copy, Debug)]
+pub enum Source {
+    /// ChaCha8, from the given seed.
+    Seeded(u64),
+    /// The operating system's entropy source.
+    Os,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Seeded(seed) => write!(
+                f,
+                "[quickcheck_async] seed: {:#x} (set QUICKCHECK_ASYNC_SEED={:#x} to replay)",
+                seed, seed
+            ),
+            Source::Os => write!(
+                f,
+                "[quickcheck_async] rng: os (this run cannot be replayed)"
+            ),
+        }
+    }
+}
+
+/// How the size of generated values grows over a run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Growth {
+    /// Every case is generated at the configured size.
+    Constant,
+    /// Sizes grow evenly from small, reaching the configured size on the last case.
+    Linear,
+    /// Sizes grow quickly from small, so that most cases are generated near the
+    /// configured size.
+    Log,
+}
+
+/// What is shrunk to find a smaller failing case.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShrinkBy {
+    /// The arguments, as their [Arbitrary] impls shrink them.
+    Values,
+    /// The random choices the arguments were generated from, which are made shorter and
+    /// smaller before generating them again. Whatever the generators ensure of a value,
+    /// such as an index being within a list, holds for every candidate this way, though
+    /// arguments given as examples are still shrunk by value.
+    Choices,
+}
+
+/// A [Gen] drawing from the [Rng] chosen for a run.
+///
+/// A test fn may take a `&mut Generator` argument, to generate values as it runs which
+/// depend on those it was called with. It is seeded from the case's other arguments, so
+/// a case draws the same values from it each time it is run, including while shrinking.
+///
+/// ```
+/// use quickcheck::Arbitrary;
+/// use quickcheck_async::Generator;
+///
+/// #[quickcheck_async::tokio]
+/// async fn index(v: Vec<u8>, g: &mut Generator) -> bool {
+///     if v.is_empty() {
+///         return true;
+///     }
+///     let i = usize::arbitrary(g) % v.len();
+///     v.get(i).is_some()
+/// }
+/// ```
+pub struct Generator {
+    pub(crate) rng: Box<dyn RngCore + Send>,
+    pub(crate) size: usize,
+}
+
+/// The seed of a [Generator] passed to a test fn, generated as one of its arguments.
+#[derive(Clone)]
+pub struct Fork {
+    seed: u64,
+    size: usize,
+}
+
+impl Fork {
+    /// The generator this seeds.
+    pub fn generator(&self) -> Generator {
+        Generator {
+            rng: Box::new(ChaCha8Rng::seed_from_u64(self.seed)),
+            size: self.size,
+        }
+    }
+}
+
+/// Call `f` with the generator `fork` seeds, for `#[dependent(f)]` arguments.
+pub fn dependent<T, F: FnOnce(&mut Generator) -> T>(fork: &Fork, f: F) -> T {
+    f(&mut fork.generator())
+}
+
+/// A generator of size `size` seeded from `g`, for `#[size(n)]` arguments.
+pub fn resized<G: Gen>(g: &mut G, size: usize) -> Generator {
+    Fork {
+        seed: g.next_u64(),
+        size,
+    }
+    .generator()
+}
+
+// made for examples, which don't give a generator's seed, at the default size
+impl Default for Fork {
+    fn default() -> Self {
+        Fork {
+            seed: 0,
+            size: DEFAULT_GEN_SIZE,
+        }
+    }
+}
+
+impl Arbitrary for Fork {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Fork {
+            seed: g.next_u64(),
+            size: g.size(),
+        }
+    }
+}
+
+impl Debug for Fork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Generator {{ seed: {:#x}, size: {} }}",
+            self.seed, self.size
+        )
+    }
+}
+
+impl RngCore for Generator {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl Gen for Generator {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Types whose values are generated asynchronously, for those which need IO to make,
+/// such as a reserved port or a freshly created database schema.
+///
+/// A test fn argument marked `#[async_arbitrary]` is made by awaiting [arbitrary] at the
+/// start of each case, on the runtime the test runs on. Such arguments are reported as the
+/// seed of the [Generator] they were made with, which makes the same value when the case
+/// is replayed, but are not shrunk.
+///
+/// [arbitrary]: AsyncArbitrary::arbitrary
+///
+/// ```
+/// use quickcheck::Arbitrary;
+/// use quickcheck_async::{AsyncArbitrary, Generator};
+///
+/// struct Port(u16);
+///
+/// impl AsyncArbitrary for Port {
+///     async fn arbitrary(g: &mut Generator) -> Self {
+///         // a real implementation could bind a listener to find a free port
+///         Port(1024 + u16::arbitrary(g) % 1000)
+///     }
+/// }
+///
+/// #[quickcheck_async::tokio]
+/// async fn unprivileged(#[async_arbitrary] port: Port) -> bool {
+///     port.0 >= 1024
+/// }
+/// ```
+pub trait AsyncArbitrary: Sized {
+    /// Make a value, drawing on `g` for any randomness.
+    fn arbitrary(g: &mut Generator) -> impl Future<Output = Self>;
+}
+
+/// Pick one of `n` enum variants uniformly, for `#[derive(AsyncArbitrary)]`.
+pub fn variant(g: &mut Generator, n: usize) -> usize {
+    (g.next_u64() % n as u64) as usize
+}
+
+/// Pick an index into `weights`, each as often as its share of their sum.
+pub fn weighted<G: Gen>(g: &mut G, weights: &[u32]) -> usize {
+    let total: u64 = weights.iter().map(|&w| u64::from(w)).sum();
+    assert!(total > 0, "at least one weight must be above zero");
+
+    let mut pick = g.next_u64() % total;
+    for (i, &w) in weights.iter().enumerate() {
+        match pick.checked_sub(u64::from(w)) {
+            Some(rest) => pick = rest,
+            None => return i,
+        }
+    }
+    unreachable!()
+}
+
+/// A seed written in decimal, or in hex with a leading `0x`.
+struct Seed(u64);
+
+impl FromStr for Seed {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).map(Seed),
+            None => s.parse().map(Seed),
+        }
+    }
+}
+
+fn env_var<T: FromStr>(var: &str) -> Option<T> {
+    env::var(var).ok()?.parse().ok()
+}
+
+fn env_or<T: FromStr>(var: &str, default: T) -> T {
+    env_var(var).unwrap_or(default)
+}
+
+/// Crate-wide defaults, registered by [config!].
+///
+/// [config!]: crate::config
+pub struct Defaults(pub fn() -> Config);
+
+inventory::collect!(Defaults);
+
+fn defaults() -> &'static Config {
+    static DEFAULTS: OnceLock<Config> = OnceLock::new();
+
+    DEFAULTS.get_or_init(|| {
+        let mut all = inventory::iter::<Defaults>.into_iter();
+
+        match (all.next(), all.next()) {
+            (None, _) => Config::new(),
+            (Some(Defaults(config)), None) => config(),
+            (Some(_), Some(_)) => panic!("quickcheck_async::config! was invoked more than once"),
+        }
+    })
+}
+
+fn failed_msg<A: Debug>(args: &A, err: Option<String>) -> String {
+    match err {
+        None => format!("[quickcheck] TEST FAILED. Arguments: {:?}", args),
+        Some(err) => format!(
+            "[quickcheck] TEST FAILED (runtime error). Arguments: {:?}\nError: {}",
+            args, err
+        ),
+    }
+}
+
+/// A test fn which can be called with generated arguments.
+///
+/// This is implemented for fn pointers of up to 8 arguments, like [quickcheck::Testable].
+/// It's called from several threads at once while shrinking in parallel.
+pub trait Property: Sync {
+    /// The arguments to generate, as a tuple.
+    type Args: Arguments;
+    /// The result of calling the fn.
+    type Output: AsyncTestable;
+
+    /// Call the fn with `args`.
+    fn call(&self, args: Self::Args) -> Self::Output;
+}
+
+macro_rules! property_fn {
+    ($($name: ident),*) => {
+        impl<T: AsyncTestable, $($name: Arbitrary + Debug),*> Property for fn($($name),*) -> T {
+            type Args = ($($name,)*);
+            type Output = T;
+
+            #[allow(non_snake_case)]
+            fn call(&self, ($($name,)*): Self::Args) -> T {
+                self($($name),*)
+            }
+        }
+    };
+}
+
+property_fn!();
+property_fn!(A);
+property_fn!(A, B);
+property_fn!(A, B, C);
+property_fn!(A, B, C, D);
+property_fn!(A, B, C, D, E);
+property_fn!(A, B, C, D, E, F);
+property_fn!(A, B, C, D, E, F, G);
+property_fn!(A, B, C, D, E, F, G, H);
+
+/// The tuples of arguments a test fn takes, which can shrink one argument at a time.
+///
+/// Shrinking tries each argument by itself first, then pairs of arguments shrunk
+/// together, as correlated arguments often can't be shrunk one at a time: deleting an
+/// element of a list may only fail if an index into it is decremented too.
+pub trait Arguments: Arbitrary + Debug {
+    /// How many arguments there are.
+    const LEN: usize;
+
+    /// Simpler tuples in which only argument `k` is shrunk, by the shrinker registered
+    /// for its type if there is one, or else as its type would.
+    fn shrink_one(&self, k: usize) -> Box<dyn Iterator<Item = Self>>;
+}
+
+macro_rules! arguments_tuple {
+    ($len: expr; $($name: ident $index: tt),*) => {
+        impl<$($name: Arbitrary + Debug),*> Arguments for ($($name,)*) {
+            const LEN: usize = $len;
+
+            #[allow(unused_variables)]
+            fn shrink_one(&self, k: usize) -> Box<dyn Iterator<Item = Self>> {
+                $(if k == $index {
+                    let args = self.clone();
+                    let candidates = registered(&self.$index).unwrap_or_else(|| self.$index.shrink());
+                    return Box::new(candidates.map(move |arg| {
+                        let mut args = args.clone();
+                        args.$index = arg;
+                        args
+                    }));
+                })*
+                Box::new(iter::empty())
+            }
+        }
+    };
+}
+
+arguments_tuple!(0;);
+arguments_tuple!(1; A 0);
+arguments_tuple!(2; A 0, B 1);
+arguments_tuple!(3; A 0, B 1, C 2);
+arguments_tuple!(4; A 0, B 1, C 2, D 3);
+arguments_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+arguments_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+arguments_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+arguments_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// The candidates to shrink `args` to by value: each argument by itself, as
+/// [Arguments::shrink_one] shrinks it, then each pair of arguments shrunk together.
+/// Pairs are only made once the single candidates run out, as there are many more.
+pub fn shrink_args<A: Arguments>(args: &A) -> impl Iterator<Item = A> {
+    let pairs = (0..A::LEN).flat_map(|k| (k + 1..A::LEN).map(move |l| (k, l)));
+    let together = {
+        let args = args.clone();
+        pairs.flat_map(move |(k, l)| args.shrink_one(k).flat_map(move |args| args.shrink_one(l)))
+    };
+    let alone = {
+        let args = args.clone();
+        (0..A::LEN).flat_map(move |k| args.shrink_one(k))
+    };
+    alone.chain(together)
+}
+
+/// The payload unwound by [discard!], marking the running case as discarded.
+///
+/// [discard!]: crate::discard
+pub struct Discard;
+
+/// Stop the running case, marking it as discarded.
+pub fn discard() -> ! {
+    // resuming rather than panicking skips the panic hook, which would print a message
+    panic::resume_unwind(Box::new(Discard))
+}
+
+thread_local! {
+    /// Where the last panic on this thread was raised, as recorded by [hook].
+    static LAST_PANIC: RefCell<Option<Origin>> = const { RefCell::new(None) };
+}
+
+/// Where a panic was raised, and the backtrace captured there.
+struct Origin {
+    location: Option<String>,
+    backtrace: Backtrace,
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "\n  at {}", location)?;
+        }
+        if let BacktraceStatus::Captured = self.backtrace.status() {
+            write!(f, "\n\nstack backtrace:\n{}", self.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// A panic payload, along with where it was raised.
+struct Caught {
+    payload: Box<dyn Any + Send>,
+    origin: Origin,
+}
+
+/// Install a panic hook recording the [Origin] of each panic, ahead of the existing one.
+fn hook() {
+    static HOOK: Once = Once::new();
+
+    HOOK.call_once(|| {
+        let prev = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let origin = Origin {
+                location: info.location().map(ToString::to_string),
+                // only captured if enabled, as by RUST_BACKTRACE
+                backtrace: Backtrace::capture(),
+            };
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(origin));
+            prev(info)
+        }));
+    });
+}
+
+/// Call `f`, catching any panic along with where it was raised.
+fn catch<T>(f: impl FnOnce() -> T) -> Result<T, Box<dyn Any + Send>> {
+    hook();
+    LAST_PANIC.with(|last| last.borrow_mut().take());
+
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        match LAST_PANIC.with(|last| last.borrow_mut().take()) {
+            Some(origin) if !payload.is::<Discard>() && !payload.is::<Filtered>() => {
+                Box::new(Caught { payload, origin })
+            }
+            _ => payload,
+        }
+    })
+}
+
+/// Separate a payload from where it was raised, if that is known.
+fn uncatch(payload: Box<dyn Any + Send>) -> (Box<dyn Any + Send>, Option<Origin>) {
+    match payload.downcast::<Caught>() {
+        Ok(caught) => (caught.payload, Some(caught.origin)),
+        Err(payload) => (payload, None),
+    }
+}
+
+/// The payload unwound by a test's filter, marking the running case as discarded.
+struct Filtered;
+
+thread_local! {
+    /// How many cases run on this thread have been discarded by a filter.
+    static FILTERED: Cell<u64> = const { Cell::new(0) };
+    /// How deeply the value being generated on this thread is nested, and the most allowed.
+    static DEPTH: Cell<(u32, u32)> = const { Cell::new((0, u32::MAX)) };
+}
+
+/// Generate a nested part of a recursive value with `f`, unless the value is already
+/// nested as deeply as `max_depth` allows, in which case `None` is returned and a leaf
+/// should be generated instead.
+///
+/// quickcheck's size alone tends to make recursive values either trivial or explosively
+/// large. Calling this around each recursive call of an [Arbitrary] impl bounds them.
+///
+/// ```
+/// use quickcheck::{Arbitrary, Gen};
+///
+/// #[derive(Clone, Debug)]
+/// enum Tree {
+///     Leaf(u8),
+///     Node(Box<Tree>, Box<Tree>),
+/// }
+///
+/// impl Tree {
+///     fn depth(&self) -> u32 {
+///         match self {
+///             Tree::Leaf(_) => 0,
+///             Tree::Node(l, r) => 1 + l.depth().max(r.depth()),
+///         }
+///     }
+/// }
+///
+/// impl Arbitrary for Tree {
+///     fn arbitrary<G: Gen>(g: &mut G) -> Self {
+///         if bool::arbitrary(g) {
+///             let node = quickcheck_async::recurse(|| {
+///                 Tree::Node(Box::new(Tree::arbitrary(g)), Box::new(Tree::arbitrary(g)))
+///             });
+///             if let Some(node) = node {
+///                 return node;
+///             }
+///         }
+///         Tree::Leaf(u8::arbitrary(g))
+///     }
+/// }
+///
+/// #[quickcheck_async::tokio(max_depth = 4)]
+/// async fn shallow(tree: Tree) -> bool {
+///     tree.depth() <= 4
+/// }
+/// ```
+pub fn recurse<T>(f: impl FnOnce() -> T) -> Option<T> {
+    /// Leaves a level of nesting, even if `f` unwinds.
+    struct Leave;
+
+    impl Drop for Leave {
+        fn drop(&mut self) {
+            DEPTH.with(|d| d.set((d.get().0 - 1, d.get().1)));
+        }
+    }
+
+    let (depth, max) = DEPTH.with(Cell::get);
+    if depth >= max {
+        return None;
+    }
+
+    DEPTH.with(|d| d.set((depth + 1, max)));
+    let _leave = Leave;
+    Some(f())
+}
+
+/// Stop the running case, marking it as discarded by a filter.
+pub fn filtered() -> ! {
+    panic::resume_unwind(Box::new(Filtered))
+}
+
+/// A future which catches panics raised while polling the one it wraps.
+pub struct CatchUnwind<Fut>(Pin<Box<Fut>>);
+
+/// Catch panics raised while polling `fut`, so they can be carried to another thread.
+pub fn catch_unwind<Fut: Future>(fut: Fut) -> CatchUnwind<Fut> {
+    CatchUnwind(Box::pin(fut))
+}
+
+impl<Fut: Future> Future for CatchUnwind<Fut> {
+    type Output = Result<Fut::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match catch(|| self.0.as_mut().poll(cx)) {
+            Ok(Poll::Ready(out)) => Poll::Ready(Ok(out)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// Await a case, treating a [discard!] within it as a discard. Other panics are not
+/// caught, unless every case is expected to panic.
+///
+/// [discard!]: crate::discard
+async fn await_local<Fut>(fut: Fut, should_panic: Option<Option<&'static str>>) -> Status
+where
+    Fut: Future,
+    Fut::Output: AsyncTestable,
+{
+    match catch_unwind(fut).await {
+        Err(payload)
+            if should_panic.is_none() && !payload.is::<Discard>() && !payload.is::<Filtered>() =>
+        {
+            panic::resume_unwind(uncatch(payload).0)
+        }
+        out => judge(out, should_panic),
+    }
+}
+
+/// Call `p`, treating a panic as a failure unless every case is expected to panic.
+fn call_caught<P: Property>(
+    p: &P,
+    args: P::Args,
+    should_panic: Option<Option<&'static str>>,
+) -> Status {
+    judge(catch(|| p.call(args)), should_panic)
+}
+
+/// Interpret a case which may have panicked, given whether it was expected to.
+fn judge<T: AsyncTestable>(
+    out: Result<T, Box<dyn Any + Send>>,
+    should_panic: Option<Option<&'static str>>,
+) -> Status {
+    let (msg, origin) = match out {
+        Err(payload) if payload.is::<Discard>() => return Status::Discard,
+        Err(payload) if payload.is::<Filtered>() => {
+            // judged on the thread running the property, which reports the total
+            FILTERED.with(|n| n.set(n.get() + 1));
+            return Status::Discard;
+        }
+        Ok(out) => match (out.outcome(), should_panic) {
+            (Status::Discard, _) => return Status::Discard,
+            (status, None) => return status,
+            (_, Some(_)) => {
+                return Status::Fail(Some("the case did not panic".to_owned()));
+            }
+        },
+        Err(payload) => {
+            let (payload, origin) = uncatch(payload);
+            let msg = if let Some(&s) = payload.downcast_ref::<&str>() {
+                s.to_owned()
+            } else if let Some(s) = payload.downcast_ref::<String>() {
+                s.to_owned()
+            } else {
+                "UNABLE TO SHOW RESULT OF PANIC.".to_owned()
+            };
+            (msg, origin)
+        }
+    };
+
+    match should_panic {
+        None => Status::Fail(Some(match origin {
+            Some(origin) => format!("{}{}", msg, origin),
+            None => msg,
+        })),
+        Some(Some(expected)) if !msg.contains(expected) => Status::Fail(Some(format!(
+            "the case panicked with {:?}, which does not contain {:?}",
+            msg, expected
+        ))),
+        Some(_) => Status::Pass,
+    }
+}
+
+/// Poll a future that never waits to completion.
+fn complete<Fut: Future>(fut: Fut) -> Fut::Output {
+    let mut fut = Box::pin(fut);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    match fut.as_mut().poll(&mut cx) {
+        Poll::Ready(out) => out,
+        Poll::Pending => unreachable!("blocking cases are always ready"),
+    }
+}
+
+/// Run quickcheck against `f` without blocking the current thread.
+///
+/// Each case is awaited in turn on whatever executor polls the returned future, which
+/// makes this suitable for single-threaded targets. Configuration not set in `config` is
+/// read from the same `QUICKCHECK_*` environment variables as quickcheck itself. Panics
+/// within a case are not caught, other than those raised by [discard!].
+///
+/// [discard!]: crate::discard
+pub async fn quickcheck_local<A, F, Fut>(config: Config, f: F)
+where
+    A: Arguments,
+    F: Fn(A) -> Fut,
+    Fut: Future,
+    Fut::Output: AsyncTestable,
+{
+    let should_panic = config.should_panic;
+    run(config, |args| await_local(f(args), should_panic), None).await
+}
+
+/// Run `f` against every combination of arguments without blocking the current thread,
+/// as [quickcheck_local] does for generated ones.
+pub async fn exhaustive_local<A, F, Fut>(config: Enumerate, f: F)
+where
+    A: Arbitrary + Debug + Exhaustive,
+    F: Fn(A) -> Fut,
+    Fut: Future,
+    Fut::Output: AsyncTestable,
+{
+    let should_panic = config.0.should_panic;
+    run_exhaustive(config, |args| await_local(f(args), should_panic)).await
+}
+
+/// Run quickcheck against `f`. If `blocking` calls it without awaiting, shrink
+/// candidates are tried in parallel on threads with that, rather than awaited together.
+async fn run<A, F, Fut>(config: Config, f: F, blocking: Option<&(dyn Fn(A) -> Status + Sync)>)
+where
+    A: Arguments + 'static,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Status>,
+{
+    let config = config.resolve();
+    FILTERED.with(|n| n.set(0));
+    DEPTH.with(|d| d.set((0, config.max_depth.unwrap_or(u32::MAX))));
+    let tests = config.tests.unwrap_or(DEFAULT_TESTS);
+    let max_tests = cmp::max(tests, config.env_or("QUICKCHECK_MAX_TESTS", 10_000));
+    let min_tests_passed = config.env_or("QUICKCHECK_MIN_TESTS_PASSED", 0);
+    let source = config.source();
+    let mut gen = config.gen(source);
+
+    let (mut n_tests_passed, mut n_discarded): (u64, u64) = (0, 0);
+
+    // the clock is only read when a duration is set, as not every target has one
+    let deadline = config.duration.map(|d| Instant::now() + d);
+    let mut reporter = Reporter::new(config.progress);
+
+    // repeated cases tell us nothing new, so only distinct ones count towards confidence
+    let samples_needed = config.samples_needed();
+    let mut distinct = HashSet::new();
+
+    // examples always run, so the limits only apply once they have
+    let mut examples = config.examples_of::<A>().into_iter();
+
+    // a checkpointed shrink carries on where it stopped, rather than testing anew
+    let checkpointed = match (&config.shrink_checkpoint, config.resume_shrink) {
+        (Some(path), Some(true)) => resume::<A>(path),
+        _ => None,
+    };
+    if let Some(case) = checkpointed {
+        log_case(&config, "resumed", &case.args);
+        match check(&f, case.args.clone(), &config).await {
+            Status::Fail(err) => {
+                let (args, err) = shrink(&f, blocking, case, err, &config).await;
+                failed(format!("{}\n{}", failed_msg(&args, err), source));
+            }
+            _ => eprintln!("[quickcheck_async] the checkpointed case passes, so testing anew"),
+        }
+    }
+
+    loop {
+        if config.bail() {
+            return;
+        }
+
+        let example = examples.next();
+
+        match deadline {
+            _ if example.is_some() => {}
+            Some(deadline) if Instant::now() >= deadline => break,
+            Some(_) => {}
+            None if n_tests_passed >= tests => break,
+            None => {}
+        }
+
+        match (config.max_discard_ratio, deadline) {
+            _ if example.is_some() => {}
+            // a ratio replaces the cap on total cases, but exceeding it is an error
+            (Some(ratio), None) if n_discarded as f64 > ratio * tests as f64 => {
+                gave_up(n_tests_passed, n_discarded, ratio, source)
+            }
+            // a time budget bounds the run by itself
+            (Some(_), _) | (None, Some(_)) => {}
+            (None, None) if n_tests_passed + n_discarded >= max_tests => break,
+            (None, None) => {}
+        }
+
+        let case = match example {
+            Some(args) => {
+                log_case(&config, "example", &args);
+                Case {
+                    args,
+                    lineage: None,
+                }
+            }
+            None => {
+                gen.size = config.size_at(n_tests_passed + n_discarded);
+                // the choices are only noted when they're needed, each case being
+                // generated from a seed of its own so that they start from nothing
+                let case = match config.shrink_by.unwrap_or(ShrinkBy::Values) {
+                    ShrinkBy::Values if config.shrink_checkpoint.is_none() => Case {
+                        args: A::arbitrary(&mut gen),
+                        lineage: None,
+                    },
+                    _ => {
+                        let rng = Box::new(ChaCha8Rng::seed_from_u64(gen.next_u64()));
+                        Case::generate(rng, gen.size)
+                    }
+                };
+                log_case(&config, "case", &case.args);
+                case
+            }
+        };
+        let args = case.args.clone();
+
+        match check(&f, args.clone(), &config).await {
+            Status::Pass => {
+                n_tests_passed += 1;
+
+                if let Some(needed) = samples_needed {
+                    let mut hasher = DefaultHasher::new();
+                    format!("{:?}", args).hash(&mut hasher);
+
+                    if distinct.insert(hasher.finish()) && distinct.len() >= needed {
+                        break;
+                    }
+                }
+            }
+            Status::Discard => n_discarded += 1,
+            Status::Fail(err) if config.expects_failure() => {
+                return found(&config, &args, err);
+            }
+            Status::Fail(err) => {
+                let (args, err) = match config.shrink.unwrap_or(true) {
+                    true => shrink(&f, blocking, case, err, &config).await,
+                    false => (args, err),
+                };
+                failed(format!("{}\n{}", failed_msg(&args, err), source));
+            }
+        }
+
+        reporter.case(n_tests_passed, n_discarded);
+    }
+
+    // without a fixed number of cases, the ratio is to those that passed in the time given
+    if let (Some(ratio), Some(_)) = (config.max_discard_ratio, deadline) {
+        if n_discarded as f64 > ratio * n_tests_passed as f64 {
+            gave_up(n_tests_passed, n_discarded, ratio, source)
+        }
+    }
+
+    if config.expects_failure() {
+        panic!(
+            "[quickcheck_async] expected a counterexample, but {} cases passed\n{}",
+            n_tests_passed, source
+        );
+    }
+
+    if n_tests_passed < min_tests_passed {
+        panic!(
+            "(Unable to generate enough tests, {} not discarded.)",
+            n_tests_passed
+        );
+    }
+
+    report(&config, n_tests_passed, n_discarded);
+}
+
+async fn run_exhaustive<A, F, Fut>(Enumerate(config): Enumerate, f: F)
+where
+    A: Debug + Exhaustive,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Status>,
+{
+    let config = config.resolve();
+    FILTERED.with(|n| n.set(0));
+    let (mut n_tests_passed, mut n_discarded): (u64, u64) = (0, 0);
+    let mut reporter = Reporter::new(config.progress);
+
+    // enumeration runs from the simplest values, so the first failure needs no shrinking
+    for args in A::all() {
+        if config.bail() {
+            return;
+        }
+
+        log_case(&config, "case", &args);
+
+        match check(&f, args.clone(), &config).await {
+            Status::Pass => n_tests_passed += 1,
+            Status::Discard => n_discarded += 1,
+            Status::Fail(err) if config.expects_failure() => {
+                return found(&config, &args, err);
+            }
+            Status::Fail(err) => failed(failed_msg(&args, err)),
+        }
+
+        reporter.case(n_tests_passed, n_discarded);
+    }
+
+    if config.expects_failure() {
+        panic!(
+            "[quickcheck_async] expected a counterexample, but all {} cases passed",
+            n_tests_passed
+        );
+    }
+
+    if let Some(ratio) = config.max_discard_ratio {
+        if n_discarded as f64 > ratio * n_tests_passed as f64 {
+            panic!(
+                "[quickcheck_async] {} passed and {} discarded cases \
+                 (max_discard_ratio = {})",
+                n_tests_passed, n_discarded, ratio
+            );
+        }
+    }
+
+    report(&config, n_tests_passed, n_discarded);
+}
+
+/// Print the counterexample found by a run expected to find one, as configured.
+fn found<A: Debug>(config: &Config, args: &A, err: Option<String>) {
+    if config.report != Some(Report::Quiet) {
+        eprintln!(
+            "[quickcheck_async] found the expected counterexample: {}",
+            failed_msg(args, err)
+        );
+    }
+}
+
+/// Print the result of a run in which every case passed, as configured.
+fn report(config: &Config, n_tests_passed: u64, n_discarded: u64) {
+    let n_filtered = FILTERED.with(Cell::get);
+
+    match config.report.unwrap_or(Report::Default) {
+        Report::Default if n_filtered > 0 => eprintln!(
+            "[quickcheck_async] {} passed, {} discarded ({} by filter)",
+            n_tests_passed, n_discarded, n_filtered
+        ),
+        Report::Default if n_discarded > 0 => eprintln!(
+            "[quickcheck_async] {} passed, {} discarded",
+            n_tests_passed, n_discarded
+        ),
+        Report::Default | Report::Quiet => {}
+        Report::Summary => eprintln!(
+            "[quickcheck_async] result=ok passed={} discarded={} filtered={}",
+            n_tests_passed, n_discarded, n_filtered
+        ),
+    }
+}
+
+/// Print the arguments of a case about to run, if configured to.
+fn log_case<A: Debug>(config: &Config, kind: &str, args: &A) {
+    if config.verbose.unwrap_or(false) {
+        eprintln!("[quickcheck_async] {}: {:?}", kind, args);
+    }
+}
+
+/// Report a counterexample, so properties set to fail fast can stop.
+fn failed(msg: String) -> ! {
+    FAILED.store(true, Ordering::SeqCst);
+    panic!("{}", msg)
+}
+
+fn gave_up(n_tests_passed: u64, n_discarded: u64, ratio: f64, source: Source) -> ! {
+    panic!(
+        "[quickcheck_async] gave up after {} passed and {} discarded cases \
+         (max_discard_ratio = {})\n{}",
+        n_tests_passed, n_discarded, ratio, source
+    )
+}
+
+/// Run a case, retrying it as configured while it fails.
+async fn check<A, F, Fut>(f: &F, args: A, config: &Config) -> Status
+where
+    A: Clone,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Status>,
+{
+    let mut status = f(args.clone()).await;
+
+    for _ in 0..config.retries.unwrap_or(0) {
+        match status {
+            Status::Fail(_) => status = f(args.clone()).await,
+            _ => break,
+        }
+    }
+
+    status
+}
+
+/// Shrink the failing case `case`, whose arguments are `args(case)`, by trying the
+/// candidates `candidates` makes of it for one which fails too, until none do.
+async fn shrink<A, F, Fut>(
+    f: &F,
+    blocking: Option<&(dyn Fn(A) -> Status + Sync)>,
+    mut case: Case<A>,
+    mut err: Option<String>,
+    config: &Config,
+) -> (A, Option<String>)
+where
+    A: Arguments,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Status>,
+{
+    // the clock is only read when a timeout is set, as not every target has one
+    let deadline = config.shrink_timeout.map(|t| Instant::now() + t);
+    let parallelism = config.shrink_parallelism.unwrap_or(1).max(1);
+    // the clock is also only read when reporting progress
+    let start = config.shrink_progress.unwrap_or(false).then(Instant::now);
+    let (mut iters, mut steps) = (0, 0);
+    let candidates = match config.shrink_by.unwrap_or(ShrinkBy::Values) {
+        ShrinkBy::Values => Case::by_values,
+        ShrinkBy::Choices => Case::by_choices,
+    };
+    checkpoint(config.shrink_checkpoint.as_deref(), &case);
+
+    'shrink: loop {
+        let mut candidates = candidates(&case);
+
+        loop {
+            let room = match config.max_shrink_iters {
+                Some(max) => cmp::min(max.saturating_sub(iters), parallelism as u64) as usize,
+                None => parallelism,
+            };
+            if room == 0 || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break 'shrink;
+            }
+
+            let mut batch: Vec<Case<A>> = candidates.by_ref().take(room).collect();
+            if batch.is_empty() {
+                break 'shrink;
+            }
+            iters += batch.len() as u64;
+            let cases: Vec<A> = batch.iter().map(|c| c.args.clone()).collect();
+            for candidate in &cases {
+                log_case(config, "shrink", candidate);
+            }
+
+            let failure = match blocking {
+                Some(call) if cases.len() > 1 => first_failure_blocking(call, &cases, config),
+                _ => first_failure(f, &cases, config).await,
+            };
+
+            if let Some((i, e)) = failure {
+                let candidate = batch.swap_remove(i);
+                steps += 1;
+                if let Some(start) = start {
+                    report_shrink(&case.args, &candidate.args, steps, iters, start.elapsed());
+                }
+                case = candidate;
+                checkpoint(config.shrink_checkpoint.as_deref(), &case);
+                err = e;
+                continue 'shrink;
+            }
+        }
+    }
+
+    if let Some(start) = start {
+        eprintln!(
+            "[quickcheck_async] shrinking done: {} steps, {} candidates tried in {:.3?}",
+            steps,
+            iters,
+            start.elapsed()
+        );
+    }
+
+    (case.args, err)
+}
+
+/// Print a step taken while shrinking, from `from` to `to`, with how many candidates
+/// had been tried and how long it had been by then.
+fn report_shrink<A: Debug>(from: &A, to: &A, step: u64, tried: u64, elapsed: Duration) {
+    let (from, to) = (arguments(from), arguments(to));
+
+    let changed = if from.len() == to.len() {
+        (from.iter().zip(&to).enumerate())
+            .filter(|(_, (from, to))| from != to)
+            .map(|(i, (from, to))| {
+                let (n, before, after) = (i + 1, from.len(), to.len());
+                // a short value is shown too, taking little more room than its size
+                match after {
+                    0..=32 => format!("argument {} from {} to {} chars: {}", n, before, after, to),
+                    _ => format!("argument {} from {} to {} chars", n, before, after),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    } else {
+        let len = |args: &[String]| args.iter().map(String::len).sum::<usize>();
+        format!("arguments from {} to {} chars", len(&from), len(&to))
+    };
+
+    eprintln!(
+        "[quickcheck_async] shrink step {} after {} candidates ({:.3?}): {}",
+        step, tried, elapsed, changed
+    );
+}
+
+/// Each argument in `args`, as shown. They're told apart in its pretty printed form, in
+/// which each element of a tuple starts on a line of its own one level in, as do only
+/// the closing brackets of those spanning several lines.
+fn arguments<A: Debug>(args: &A) -> Vec<String> {
+    let pretty = format!("{:#?}", args);
+    let inner = match pretty.strip_prefix("(\n").and_then(|s| s.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => return vec![format!("{:?}", args)],
+    };
+
+    let mut args: Vec<String> = Vec::new();
+    for line in inner.lines() {
+        let item = line.trim_start();
+        let first = line.len() - item.len() == 4 && !item.starts_with([')', ']', '}']);
+        match args.last_mut() {
+            Some(arg) if !first => arg.push_str(line.trim()),
+            _ => args.push(line.trim().to_owned()),
+        }
+    }
+
+    // shown without the separating comma, or the spaces a line would have
+    (args.into_iter())
+        .map(|arg| arg.strip_suffix(',').map(str::to_owned).unwrap_or(arg))
+        .collect()
+}
+
+/// The first of `batch` to fail, in order, with its error. Every case is awaited at
+/// once, and those after a failure are dropped once the ones before it have passed.
+async fn first_failure<A, F, Fut>(
+    f: &F,
+    batch: &[A],
+    config: &Config,
+) -> Option<(usize, Option<String>)>
+where
+    A: Clone,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Status>,
+{
+    let mut cases: Vec<_> = (batch.iter())
+        .map(|args| Some(Box::pin(check(f, args.clone(), config))))
+        .collect();
+    let mut statuses: Vec<Option<Status>> = batch.iter().map(|_| None).collect();
+
+    future::poll_fn(|cx| {
+        for (case, status) in cases.iter_mut().zip(&mut statuses) {
+            if let Some(Poll::Ready(out)) = case.as_mut().map(|case| case.as_mut().poll(cx)) {
+                *status = Some(out);
+                *case = None;
+            }
+        }
+
+        for (i, status) in statuses.iter_mut().enumerate() {
+            match status {
+                None => return Poll::Pending,
+                Some(Status::Fail(err)) => return Poll::Ready(Some((i, err.take()))),
+                Some(_) => {}
+            }
+        }
+        Poll::Ready(None)
+    })
+    .await
+}
+
+/// The first of `batch` to fail, in order, with its error, calling `call` with each on
+/// a thread of its own.
+fn first_failure_blocking<A: Clone + Send>(
+    call: &(dyn Fn(A) -> Status + Sync),
+    batch: &[A],
+    config: &Config,
+) -> Option<(usize, Option<String>)> {
+    let statuses: Vec<Status> = thread::scope(|scope| {
+        let handles: Vec<_> = (batch.iter())
+            .map(|args| {
+                let args = args.clone();
+                scope
+                    .spawn(move || complete(check(&|args| future::ready(call(args)), args, config)))
+            })
+            .collect();
+
+        (handles.into_iter())
+            .map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+
+    (statuses.into_iter().enumerate()).find_map(|(i, status)| match status {
+        Status::Fail(err) => Some((i, err)),
+        _ => None,
+    })
+}
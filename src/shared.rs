@@ -0,0 +1,33 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+// each value is a leaked OnceLock<T>, so it's initialised without holding the map's lock
+type Values = HashMap<(&'static str, TypeId), &'static (dyn Any + Send + Sync)>;
+
+static VALUES: Mutex<Option<Values>> = Mutex::new(None);
+
+/// Returns the value of type `T` registered under `key`, calling `init` to create it on
+/// first use. Values live until the process exits, and are shared by every test in it.
+/// `init` may itself ask for values under other keys, but not under its own.
+pub fn shared<T: Any + Send + Sync>(key: &'static str, init: impl FnOnce() -> T) -> &'static T {
+    let cell = *VALUES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .entry((key, TypeId::of::<T>()))
+        .or_insert_with(|| Box::leak(Box::new(OnceLock::<T>::new())));
+
+    // a panicking init leaves the cell empty, so the next caller tries again
+    cell.downcast_ref::<OnceLock<T>>()
+        .expect("shared value is keyed by its type")
+        .get_or_init(init)
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use std::{
+    any::{Any, TypeId},
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// A shrinker registered by [register_shrinker].
+type Shrinker<T> = Arc<dyn Fn(&T) -> Vec<T> + Send + Sync>;
+
+/// The registered shrinkers, each a [Shrinker] of the type it's keyed by.
+static SHRINKERS: Mutex<BTreeMap<TypeId, Box<dyn Any + Send>>> = Mutex::new(BTreeMap::new());
+
+/// Shrink arguments of type `T` by calling `shrinker` with a reference to them, in place
+/// of the [Arbitrary](quickcheck::Arbitrary) impl of `T`, in every test run after this.
+/// It returns the candidates to try.
+///
+/// This is for types from other crates, whose shrinkers can't be changed, used by many
+/// tests, so that `#[shrink(path)]` would be repeated on each. A shrinker registered
+/// again for the same type replaces the last. Only whole arguments are shrunk by it,
+/// not values of the type within them, such as the elements of a `Vec<T>` argument.
+///
+/// ```
+/// use quickcheck_async::Config;
+///
+/// fn config() -> Config {
+///     quickcheck_async::register_shrinker::<Vec<u8>>(|v| {
+///         (0..v.len()).map(|i| [&v[..i], &v[i + 1..]].concat()).collect()
+///     });
+///     Config::new()
+/// }
+///
+/// #[quickcheck_async::tokio(config = "config")]
+/// async fn sums(v: Vec<u8>) -> bool {
+///     v.iter().map(|&x| u64::from(x)).sum::<u64>() < 256 * 1000
+/// }
+/// ```
+pub fn register_shrinker<T: 'static>(shrinker: impl Fn(&T) -> Vec<T> + Send + Sync + 'static) {
+    let shrinker: Shrinker<T> = Arc::new(shrinker);
+    let mut shrinkers = SHRINKERS.lock().unwrap_or_else(|e| e.into_inner());
+    shrinkers.insert(TypeId::of::<T>(), Box::new(shrinker));
+}
+
+/// The candidates the shrinker registered for the type of `value` makes, if there is one.
+pub fn registered<T: 'static>(value: &T) -> Option<Box<dyn Iterator<Item = T>>> {
+    let shrinker = {
+        let shrinkers = SHRINKERS.lock().unwrap_or_else(|e| e.into_inner());
+        shrinkers
+            .get(&TypeId::of::<T>())?
+            .downcast_ref::<Shrinker<T>>()?
+            .clone()
+    };
+    Some(Box::new(shrinker(value).into_iter()))
+}
@@ -0,0 +1,101 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use futures_core::Stream;
+use quickcheck::{Arbitrary, Gen};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    iter,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A stream of generated items, which is pending a generated number of times before
+/// each, for `impl Stream<Item = T>` arguments.
+///
+/// Consumers are fed irregularly paced input this way, as they would be by a socket or
+/// channel, though the stream always wakes its task, so it never stalls. It shrinks to
+/// the same items without pauses, then as a `Vec` of items does. In a failing case it's
+/// shown as a list of its items, with `Pending` for each pause.
+///
+/// ```
+/// use futures::StreamExt;
+/// use quickcheck_async::ArbStream;
+///
+/// #[quickcheck_async::tokio]
+/// async fn chunks(s: ArbStream<u8>) -> bool {
+///     let items: Vec<u8> = s.clone().collect().await;
+///     s.ready_chunks(4).concat().await == items
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ArbStream<T> {
+    // each item, with the times the stream is pending before it
+    items: VecDeque<(usize, T)>,
+}
+
+impl<T> Unpin for ArbStream<T> {}
+
+impl<T> Stream for ArbStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.items.front_mut() {
+            Some((pauses, _)) if *pauses > 0 => {
+                *pauses -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            _ => Poll::Ready(self.items.pop_front().map(|(_, item)| item)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.items.len(), Some(self.items.len()))
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for ArbStream<T> {
+    /// Generate items as a `Vec` would be, each pausing 1 to 4 times half the time.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let items = Vec::<T>::arbitrary(g)
+            .into_iter()
+            .map(|item| match g.next_u32() % 8 {
+                i if i < 4 => (i as usize + 1, item),
+                _ => (0, item),
+            })
+            .collect();
+
+        ArbStream { items }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let items: Vec<_> = self.items.iter().cloned().collect();
+        let paced = items.iter().any(|(pauses, _)| *pauses > 0);
+        let unpaced = ArbStream {
+            items: items.iter().map(|(_, item)| (0, item.clone())).collect(),
+        };
+        let shrunk = items.shrink().map(|items| ArbStream {
+            items: items.into(),
+        });
+
+        Box::new(iter::once(unpaced).filter(move |_| paced).chain(shrunk))
+    }
+}
+
+impl<T: Debug> Debug for ArbStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for (pauses, item) in &self.items {
+            for _ in 0..*pauses {
+                list.entry(&format_args!("Pending"));
+            }
+            list.entry(item);
+        }
+        list.finish()
+    }
+}
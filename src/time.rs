@@ -0,0 +1,216 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+use quickcheck::{Arbitrary, Gen};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// Time types with generators of sensible values, for `#[time]` arguments. Each value
+/// is an offset in nanoseconds from an origin: zero for durations, the first instant
+/// generated for [Instant], and the start of 2000 UTC for dates and times.
+pub trait Temporal: Sized {
+    /// Whether a value may be before the origin.
+    const SIGNED: bool;
+    /// The largest unit offsets are generated in, in nanoseconds.
+    const SCALE: i64;
+
+    /// The value `nanos` after the origin.
+    fn from_offset(nanos: i64) -> Self;
+    /// How long after the origin the value is, in nanoseconds.
+    fn since_origin(&self) -> i64;
+}
+
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+const NANOS_PER_YEAR: i64 = 365 * NANOS_PER_DAY;
+/// 2000-01-01T00:00:00Z, as a unix timestamp.
+#[cfg(any(feature = "chrono", feature = "time"))]
+const Y2K: i64 = 946_684_800;
+
+/// Generate a value of up to the generator's size in one of nanoseconds, microseconds,
+/// milliseconds, seconds, minutes, hours, days or years, so both tiny and huge offsets
+/// turn up.
+pub fn time<T: Temporal, G: Gen>(g: &mut G) -> T {
+    const UNITS: &[i64] = &[
+        1,
+        1_000,
+        1_000_000,
+        1_000_000_000,
+        60_000_000_000,
+        3_600_000_000_000,
+        NANOS_PER_DAY,
+        NANOS_PER_YEAR,
+    ];
+
+    let units = UNITS.iter().filter(|&&unit| unit <= T::SCALE).count();
+    let unit = UNITS[(g.next_u64() % units as u64) as usize];
+    let count = (g.next_u64() % (g.size() as u64 + 1)) as i64;
+    let nanos = count
+        .saturating_mul(unit)
+        .saturating_add((g.next_u64() % unit as u64) as i64);
+
+    match T::SIGNED && g.next_u32().is_multiple_of(2) {
+        true => T::from_offset(-nanos),
+        false => T::from_offset(nanos),
+    }
+}
+
+/// Shrink a value towards its origin.
+pub fn shrink_time<T: Temporal + 'static>(t: &T) -> Box<dyn Iterator<Item = T>> {
+    Box::new(t.since_origin().shrink().map(T::from_offset))
+}
+
+impl Temporal for Duration {
+    const SIGNED: bool = false;
+    const SCALE: i64 = NANOS_PER_DAY;
+
+    fn from_offset(nanos: i64) -> Self {
+        Duration::from_nanos(nanos.max(0) as u64)
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.as_nanos().min(i64::MAX as u128) as i64
+    }
+}
+
+impl Temporal for Instant {
+    const SIGNED: bool = false;
+    const SCALE: i64 = NANOS_PER_DAY;
+
+    fn from_offset(nanos: i64) -> Self {
+        instant_origin() + Duration::from_offset(nanos)
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.saturating_duration_since(instant_origin())
+            .since_origin()
+    }
+}
+
+/// The origin of generated instants, which are all in the future of it.
+fn instant_origin() -> Instant {
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    *ORIGIN.get_or_init(Instant::now)
+}
+
+#[cfg(feature = "chrono")]
+impl Temporal for chrono::Duration {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_DAY;
+
+    fn from_offset(nanos: i64) -> Self {
+        chrono::Duration::nanoseconds(nanos)
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.num_nanoseconds().unwrap_or(i64::MAX)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Temporal for chrono::DateTime<chrono::Utc> {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_YEAR;
+
+    fn from_offset(nanos: i64) -> Self {
+        let origin = chrono::DateTime::from_timestamp(Y2K, 0).unwrap();
+        origin + chrono::Duration::from_offset(nanos)
+    }
+
+    fn since_origin(&self) -> i64 {
+        let origin = chrono::DateTime::from_timestamp(Y2K, 0).unwrap();
+        (*self - origin).since_origin()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Temporal for chrono::NaiveDateTime {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_YEAR;
+
+    fn from_offset(nanos: i64) -> Self {
+        chrono::DateTime::<chrono::Utc>::from_offset(nanos).naive_utc()
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.and_utc().since_origin()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Temporal for chrono::NaiveDate {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_YEAR;
+
+    fn from_offset(nanos: i64) -> Self {
+        chrono::NaiveDateTime::from_offset(nanos).date()
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.and_time(chrono::NaiveTime::MIN).since_origin()
+    }
+}
+
+#[cfg(feature = "time")]
+impl Temporal for ::time::Duration {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_DAY;
+
+    fn from_offset(nanos: i64) -> Self {
+        ::time::Duration::nanoseconds(nanos)
+    }
+
+    fn since_origin(&self) -> i64 {
+        let nanos = self.whole_nanoseconds();
+        nanos.clamp(i64::MIN.into(), i64::MAX.into()) as i64
+    }
+}
+
+#[cfg(feature = "time")]
+impl Temporal for ::time::OffsetDateTime {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_YEAR;
+
+    fn from_offset(nanos: i64) -> Self {
+        let origin = ::time::OffsetDateTime::from_unix_timestamp(Y2K).unwrap();
+        origin + ::time::Duration::from_offset(nanos)
+    }
+
+    fn since_origin(&self) -> i64 {
+        let origin = ::time::OffsetDateTime::from_unix_timestamp(Y2K).unwrap();
+        (*self - origin).since_origin()
+    }
+}
+
+#[cfg(feature = "time")]
+impl Temporal for ::time::PrimitiveDateTime {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_YEAR;
+
+    fn from_offset(nanos: i64) -> Self {
+        let at = ::time::OffsetDateTime::from_offset(nanos);
+        ::time::PrimitiveDateTime::new(at.date(), at.time())
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.assume_utc().since_origin()
+    }
+}
+
+#[cfg(feature = "time")]
+impl Temporal for ::time::Date {
+    const SIGNED: bool = true;
+    const SCALE: i64 = NANOS_PER_YEAR;
+
+    fn from_offset(nanos: i64) -> Self {
+        ::time::PrimitiveDateTime::from_offset(nanos).date()
+    }
+
+    fn since_origin(&self) -> i64 {
+        self.midnight().since_origin()
+    }
+}
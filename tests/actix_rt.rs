@@ -0,0 +1,37 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::actix_rt]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::actix_rt]
+async fn unit_test() {}
+
+#[quickcheck_async::actix_rt]
+async fn system_test(x: u8) -> bool {
+    let _ = actix_rt::System::current();
+    actix_rt::spawn(async move { x }).await.unwrap() == x
+}
+
+// the case runs on an arbiter, so the discard has to be carried back to the test thread
+#[quickcheck_async::actix_rt(max_discard_ratio = 20)]
+async fn reject_if(x: u8) -> bool {
+    quickcheck_async::reject_if!(!x.is_multiple_of(8));
+    x.is_multiple_of(8)
+}
+
+// were shrink candidates run off the system, the timer would panic in each of them, so
+// this would shrink to 0
+#[quickcheck_async::actix_rt]
+#[should_panic(expected = "TEST FAILED. Arguments: (10,)")]
+async fn shrinks_on_runtime(x: u8) -> bool {
+    actix_rt::time::sleep(std::time::Duration::from_micros(1)).await;
+    x < 10
+}
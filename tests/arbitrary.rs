@@ -0,0 +1,25 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "arbitrary")]
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::futures(arbitrary)]
+async fn from_bytes(v: Vec<u8>, s: &str, (x, b): (u32, bool)) -> bool {
+    v.len() <= 100 && s.len() <= 100 && (b || x.to_string().parse() == Ok(x))
+}
+
+#[quickcheck_async::futures(arbitrary)]
+#[quickcheck_async::filter(|x: &u16, _| *x > 0)]
+async fn with_generators(x: u16, #[range(1..10)] y: u8) -> bool {
+    x > 0 && (1..10).contains(&y)
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test, arbitrary)]
+#[should_panic(expected = "TEST FAILED. Arguments: ([0],)")]
+async fn shrinks_bytes(v: Vec<u8>) -> bool {
+    v.is_empty()
+}
@@ -13,3 +13,11 @@ async fn bool_test() -> bool {
 
 #[quickcheck_async::async_std]
 async fn unit_test() {}
+
+#[quickcheck_async::async_std]
+async fn runtime_test(x: u8) -> bool {
+    // panics when not called from within an async-std task
+    let _ = async_std::task::current();
+    async_std::task::sleep(std::time::Duration::from_micros(x as u64)).await;
+    async_std::task::spawn(async move { x }).await == x
+}
@@ -0,0 +1,23 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "bytes")]
+#![warn(rust_2018_idioms)]
+
+use bytes::BytesMut;
+
+#[quickcheck_async::futures]
+async fn frames(payload: bytes::Bytes, #[foreign] mut buf: BytesMut) -> bool {
+    let len = buf.len();
+    buf.extend_from_slice(&payload);
+    buf.len() == len + payload.len() && buf.freeze().ends_with(&payload)
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "TEST FAILED. Arguments: (b\"\\0\\0\\0\\0\",)")]
+async fn bytes_shrink(payload: bytes::Bytes) -> bool {
+    payload.len() < 4
+}
@@ -0,0 +1,23 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "chrono")]
+#![warn(rust_2018_idioms)]
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+
+#[quickcheck_async::futures]
+async fn dates(#[time] at: DateTime<Utc>, #[time] day: NaiveDate, #[time] d: TimeDelta) -> bool {
+    (1890..2110).contains(&at.year())
+        && (1890..2110).contains(&day.year())
+        && d.abs() <= TimeDelta::days(101)
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (2000-01-01T00:00:00Z,)")]
+async fn shrinks_to_2000(#[time] at: DateTime<Utc>) -> bool {
+    at.year() < 2000
+}
@@ -0,0 +1,46 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+quickcheck_async::config!(tests = 7, gen_size = 4);
+
+static DEFAULTS: AtomicUsize = AtomicUsize::new(0);
+static OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+
+#[quickcheck_async::futures]
+async fn defaults(v: Vec<u8>) -> bool {
+    DEFAULTS.fetch_add(1, Ordering::SeqCst) < 7 && v.len() <= 4
+}
+
+#[quickcheck_async::futures(tests = 3)]
+async fn override_defaults(v: Vec<u8>) -> bool {
+    OVERRIDE.fetch_add(1, Ordering::SeqCst) < 3 && v.len() <= 4
+}
+
+mod nested {
+    #[quickcheck_async::smol(gen_size = 1)]
+    async fn nested(v: Vec<u8>) -> bool {
+        v.len() <= 1
+    }
+}
+
+static IGNORE_ENV: AtomicUsize = AtomicUsize::new(0);
+
+#[quickcheck_async::futures(ignore_env = true)]
+async fn ignore_env(v: Vec<u8>) -> bool {
+    IGNORE_ENV.fetch_add(1, Ordering::SeqCst) < 7 && v.len() <= 4
+}
+
+static PROFILE: AtomicUsize = AtomicUsize::new(0);
+
+#[quickcheck_async::futures(tests = 50, debug_tests = 2, release_tests = 5)]
+async fn profile_tests(v: Vec<u8>) -> bool {
+    let max = if cfg!(debug_assertions) { 2 } else { 5 };
+    PROFILE.fetch_add(1, Ordering::SeqCst) < max && v.len() <= 4
+}
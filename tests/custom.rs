@@ -0,0 +1,34 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::future::Future;
+
+mod my_rt {
+    use super::*;
+
+    pub fn block_on<F: Future>(fut: F) -> F::Output {
+        futures::executor::block_on(fut)
+    }
+}
+
+#[quickcheck_async::custom(block_on = "my_rt::block_on")]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::custom(block_on = "my_rt::block_on")]
+async fn unit_test() {}
+
+#[quickcheck_async::custom(block_on = "::smol::block_on")]
+async fn smol_test(x: u8) -> bool {
+    smol::future::yield_now().await;
+    x.rotate_left(3).rotate_right(3) == x
+}
+
+#[quickcheck_async::test(runtime = "custom", block_on = "async_std::task::block_on")]
+async fn runtime_test() {}
@@ -0,0 +1,22 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "embassy")]
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::embassy]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::embassy]
+async fn unit_test() {}
+
+#[quickcheck_async::embassy(tests = 20)]
+async fn tests(x: u8) -> bool {
+    embassy_futures::yield_now().await;
+    x.count_ones() + x.count_zeros() == 8
+}
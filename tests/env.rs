@@ -0,0 +1,62 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use quickcheck_async::Config;
+use std::{
+    env, panic,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static CASES: AtomicUsize = AtomicUsize::new(0);
+
+fn count(_: u8) -> bool {
+    CASES.fetch_add(1, Ordering::SeqCst);
+    true
+}
+
+// the only test in this binary, so its environment and failures can't affect others
+#[test]
+fn environment() {
+    env::set_var("QUICKCHECK_TESTS", "1000");
+
+    Config::new()
+        .tests(3)
+        .ignore_env(true)
+        .quickcheck(count as fn(u8) -> bool);
+    assert_eq!(CASES.swap(0, Ordering::SeqCst), 3);
+
+    Config::new().quickcheck(count as fn(u8) -> bool);
+    assert_eq!(CASES.swap(0, Ordering::SeqCst), 1000);
+
+    env::remove_var("QUICKCHECK_TESTS");
+    env::set_var("DEEP_FUZZ", "1");
+
+    Config::new()
+        .tests(3)
+        .ci_scale(5)
+        .ci_var("DEEP_FUZZ")
+        .quickcheck(count as fn(u8) -> bool);
+    assert_eq!(CASES.swap(0, Ordering::SeqCst), 15);
+
+    env::set_var("DEEP_FUZZ", "false");
+
+    Config::new()
+        .tests(3)
+        .ci_scale(5)
+        .ci_var("DEEP_FUZZ")
+        .quickcheck(count as fn(u8) -> bool);
+    assert_eq!(CASES.swap(0, Ordering::SeqCst), 3);
+
+    let failing = panic::catch_unwind(|| Config::new().quickcheck((|_| false) as fn(u8) -> bool));
+    assert!(failing.is_err());
+
+    Config::new()
+        .fail_fast(true)
+        .quickcheck(count as fn(u8) -> bool);
+    assert_eq!(CASES.swap(0, Ordering::SeqCst), 0);
+}
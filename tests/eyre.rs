@@ -0,0 +1,25 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "eyre")]
+#![warn(rust_2018_idioms)]
+
+use eyre::WrapErr;
+
+#[quickcheck_async::futures]
+async fn ok(x: u8) -> eyre::Result<()> {
+    let y: u16 = x.to_string().parse()?;
+    assert_eq!(y, u16::from(x));
+    Ok(())
+}
+
+// the cause is only shown in the report's Debug output
+#[quickcheck_async::wasm(unsupported = tokio::test, gen_size = 1000)]
+#[should_panic(expected = "Caused by:")]
+async fn chain(x: u16) -> eyre::Result<()> {
+    let _: u8 = x.to_string().parse().wrap_err("parsing a byte")?;
+    Ok(())
+}
@@ -0,0 +1,176 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use quickcheck::TestResult;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[quickcheck_async::futures(seed = 0xDEADBEEF, gen_size = 4)]
+#[should_panic(expected = "TEST FAILED. Arguments: (0,)\n[quickcheck_async] seed: 0xdeadbeef")]
+async fn seed(x: u8) -> bool {
+    x != 0
+}
+
+#[quickcheck_async::futures(deterministic = true)]
+#[should_panic(expected = "[quickcheck_async] seed: 0x5eed")]
+async fn deterministic(x: u8) -> bool {
+    x < 10
+}
+
+#[quickcheck_async::futures(max_discard_ratio = 0.5)]
+#[should_panic(expected = "gave up after 0 passed and 51 discarded cases")]
+async fn max_discard_ratio() -> TestResult {
+    TestResult::discard()
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED (runtime error). Arguments: (10,)\n\
+                           Error: \"10\" is\tnot\nbelow ten")]
+async fn test_result_error(x: u8) -> TestResult {
+    if x < 10 {
+        return TestResult::passed();
+    }
+    TestResult::error(format!("{:?} is\tnot\nbelow ten", x.to_string()))
+}
+
+// a TestResult is only read through its Debug output, so a change to it must be caught
+#[test]
+fn test_result_debug() {
+    let debug = |r: TestResult| format!("{:?}", r);
+    assert_eq!(
+        debug(TestResult::discard()),
+        "TestResult { status: Discard, arguments: [], err: None }"
+    );
+    assert_eq!(
+        debug(TestResult::error("\"boom\"")),
+        "TestResult { status: Fail, arguments: [], err: Some(\"\\\"boom\\\"\") }"
+    );
+}
+
+#[quickcheck_async::futures(rng = "os")]
+#[should_panic(expected = "rng: os (this run cannot be replayed)")]
+async fn os_rng(x: u8) -> bool {
+    x < 10
+}
+
+static RAN: AtomicU64 = AtomicU64::new(0);
+
+// a single passing case would be enough without the time budget
+#[quickcheck_async::futures(tests = 1, duration = "10s")]
+#[should_panic(expected = "TEST FAILED. Arguments: ()")]
+async fn duration() -> bool {
+    RAN.fetch_add(1, Ordering::SeqCst) < 10
+}
+
+// random cases would be unlikely to find this, let alone first
+#[quickcheck_async::futures(exhaustive)]
+#[should_panic(expected = "TEST FAILED. Arguments: (true, Some(false), 54321)")]
+async fn exhaustive(b: bool, o: Option<bool>, x: u16) -> bool {
+    !(b && o.is_some() && x >= 54321)
+}
+
+#[quickcheck_async::futures(max_discard_ratio = 0.5)]
+#[should_panic(expected = "gave up after 0 passed and 51 discarded cases")]
+async fn discard() -> bool {
+    quickcheck_async::discard!()
+}
+
+#[quickcheck_async::futures(gen_size = 1000)]
+#[should_panic(expected = "Error: number too large to fit in target type")]
+async fn result_err(x: u16) -> Result<(), std::num::ParseIntError> {
+    let y: u8 = x.to_string().parse()?;
+    assert_eq!(u16::from(y), x);
+    Ok(())
+}
+
+#[quickcheck_async::futures(gen_size = 1000)]
+#[should_panic(expected = "TEST FAILED. Arguments: (256,)")]
+async fn result_falsified(x: u16) -> Result<bool, std::num::ParseIntError> {
+    Ok(x.to_string().parse::<u16>()? < 256)
+}
+
+#[quickcheck_async::futures(max_discard_ratio = 0.5)]
+#[should_panic(expected = "gave up after 0 passed and 51 discarded cases")]
+async fn option_none() -> Option<()> {
+    None
+}
+
+#[quickcheck_async::futures(gen_size = 1000, fallible)]
+#[should_panic(expected = "TEST FAILED (runtime error). Arguments: (256,)\n\
+                           Error: number too large to fit in target type")]
+async fn fallible_err(x: u16) {
+    let y: u8 = x.to_string().parse()?;
+    assert_eq!(u16::from(y), x);
+}
+
+fn seven<G: quickcheck::Gen>(_: &mut G) -> u8 {
+    7
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (7, 0)")]
+async fn custom_gen(#[gen(seven)] x: u8, y: u8) -> bool {
+    x < 7 || y > 200
+}
+
+// generated values are never this large, so only the example finds the failure
+#[quickcheck_async::futures]
+#[quickcheck_async::examples(u32::MAX)]
+#[should_panic(expected = "TEST FAILED. Arguments: (1000,)")]
+async fn example_fails(x: u32) -> bool {
+    x < 1000
+}
+
+#[quickcheck_async::futures(max_discard_ratio = 0.5)]
+#[quickcheck_async::filter(|_: &u8| false)]
+#[should_panic(expected = "gave up after 0 passed and 51 discarded cases")]
+async fn filter_everything(_x: u8) -> bool {
+    false
+}
+
+#[quickcheck_async::futures(expect = "fail")]
+#[should_panic(expected = "expected a counterexample, but 100 cases passed")]
+async fn expect_fail_passes(x: u8) -> bool {
+    x.to_string().parse() == Ok(x)
+}
+
+#[quickcheck_async::futures(exhaustive, expect = "fail")]
+async fn expect_fail_exhaustive(b: bool, x: u8) -> bool {
+    !(b && x == u8::MAX)
+}
+
+#[quickcheck_async::futures(should_panic)]
+#[should_panic(expected = "the case did not panic")]
+async fn should_panic_completes(x: u8) {
+    if x.is_multiple_of(2) {
+        panic!("even");
+    }
+}
+
+#[quickcheck_async::futures(should_panic = "zero")]
+#[should_panic(expected = "panicked with \"small\", which does not contain \"zero\"")]
+async fn should_panic_message(x: u8) {
+    assert!(x > 10, "small");
+}
+
+struct Report(Option<String>);
+
+impl quickcheck_async::AsyncTestable for Report {
+    fn outcome(self) -> quickcheck_async::Status {
+        match self.0 {
+            None => quickcheck_async::Status::Pass,
+            Some(msg) => quickcheck_async::Status::Fail(Some(msg)),
+        }
+    }
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "Error: 10 is too big")]
+async fn async_testable(x: u8) -> Report {
+    Report(Some(x).filter(|&x| x >= 10).map(|x| format!("{} is too big", x)))
+}
+
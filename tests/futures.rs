@@ -0,0 +1,677 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use futures::{channel::oneshot, future, join, Future};
+use quickcheck::TestResult;
+use quickcheck_async::{prop_assert, prop_assert_eq, PropError};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+#[quickcheck_async::futures]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::futures]
+async fn unit_test() {}
+
+#[quickcheck_async::futures]
+async fn channel_test(x: u8) -> bool {
+    let (tx, rx) = oneshot::channel();
+    let (_, y) = join!(async move { tx.send(x) }, rx);
+    y == Ok(x)
+}
+
+#[quickcheck_async::futures(gen_size = 4)]
+async fn gen_size(v: Vec<u8>) -> bool {
+    v.len() <= 4
+}
+
+static SEEN: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+static SEEN_AGAIN: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+// both tests must see the same cases, in the same order, regardless of which runs first
+fn same_cases(seen: &mut Vec<u32>, other: &[u32], x: u32) -> bool {
+    seen.push(x);
+    other.get(seen.len() - 1).is_none_or(|&y| x == y)
+}
+
+#[quickcheck_async::futures(seed = 0xDEADBEEF)]
+async fn seed(x: u32) -> bool {
+    let (mut seen, other) = (SEEN.lock().unwrap(), SEEN_AGAIN.lock().unwrap());
+    same_cases(&mut seen, &other, x)
+}
+
+#[quickcheck_async::futures(seed = 0xDEADBEEF, rng = "chacha8")]
+async fn seed_again(x: u32) -> bool {
+    let (other, mut seen) = (SEEN.lock().unwrap(), SEEN_AGAIN.lock().unwrap());
+    same_cases(&mut seen, &other, x)
+}
+
+#[quickcheck_async::futures(max_discard_ratio = 20)]
+async fn max_discard_ratio(x: u8) -> TestResult {
+    if !x.is_multiple_of(8) {
+        return TestResult::discard();
+    }
+    TestResult::passed()
+}
+
+#[quickcheck_async::futures(rng = "os")]
+async fn os_rng(x: u32) -> bool {
+    u32::from_be_bytes(x.to_be_bytes()) == x
+}
+
+#[quickcheck_async::futures(exhaustive)]
+async fn exhaustive(b: bool, x: Option<u8>) -> bool {
+    u16::from(x.unwrap_or(0)) + u16::from(b) <= 256
+}
+
+#[quickcheck_async::futures(tests = 20, progress = 10)]
+async fn progress_cases(x: u8) -> bool {
+    x.count_ones() + x.count_zeros() == 8
+}
+
+#[quickcheck_async::futures(progress = "1ms")]
+async fn progress_time(x: u8) -> bool {
+    x.to_string().parse() == Ok(x)
+}
+
+#[quickcheck_async::futures(tests = 3, verbose = true)]
+async fn verbose(x: u8) -> bool {
+    x.rotate_left(3).rotate_right(3) == x
+}
+
+#[quickcheck_async::futures(report = "summary")]
+async fn report_summary(x: u8) -> TestResult {
+    if x.is_multiple_of(2) {
+        return TestResult::discard();
+    }
+    TestResult::passed()
+}
+
+#[quickcheck_async::futures(report = "quiet")]
+async fn report_quiet(x: u8) -> TestResult {
+    if x.is_multiple_of(2) {
+        return TestResult::discard();
+    }
+    TestResult::passed()
+}
+
+fn few_small() -> quickcheck_async::Config {
+    quickcheck_async::Config::new().tests(5).gen_size(2)
+}
+
+#[quickcheck_async::futures(config = "few_small", gen_size = 3)]
+async fn config_fn(v: Vec<u8>) -> bool {
+    v.len() <= 3
+}
+
+static GROWN: AtomicU64 = AtomicU64::new(0);
+
+// case n of 10 is generated at size n + 1
+#[quickcheck_async::futures(tests = 10, gen_size = 10, growth = "linear")]
+async fn growth(v: Vec<u8>) -> bool {
+    v.len() as u64 <= GROWN.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+static ATTEMPTS: AtomicU64 = AtomicU64::new(0);
+
+// every other attempt fails, so each failure passes on its retry
+#[quickcheck_async::futures(retries = 1)]
+async fn retries(_x: u8) -> bool {
+    ATTEMPTS.fetch_add(1, Ordering::SeqCst).is_multiple_of(2)
+}
+
+static DISTINCT: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+// 95% confidence of a failure rate under 10% takes 29 distinct passing cases
+#[quickcheck_async::futures(tests = 1000, confidence = 0.95, failure_rate = 0.1)]
+async fn confidence(x: u64) -> bool {
+    let mut distinct = DISTINCT.lock().unwrap();
+    if !distinct.contains(&x) {
+        distinct.push(x);
+    }
+    distinct.len() <= 29
+}
+
+#[quickcheck_async::futures(max_discard_ratio = 20)]
+async fn reject_if(x: u8) -> bool {
+    quickcheck_async::reject_if!(!x.is_multiple_of(8));
+    x.is_multiple_of(8)
+}
+
+#[quickcheck_async::futures(expect = "fail")]
+async fn expect_fail(v: Vec<u8>) -> bool {
+    v.len() < 10
+}
+
+#[quickcheck_async::futures(should_panic = "attempt to divide by zero")]
+async fn should_panic(x: u8) -> bool {
+    x / (x - x) == x
+}
+
+#[quickcheck_async::futures]
+fn impl_future((a, b): (u8, u8)) -> impl Future<Output = bool> {
+    future::ready(a.checked_add(b).is_some() == (u16::from(a) + u16::from(b) <= 255))
+}
+
+#[quickcheck_async::futures]
+async fn prop_asserts(x: u16) -> Result<(), PropError> {
+    prop_assert!(x.swap_bytes().swap_bytes() == x, "{} didn't swap back", x);
+    prop_assert_eq!(x.to_string().parse::<u16>(), Ok(x));
+    Ok(())
+}
+
+#[quickcheck_async::futures]
+async fn result_bool(x: u8) -> Result<bool, std::num::ParseIntError> {
+    Ok(x.to_string().parse::<u8>()? == x)
+}
+
+#[quickcheck_async::futures(gen_size = 1000)]
+#[should_panic(expected = "TEST FAILED (runtime error). Arguments: (256,)")]
+async fn result_bool_err(x: u16) -> Result<bool, std::num::ParseIntError> {
+    Ok(x.to_string().parse::<u8>()? == x as u8)
+}
+
+#[quickcheck_async::futures]
+async fn option_discard(s: String) -> Option<bool> {
+    let x: u8 = s.parse().ok()?;
+    Some(x.to_string().parse() == Ok(x))
+}
+
+#[quickcheck_async::futures(fallible)]
+async fn fallible(x: u8) -> bool {
+    if x == 0 {
+        return true;
+    }
+    let y: u8 = x.to_string().parse()?;
+    let check = |y: u8| -> Option<bool> { Some(y.checked_div(x)? == 1) };
+    check(y).unwrap_or(false)
+}
+
+#[derive(Clone, Debug)]
+struct Even(u32);
+
+fn even<G: quickcheck::Gen>(g: &mut G) -> Even {
+    Even(<u32 as quickcheck::Arbitrary>::arbitrary(g) & !1)
+}
+
+#[quickcheck_async::futures]
+async fn custom_gen(#[gen(even)] x: Even, y: u8) -> bool {
+    x.0.is_multiple_of(2) && u32::from(y) <= 255
+}
+
+#[quickcheck_async::futures]
+async fn ranges(
+    #[range(1..=1000)] x: u16,
+    #[range(-5..5)] y: i64,
+    #[range(..)] z: i8,
+    #[range(0.5..2.0)] f: f32,
+) -> bool {
+    (1..=1000).contains(&x)
+        && (-5..5).contains(&y)
+        && z.checked_abs() != Some(-1)
+        && (0.5..2.0).contains(&f)
+}
+
+#[quickcheck_async::futures]
+#[quickcheck_async::filter(|a, b| a < b)]
+#[quickcheck_async::filter(|_, b: &u8| b.is_multiple_of(2))]
+async fn filter(a: u8, b: u8) -> bool {
+    a < b && b.is_multiple_of(2)
+}
+
+static FIXTURES: AtomicU64 = AtomicU64::new(0);
+
+fn fixture() -> u64 {
+    FIXTURES.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+#[quickcheck_async::futures(tests = 10)]
+#[quickcheck_async::filter(|x: &u8| *x < 200)]
+async fn fixtures(#[fixture(fixture)] n: u64, x: u8, #[fixture] v: Vec<u8>) -> bool {
+    n >= 1 && x < 200 && v.is_empty()
+}
+
+#[quickcheck_async::futures]
+async fn generator(v: Vec<u8>, g: &mut quickcheck_async::Generator) -> bool {
+    if v.is_empty() {
+        return true;
+    }
+    let i = <usize as quickcheck::Arbitrary>::arbitrary(g) % v.len();
+    future::ready(()).await;
+    v.get(i).is_some()
+}
+
+async fn byte_len(s: &str) -> usize {
+    future::ready(s.len()).await
+}
+
+#[quickcheck_async::futures]
+#[quickcheck_async::filter(|s: &&str, _, _| !s.is_empty())]
+async fn borrowed(s: &str, b: &[u8], x: &u8) -> bool {
+    byte_len(s).await == s.chars().map(char::len_utf8).sum::<usize>()
+        && b.to_vec().len() == b.len()
+        && x.to_string().parse() == Ok(*x)
+}
+
+#[quickcheck_async::futures]
+#[allow(clippy::too_many_arguments)]
+async fn wide(
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    f: u8,
+    g: u8,
+    h: u8,
+    i: u8,
+    j: u8,
+    mut k: u8,
+    l: bool,
+    m: u8,
+    n: u8,
+    o: u8,
+    p: u8,
+    q: &str,
+) -> bool {
+    k /= 2;
+    let all = [a, b, c, d, e, f, g, h, i, j, k, m, n, o, p];
+    let sum: u32 = all.iter().map(|&x| u32::from(x)).sum();
+    let max = all.iter().max().map_or(0, |&x| u32::from(x));
+    sum <= 15 * max && k <= 127 && (l || q.chars().count() <= q.len())
+}
+
+#[quickcheck_async::futures(types(u8, String, Vec<u32>), tests = 10)]
+async fn generic<T: quickcheck::Arbitrary + Clone + PartialEq + std::fmt::Debug>(
+    x: T,
+    v: Vec<T>,
+) -> bool {
+    future::ready(x.clone()).await == x && v.clone() == v
+}
+
+#[derive(Clone, Debug)]
+struct Point {
+    x: u8,
+    y: u8,
+}
+
+impl quickcheck::Arbitrary for Point {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        Point {
+            x: u8::arbitrary(g),
+            y: u8::arbitrary(g),
+        }
+    }
+}
+
+#[quickcheck_async::futures]
+#[quickcheck_async::filter(|&(a, b): &(u8, u8), _, _, _, _, _| a <= b)]
+async fn patterns(
+    (a, b): (u8, u8),
+    Point { x, y }: Point,
+    _: Vec<u8>,
+    _unused: String,
+    mut m: u8,
+    ref r: u8,
+) -> bool {
+    m /= 2;
+    a <= b && x.saturating_add(y) >= x.max(y) && m <= 127 && r.to_string().parse() == Ok(*r)
+}
+
+#[quickcheck_async::futures(types((u8, String, ()), (bool, Vec<u8>, char)), tests = 10)]
+async fn impl_args<K: quickcheck::Arbitrary + Ord>(
+    k: K,
+    v: impl quickcheck::Arbitrary + Clone,
+    _: impl quickcheck::Arbitrary,
+) -> bool {
+    let map: std::collections::BTreeMap<_, _> = vec![(k, v.clone())].into_iter().collect();
+    map.len() == 1
+}
+
+struct Schema(String);
+
+impl quickcheck_async::AsyncArbitrary for Schema {
+    async fn arbitrary(g: &mut quickcheck_async::Generator) -> Self {
+        future::ready(()).await;
+        Schema(format!(
+            "test_{}",
+            <u32 as quickcheck::Arbitrary>::arbitrary(g)
+        ))
+    }
+}
+
+#[quickcheck_async::futures]
+#[quickcheck_async::filter(|s: &Schema, _| s.0.len() > 5)]
+async fn async_arbitrary(#[async_arbitrary] schema: Schema, x: u8) -> bool {
+    schema.0.starts_with("test_") && x.to_string().len() <= 3
+}
+
+#[derive(quickcheck_async::AsyncArbitrary)]
+struct Tenant {
+    id: u32,
+    #[async_arbitrary]
+    schema: Schema,
+}
+
+#[derive(quickcheck_async::AsyncArbitrary)]
+enum Message {
+    Hello { name: String },
+    Data(u8, bool),
+    Bye,
+}
+
+#[quickcheck_async::futures]
+async fn derive_async_arbitrary(#[async_arbitrary] t: Tenant, #[async_arbitrary] m: Message) {
+    assert!(t.schema.0.starts_with("test_") && t.id.to_string().parse() == Ok(t.id));
+    match m {
+        Message::Hello { name } => assert!(name.chars().count() <= 100),
+        Message::Data(x, b) => assert!(x.to_string().len() <= 3 + usize::from(b)),
+        Message::Bye => {}
+    }
+}
+
+/// Not tokio's handle, which only the tokio attribute passes in.
+#[derive(Clone, Debug, PartialEq)]
+struct Handle(u8);
+
+impl quickcheck::Arbitrary for Handle {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        Handle(u8::arbitrary(g))
+    }
+}
+
+static HANDLES: AtomicU64 = AtomicU64::new(0);
+
+#[quickcheck_async::futures(tests = 1)]
+#[quickcheck_async::examples((Handle(7), 3))]
+async fn handle_generated(h: Handle, x: u8) -> bool {
+    // the example comes first, with the handle as its first argument
+    HANDLES.fetch_add(1, Ordering::SeqCst) > 0 || (h == Handle(7) && x == 3)
+}
+
+#[derive(Clone, Debug)]
+enum Packet {
+    Normal(u8),
+    Edge,
+    Malformed { len: u16 },
+}
+
+static NORMAL: AtomicU64 = AtomicU64::new(0);
+static EDGE: AtomicU64 = AtomicU64::new(0);
+
+#[quickcheck_async::futures(seed = 0xDEADBEEF, tests = 1000)]
+async fn weighted_variants(
+    #[weights(Normal(u8) = 80, Edge = 20)] p: Packet,
+    #[weights(Malformed { len: u16 } = 1)] m: Packet,
+) -> bool {
+    match p {
+        Packet::Normal(_) => NORMAL.fetch_add(1, Ordering::SeqCst),
+        Packet::Edge => EDGE.fetch_add(1, Ordering::SeqCst),
+        Packet::Malformed { .. } => return false,
+    };
+    matches!(m, Packet::Malformed { .. })
+}
+
+#[test]
+fn weighted_variants_biased() {
+    weighted_variants();
+    let (normal, edge) = (NORMAL.load(Ordering::SeqCst), EDGE.load(Ordering::SeqCst));
+    // about one in five is an edge case
+    let share = edge as f64 / (normal + edge) as f64;
+    assert!(
+        (0.15..0.25).contains(&share),
+        "{} normal, {} edge",
+        normal,
+        edge
+    );
+}
+
+#[quickcheck_async::futures(gen_size = 1000)]
+async fn collection_len(
+    #[len(0..=4)] control: Vec<u8>,
+    #[len(200..300)] payload: String,
+    #[len(3..=3)] keys: std::collections::HashMap<u16, bool>,
+) -> bool {
+    control.len() <= 4 && (200..300).contains(&payload.chars().count()) && keys.len() == 3
+}
+
+#[quickcheck_async::futures]
+#[quickcheck_async::filter(|items: &Vec<u8>, _: &&str| !items.is_empty())]
+async fn dependent(
+    items: Vec<u8>,
+    #[dependent(|g| <usize as quickcheck::Arbitrary>::arbitrary(g) % items.len())] i: usize,
+    name: &str,
+    #[dependent(|g| format!("{}{}", name, <u8 as quickcheck::Arbitrary>::arbitrary(g)))]
+    tagged: String,
+) -> bool {
+    i < items.len() && tagged.starts_with(name)
+}
+
+fn halves(x: &u32) -> std::iter::Once<u32> {
+    std::iter::once(x / 2)
+}
+
+#[quickcheck_async::futures]
+async fn shrink_with_generator(
+    #[range(10..20)]
+    #[shrink(halves)]
+    x: u32,
+    #[shrink(halves)] y: u32,
+) -> bool {
+    (10..20).contains(&x) && y / 2 <= y
+}
+
+// stands in for a foreign type, which couldn't be given an Arbitrary impl here
+#[derive(Clone, Debug, PartialEq)]
+struct Uuid(u128);
+
+fn uuid<G: quickcheck::Gen>(g: &mut G) -> Uuid {
+    Uuid(u128::from(g.next_u64()) << 64 | u128::from(g.next_u64()))
+}
+
+fn nil(id: &Uuid) -> Vec<Uuid> {
+    Some(Uuid(0)).filter(|nil| nil != id).into_iter().collect()
+}
+
+#[quickcheck_async::futures]
+async fn foreign_types(
+    #[gen(uuid)] a: Uuid,
+    #[gen(uuid)]
+    #[shrink(nil)]
+    b: Uuid,
+) -> bool {
+    a.0.to_string().parse() == Ok(a.0) && b.0.swap_bytes().swap_bytes() == b.0
+}
+
+static EXAMPLES: AtomicU64 = AtomicU64::new(0);
+
+// every example runs, though fewer tests are asked for
+#[quickcheck_async::futures(tests = 1)]
+#[quickcheck_async::examples((0, "", 3, Vec::new()), (u64::MAX, "x", 9, vec![1, 2]))]
+async fn examples(
+    x: u64,
+    s: &str,
+    #[range(3..10)] r: u8,
+    g: &mut quickcheck_async::Generator,
+    v: Vec<u8>,
+) -> bool {
+    EXAMPLES.fetch_add(1, Ordering::SeqCst);
+    let _ = <u8 as quickcheck::Arbitrary>::arbitrary(g);
+    (3..10).contains(&r) && x.to_string().parse() == Ok(x) && s.len() <= 100 && v.len() <= 100
+}
+
+#[test]
+fn examples_ran() {
+    examples();
+    assert!(EXAMPLES.load(Ordering::SeqCst) >= 3);
+}
+
+#[quickcheck_async::futures]
+#[quickcheck_async::examples(
+    (1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
+    (u8::MAX, 0, 0, 0, 0, 0, 0, 0, 0, u8::MAX)
+)]
+#[allow(clippy::too_many_arguments)]
+async fn wide_examples(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8, g: u8, h: u8, i: u8, j: u8) {
+    let sum: u32 = [a, b, c, d, e, f, g, h, i, j]
+        .iter()
+        .map(|&x| u32::from(x))
+        .sum();
+    assert!(sum <= 2550);
+}
+
+const KEYWORDS: [&str; 3] = ["GET", "POST", "\r\n"];
+
+static SPLICED: AtomicU64 = AtomicU64::new(0);
+
+#[quickcheck_async::futures]
+async fn dictionary(
+    #[dictionary(KEYWORDS)] s: String,
+    #[dictionary([0xDEAD, u32::MAX])] x: u32,
+    #[dictionary([&b"\x00\xff"[..]])] b: Vec<u8>,
+) -> bool {
+    if KEYWORDS.iter().any(|k| s.contains(k)) || x == u32::MAX {
+        SPLICED.fetch_add(1, Ordering::SeqCst);
+    }
+    b.len() <= 200 && x.to_string().parse() == Ok(x)
+}
+
+#[test]
+fn dictionary_spliced() {
+    dictionary();
+    assert!(SPLICED.load(Ordering::SeqCst) > 0);
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit,
+    Add(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+}
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Lit => 0,
+            Expr::Add(l, r) => 1 + l.depth().max(r.depth()),
+            Expr::Neg(e) => 1 + e.depth(),
+        }
+    }
+}
+
+impl quickcheck::Arbitrary for Expr {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        // nodes are likelier than leaves, so only the limit keeps these small
+        let node = match g.next_u32() % 4 {
+            0 => None,
+            1 => quickcheck_async::recurse(|| Expr::Neg(Box::new(Expr::arbitrary(g)))),
+            _ => quickcheck_async::recurse(|| {
+                Expr::Add(Box::new(Expr::arbitrary(g)), Box::new(Expr::arbitrary(g)))
+            }),
+        };
+        node.unwrap_or(Expr::Lit)
+    }
+}
+
+#[quickcheck_async::futures(max_depth = 3)]
+async fn max_depth(e: Expr) -> bool {
+    e.depth() <= 3
+}
+
+#[quickcheck_async::futures]
+async fn alphabets(
+    #[alphabet(ascii)] a: String,
+    #[alphabet(alphanumeric)] b: String,
+    #[alphabet(multibyte)] c: String,
+    #[alphabet(lossy)] d: String,
+) -> bool {
+    a.is_ascii()
+        && b.bytes().all(|x| x.is_ascii_alphanumeric())
+        && c.chars().count() <= 100
+        && d.len() <= 300
+}
+
+#[quickcheck_async::futures]
+async fn float_specials(#[floats()] x: f64, #[floats(nan, subnormal)] y: f32) -> bool {
+    x.is_finite() && !x.is_subnormal() && !y.is_infinite()
+}
+
+#[quickcheck_async::futures]
+async fn times(#[time] timeout: std::time::Duration, #[time] at: std::time::Instant) -> bool {
+    timeout <= std::time::Duration::from_secs(101 * 86_400)
+        && at.checked_add(timeout).is_some_and(|end| end >= at)
+}
+
+#[quickcheck_async::futures]
+async fn net_addrs(
+    #[net] ip: std::net::IpAddr,
+    #[net] addr: std::net::SocketAddr,
+    block: quickcheck_async::Cidr,
+) -> bool {
+    let network = block.network();
+    ip.is_ipv4() != ip.is_ipv6()
+        && addr.is_ipv4() == addr.ip().is_ipv4()
+        && block.contains(network)
+        && block.to_string().parse::<std::net::IpAddr>().is_err()
+}
+
+#[quickcheck_async::futures]
+async fn free_ports(
+    #[fixture] a: quickcheck_async::FreePort,
+    #[fixture] b: quickcheck_async::FreePort,
+) -> bool {
+    let listener = std::net::TcpListener::bind(a.addr()).unwrap();
+    a.port() != b.port() && listener.local_addr().unwrap() == a.addr()
+}
+
+fn sorted(mut v: Vec<u8>) -> Vec<u8> {
+    v.sort_unstable();
+    v
+}
+
+#[quickcheck_async::futures]
+async fn mapped(
+    #[map(sorted)] v: Vec<u8>,
+    #[map(|s: String| s.to_lowercase())] s: String,
+    #[map(|x: u8| u32::from(x) * 2)] even: u32,
+) -> bool {
+    v.windows(2).all(|w| w[0] <= w[1]) && s == s.to_lowercase() && even.is_multiple_of(2)
+}
+
+fn square(n: u8, g: &mut quickcheck_async::Generator) -> Vec<Vec<u8>> {
+    let n = usize::from(n % 8);
+    (0..n)
+        .map(|_| {
+            (0..n)
+                .map(|_| quickcheck::Arbitrary::arbitrary(g))
+                .collect()
+        })
+        .collect()
+}
+
+#[quickcheck_async::futures]
+async fn flat_mapped(#[flat_map(square)] m: Vec<Vec<u8>>) -> bool {
+    m.iter().all(|row| row.len() == m.len())
+}
+
+#[quickcheck_async::futures(gen_size = 1000)]
+async fn sized(
+    #[size(4)] small: Vec<u8>,
+    #[size(8)]
+    #[range(0..100)]
+    ranged: u32,
+    #[size(2)]
+    #[shrink(Vec::<u8>::shrink)]
+    shrunk: Vec<u8>,
+) -> bool {
+    small.len() <= 4 && small.iter().all(|&x| x < 4) && ranged < 100 && shrunk.len() <= 2
+}
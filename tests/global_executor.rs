@@ -0,0 +1,20 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::global_executor]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::global_executor]
+async fn unit_test() {}
+
+#[quickcheck_async::global_executor]
+async fn spawn_test(x: u8) -> bool {
+    async_global_executor::spawn(async move { x }).await == x
+}
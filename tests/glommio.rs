@@ -0,0 +1,30 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::rc::Rc;
+
+#[quickcheck_async::glommio]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::glommio]
+async fn unit_test() {}
+
+#[quickcheck_async::glommio]
+async fn local_test(x: u8) -> bool {
+    let rc = Rc::new(x);
+    glommio::yield_if_needed().await;
+    *rc == x
+}
+
+#[quickcheck_async::glommio(isolate_cases)]
+async fn isolate_cases(x: u8) -> bool {
+    glommio::spawn_local(futures::future::pending::<()>()).detach();
+    glommio::spawn_local(async move { x }).await == x
+}
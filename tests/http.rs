@@ -0,0 +1,33 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "http")]
+#![warn(rust_2018_idioms)]
+
+use http::Uri;
+
+type Headers = http::HeaderMap;
+
+#[quickcheck_async::futures]
+async fn uris(uri: http::Uri) -> bool {
+    uri.to_string()
+        .parse::<Uri>()
+        .is_ok_and(|parsed| parsed == uri)
+        && uri.path().starts_with('/')
+}
+
+#[quickcheck_async::futures]
+async fn headers(map: http::HeaderMap, #[foreign] aliased: Headers) -> bool {
+    map.iter()
+        .chain(&aliased)
+        .all(|(_, value)| value.to_str().is_ok())
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = ".example.com/,)")]
+async fn uri_shrinks(#[foreign] uri: Uri) -> bool {
+    uri.authority().is_none()
+}
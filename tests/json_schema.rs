@@ -0,0 +1,59 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "json-schema")]
+#![warn(rust_2018_idioms)]
+
+use serde_json::{json, Value};
+
+fn order() -> Value {
+    json!({
+        "type": "object",
+        "required": ["id", "items"],
+        "properties": {
+            "id": { "type": "string", "format": "uuid" },
+            "items": {
+                "type": "array",
+                "minItems": 1,
+                "maxItems": 5,
+                "items": { "$ref": "#/$defs/item" }
+            },
+            "note": { "type": ["string", "null"], "maxLength": 10 },
+            "status": { "enum": ["new", "paid", "shipped"] }
+        },
+        "$defs": {
+            "item": {
+                "type": "object",
+                "required": ["sku", "qty"],
+                "properties": {
+                    "sku": { "type": "string", "minLength": 3, "maxLength": 8 },
+                    "qty": { "type": "integer", "minimum": 1, "maximum": 99 }
+                }
+            }
+        }
+    })
+}
+
+#[quickcheck_async::futures]
+async fn orders(#[schema(order())] order: Value) -> bool {
+    let items = order["items"].as_array().unwrap();
+    order["id"].as_str().unwrap().len() == 36
+        && (1..=5).contains(&items.len())
+        && items.iter().all(|item| {
+            let (sku, qty) = (item["sku"].as_str().unwrap(), item["qty"].as_i64().unwrap());
+            (3..=8).contains(&sku.len()) && (1..=99).contains(&qty)
+        })
+        && order
+            .get("note")
+            .is_none_or(|note| note.as_str().is_none_or(|n| n.len() <= 10))
+}
+
+// the optional properties are dropped, and each item is as small as it can be
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "\"), \"items\": Array [Object {\"qty\": Number(1), \"sku\": String(\"")]
+async fn shrinks_to_required(#[schema(order())] order: Value) -> bool {
+    order["items"].as_array().unwrap().len() < 2
+}
@@ -0,0 +1,40 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::{rc::Rc, time::Duration};
+
+#[quickcheck_async::monoio]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::monoio]
+async fn unit_test() {}
+
+#[quickcheck_async::monoio(entries = 64)]
+async fn entries(x: u8) -> bool {
+    let rc = Rc::new(x);
+    *rc == x
+}
+
+#[quickcheck_async::monoio(enable_timer)]
+async fn timer_flag(ms: u8) {
+    monoio::time::sleep(Duration::from_micros(ms as u64)).await;
+}
+
+#[quickcheck_async::monoio(timer_enabled = true, driver = "legacy")]
+async fn all_args(ms: u8) {
+    monoio::time::sleep(Duration::from_micros(ms as u64)).await;
+}
+
+#[quickcheck_async::monoio(isolate_cases, enable_timer)]
+async fn isolate_cases(ms: u8) -> bool {
+    monoio::spawn(futures::future::pending::<()>());
+    monoio::time::sleep(Duration::from_micros(ms as u64)).await;
+    true
+}
@@ -0,0 +1,97 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use quickcheck::{Arbitrary, Gen};
+use quickcheck_async::Config;
+
+fn below_ten(x: u8) -> bool {
+    assert!(x < 10, "boom");
+    true
+}
+
+#[test]
+#[should_panic(expected = "Arguments: (10,)\nError: boom\n  at tests/panic.rs:13:5")]
+fn panic_location() {
+    Config::new().quickcheck(below_ten as fn(u8) -> bool);
+}
+
+#[test]
+#[should_panic(expected = "Arguments: (10,)\nError: boom")]
+fn parallel_shrink() {
+    Config::new()
+        .shrink_parallelism(4)
+        .quickcheck(below_ten as fn(u8) -> bool);
+}
+
+#[test]
+#[should_panic(expected = "Arguments: (10,)\nError: boom")]
+fn resume_shrink() {
+    let path = std::env::temp_dir().join(format!("resume_shrink-{}", std::process::id()));
+    let stopped = std::panic::catch_unwind(|| {
+        Config::new()
+            .max_shrink_iters(1)
+            .shrink_checkpoint(&path)
+            .quickcheck(below_ten as fn(u8) -> bool)
+    });
+    assert!(stopped.is_err());
+
+    // no cases are generated, so the counterexample can only be the checkpointed one
+    Config::new()
+        .tests(0)
+        .shrink_checkpoint(&path)
+        .resume_shrink(true)
+        .quickcheck(below_ten as fn(u8) -> bool);
+}
+
+/// A list with an index into it, which shrinks as if they were unrelated.
+#[derive(Clone, Debug)]
+struct Indexed {
+    v: Vec<u8>,
+    i: usize,
+}
+
+impl Arbitrary for Indexed {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let mut v = Vec::<u8>::arbitrary(g);
+        v.push(u8::arbitrary(g));
+        let i = usize::arbitrary(g) % v.len();
+        Indexed { v, i }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let i = self.i;
+        Box::new(self.v.shrink().map(move |v| Indexed { v, i }))
+    }
+}
+
+/// Delete each element other than the indexed one, then shrink that one.
+fn keep_index(x: &Indexed) -> Vec<Indexed> {
+    let deleted = (0..x.v.len()).filter(|&j| j != x.i).map(|j| {
+        let mut v = x.v.clone();
+        v.remove(j);
+        let i = if j < x.i { x.i - 1 } else { x.i };
+        Indexed { v, i }
+    });
+    let shrunk = x.v[x.i].shrink().map(|y| {
+        let mut v = x.v.clone();
+        v[x.i] = y;
+        Indexed { v, i: x.i }
+    });
+    deleted.chain(shrunk).collect()
+}
+
+fn indexed_zero(x: Indexed) -> bool {
+    x.v.get(x.i).is_none_or(|&y| y == 0)
+}
+
+#[test]
+#[should_panic(expected = "Arguments: (Indexed { v: [1], i: 0 },)")]
+fn registered_shrinker() {
+    quickcheck_async::register_shrinker::<Indexed>(keep_index);
+    Config::new().quickcheck(indexed_zero as fn(Indexed) -> bool);
+}
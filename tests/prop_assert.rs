@@ -0,0 +1,23 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use quickcheck_async::{prop_assert, PropContext, PropError};
+
+async fn below_ten(x: u8) -> Result<(), PropError> {
+    prop_assert!(x < 10);
+    Ok(())
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "Arguments: (10,)\nError: assertion failed: x < 10\n  \
+                           at tests/prop_assert.rs:12:5\n  while checking 10")]
+async fn prop_assert_context(x: u8) -> Result<(), PropError> {
+    below_ten(x)
+        .await
+        .with_context(|| format!("checking {}", x))
+}
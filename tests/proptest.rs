@@ -0,0 +1,39 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "proptest")]
+#![warn(rust_2018_idioms)]
+
+use proptest::{collection::vec, prelude::*};
+
+fn evens() -> impl Strategy<Value = u32> {
+    (0..1000u32).prop_map(|x| x * 2)
+}
+
+#[quickcheck_async::futures]
+async fn strategies(#[strategy(0..100u32)] x: u32, #[strategy(evens())] y: u32, z: u8) -> bool {
+    x < 100 && y.is_multiple_of(2) && z.to_string().parse() == Ok(z)
+}
+
+#[quickcheck_async::tokio]
+async fn strategy_collection(#[strategy(vec(1..5u8, 2..4))] v: Vec<u8>) -> bool {
+    (2..4).contains(&v.len()) && v.iter().all(|x| (1..5).contains(x))
+}
+
+// a type without an Arbitrary impl
+#[derive(Clone, Debug)]
+struct Port(u16);
+
+#[quickcheck_async::futures]
+async fn strategy_foreign_type(#[strategy((1024..u16::MAX).prop_map(Port))] port: Port) -> bool {
+    port.0 >= 1024
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "TEST FAILED. Arguments: (500,)")]
+async fn strategy_shrinks(#[strategy(300..1000u32)] x: u32) -> bool {
+    x < 500
+}
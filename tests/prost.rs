@@ -0,0 +1,150 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "prost")]
+#![warn(rust_2018_idioms)]
+
+use prost_reflect::{
+    prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, OneofDescriptorProto,
+    },
+    DescriptorPool, MessageDescriptor, ReflectMessage,
+};
+use std::sync::OnceLock;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Order {
+    #[prost(string, tag = "1")]
+    id: String,
+    #[prost(message, repeated, tag = "2")]
+    items: Vec<Item>,
+    #[prost(oneof = "Payment", tags = "3, 4")]
+    payment: Option<Payment>,
+    #[prost(enumeration = "Status", tag = "5")]
+    status: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Item {
+    #[prost(string, tag = "1")]
+    sku: String,
+    #[prost(uint32, tag = "2")]
+    qty: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum Payment {
+    #[prost(string, tag = "3")]
+    Card(String),
+    #[prost(uint64, tag = "4")]
+    Account(u64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+enum Status {
+    New = 0,
+    Paid = 1,
+    Shipped = 2,
+}
+
+fn field(name: &str, number: i32, ty: Type) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.into()),
+        number: Some(number),
+        label: Some(Label::Optional as i32),
+        r#type: Some(ty as i32),
+        ..Default::default()
+    }
+}
+
+// what prost-reflect-build would embed from a .proto file
+fn pool() -> &'static DescriptorPool {
+    static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let order = DescriptorProto {
+            name: Some("Order".into()),
+            field: vec![
+                field("id", 1, Type::String),
+                FieldDescriptorProto {
+                    label: Some(Label::Repeated as i32),
+                    type_name: Some(".shop.Item".into()),
+                    ..field("items", 2, Type::Message)
+                },
+                FieldDescriptorProto {
+                    oneof_index: Some(0),
+                    ..field("card", 3, Type::String)
+                },
+                FieldDescriptorProto {
+                    oneof_index: Some(0),
+                    ..field("account", 4, Type::Uint64)
+                },
+                FieldDescriptorProto {
+                    type_name: Some(".shop.Status".into()),
+                    ..field("status", 5, Type::Enum)
+                },
+            ],
+            oneof_decl: vec![OneofDescriptorProto {
+                name: Some("payment".into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let item = DescriptorProto {
+            name: Some("Item".into()),
+            field: vec![field("sku", 1, Type::String), field("qty", 2, Type::Uint32)],
+            ..Default::default()
+        };
+        let status = EnumDescriptorProto {
+            name: Some("Status".into()),
+            value: ["NEW", "PAID", "SHIPPED"]
+                .iter()
+                .zip(0..)
+                .map(|(name, number)| EnumValueDescriptorProto {
+                    name: Some(name.to_string()),
+                    number: Some(number),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("shop.proto".into()),
+            package: Some("shop".into()),
+            message_type: vec![order, item],
+            enum_type: vec![status],
+            syntax: Some("proto3".into()),
+            ..Default::default()
+        };
+
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(file).unwrap();
+        pool
+    })
+}
+
+impl ReflectMessage for Order {
+    fn descriptor(&self) -> MessageDescriptor {
+        pool().get_message_by_name("shop.Order").unwrap()
+    }
+}
+
+#[quickcheck_async::futures]
+async fn orders(#[message] order: Order) -> bool {
+    (0..3).contains(&order.status) && order.items.len() <= 50
+}
+
+// every field but one item is cleared, and that item is as simple as it can be
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(
+    expected = "TEST FAILED. Arguments: (Order { id: \"\", items: [Item { sku: \"\", \
+                qty: 0 }], payment: None, status: New },)"
+)]
+async fn shrinks_to_one_item(#[message] order: Order) -> bool {
+    order.items.is_empty()
+}
@@ -0,0 +1,34 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "regex")]
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::futures]
+async fn emails(#[matches("[a-z]{1,8}@example\\.com")] email: String) -> bool {
+    let (user, domain) = email.split_once('@').unwrap();
+    (1..=8).contains(&user.len())
+        && user.bytes().all(|b| b.is_ascii_lowercase())
+        && domain == "example.com"
+}
+
+#[quickcheck_async::tokio]
+async fn alternation(#[matches("(GET|POST) /[0-9]*")] line: String, x: u8) -> bool {
+    (line.starts_with("GET /") || line.starts_with("POST /"))
+        && line
+            .split('/')
+            .nth(1)
+            .unwrap()
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+        && x.to_string().parse() == Ok(x)
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "TEST FAILED. Arguments: (\"k\",)")]
+async fn regex_shrinks(#[matches("[a-z]+")] s: String) -> bool {
+    !s.contains('k')
+}
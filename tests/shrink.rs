@@ -0,0 +1,191 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+static SHRUNK: AtomicBool = AtomicBool::new(false);
+
+// any case after the first failure would be a shrink candidate
+#[quickcheck_async::futures(shrink = false)]
+#[should_panic(expected = "TEST FAILED. Arguments: ")]
+async fn no_shrink(x: u32) -> bool {
+    assert!(!SHRUNK.swap(true, Ordering::SeqCst), "shrunk");
+    x == u32::MAX
+}
+
+static SHRINK_CALLS: AtomicU64 = AtomicU64::new(0);
+
+#[quickcheck_async::futures(max_shrink_iters = 3)]
+#[should_panic(expected = "TEST FAILED. Arguments: ")]
+async fn max_shrink_iters(x: u32) -> bool {
+    assert!(
+        SHRINK_CALLS.fetch_add(1, Ordering::SeqCst) <= 3,
+        "too many shrinks"
+    );
+    x == u32::MAX
+}
+
+static TIMED_OUT: AtomicBool = AtomicBool::new(false);
+
+// with no time to shrink, the generated case is reported as is
+#[quickcheck_async::futures(shrink_timeout = "0s")]
+#[should_panic(expected = "TEST FAILED. Arguments: ")]
+async fn shrink_timeout(x: u32) -> bool {
+    assert!(!TIMED_OUT.swap(true, Ordering::SeqCst), "shrunk");
+    x == u32::MAX
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (500,)")]
+async fn range_shrinks(#[range(300..1000)] x: u32) -> bool {
+    x < 500
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: ([0, 0, 0],)")]
+async fn len_shrinks(#[len(3..10)] v: Vec<u8>) -> bool {
+    v.len() < 3
+}
+
+// the default shrinker would find 50
+fn to_max(x: &u8) -> Vec<u8> {
+    if *x < u8::MAX {
+        vec![u8::MAX]
+    } else {
+        vec![]
+    }
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (255,)")]
+async fn custom_shrink(#[shrink(to_max)] x: u8) -> bool {
+    x < 50
+}
+
+static FAILED_SEED: AtomicU64 = AtomicU64::new(u64::MAX);
+
+// x shrinks to 10, while the seed it first failed with is kept
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (10, ")]
+async fn no_shrink_arg(x: u8, #[no_shrink] seed: u64) -> bool {
+    if x >= 10 {
+        let first =
+            FAILED_SEED.compare_exchange(u64::MAX, seed, Ordering::SeqCst, Ordering::SeqCst);
+        assert!(first.is_ok() || first == Err(seed), "seed shrunk");
+    }
+    x < 10
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (\"A\",)")]
+async fn alphabet_shrinks(#[alphabet(alphanumeric)] s: String) -> bool {
+    s.bytes().all(|b| !b.is_ascii_uppercase())
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (NaN,)")]
+async fn floats_nan(#[floats(nan)] x: f64) -> bool {
+    x == x
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (127.0.0.0,)")]
+async fn net_shrinks(#[net] ip: std::net::Ipv4Addr) -> bool {
+    !ip.is_loopback()
+}
+
+fn sorted(mut v: Vec<u8>) -> Vec<u8> {
+    v.sort_unstable();
+    v
+}
+
+// each candidate the raw value shrinks to is sorted again
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: ([0, 1],)")]
+async fn map_shrinks(#[map(sorted)] v: Vec<u8>) -> bool {
+    v.first() >= v.last()
+}
+
+fn square(n: u8, g: &mut quickcheck_async::Generator) -> Vec<Vec<u8>> {
+    let n = usize::from(n % 8);
+    (0..n)
+        .map(|_| {
+            (0..n)
+                .map(|_| quickcheck::Arbitrary::arbitrary(g))
+                .collect()
+        })
+        .collect()
+}
+
+// the length shrinks first, then the matrix is made again by smaller generators
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: ([[0, 0], [0, 0]],)")]
+async fn flat_map_shrinks(#[flat_map(square)] m: Vec<Vec<u8>>) -> bool {
+    m.len() < 2
+}
+
+#[quickcheck_async::futures(shrink_progress = true)]
+#[should_panic(expected = "TEST FAILED. Arguments: (10, [])")]
+async fn shrink_progress(x: u8, _log: Vec<String>) -> bool {
+    x < 10
+}
+
+/// A list with an index into it, which shrinking by value would leave out of bounds.
+#[derive(Clone, Debug)]
+struct Indexed {
+    v: Vec<u8>,
+    i: usize,
+}
+
+impl quickcheck::Arbitrary for Indexed {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        let mut v = Vec::<u8>::arbitrary(g);
+        v.push(u8::arbitrary(g));
+        let i = usize::arbitrary(g) % v.len();
+        Indexed { v, i }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let i = self.i;
+        Box::new(self.v.shrink().map(move |v| Indexed { v, i }))
+    }
+}
+
+#[quickcheck_async::futures(shrink_by = "choices")]
+#[should_panic(expected = "TEST FAILED. Arguments: (Indexed { v: [1], i: 0 },)")]
+async fn shrink_by_choices(x: Indexed) -> bool {
+    x.v[x.i] == 0
+}
+
+#[quickcheck_async::futures(tests = 10000)]
+#[should_panic(expected = "TEST FAILED. Arguments: ([1], 0)")]
+async fn shrink_together(v: Vec<u8>, i: usize) -> bool {
+    v.len() != i + 1 || v[i] == 0
+}
+
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (10, 1ms)")]
+async fn shrink_toward(
+    #[shrink_toward(10)] x: u32,
+    #[shrink_toward(std::time::Duration::from_millis(1))] d: std::time::Duration,
+) -> bool {
+    x <= 5 || d < std::time::Duration::from_millis(1)
+}
+
+#[derive(Clone, Debug)]
+enum Packet {
+    Normal(u8),
+    Edge,
+}
+
+// shrinking the field keeps the variant, which needs no Arbitrary impl of its own
+#[quickcheck_async::futures]
+#[should_panic(expected = "TEST FAILED. Arguments: (Normal(10),)")]
+async fn weighted_shrinks(#[weights(Normal(u8) = 1, Edge = 1)] p: Packet) -> bool {
+    !matches!(p, Packet::Normal(x) if x >= 10)
+}
@@ -0,0 +1,22 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::smol]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::smol]
+async fn unit_test() {}
+
+#[quickcheck_async::smol]
+async fn timer_test(ms: u8) -> bool {
+    let dur = std::time::Duration::from_micros(ms as u64);
+    smol::Timer::after(dur).await;
+    true
+}
@@ -0,0 +1,30 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "stream")]
+#![warn(rust_2018_idioms)]
+
+use futures::{Stream, StreamExt};
+use quickcheck_async::ArbStream;
+
+#[quickcheck_async::futures]
+async fn streams(s: impl Stream<Item = u8> + Send) -> bool {
+    let (low, high) = s.size_hint();
+    let items: Vec<u8> = s.collect().await;
+    low == items.len() && high == Some(items.len())
+}
+
+#[quickcheck_async::futures]
+async fn paced(s: ArbStream<u8>) -> bool {
+    let items: Vec<u8> = s.clone().collect().await;
+    s.ready_chunks(4).concat().await == items
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "TEST FAILED. Arguments: ([0, 0],)")]
+async fn stream_shrinks(s: impl Stream<Item = u8>) -> bool {
+    s.count().await < 2
+}
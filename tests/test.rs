@@ -0,0 +1,35 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::test(runtime = "tokio")]
+async fn tokio_test() -> bool {
+    true
+}
+
+#[quickcheck_async::test(core_threads = 3, runtime = "tokio", max_threads = 5)]
+async fn tokio_args() {}
+
+#[quickcheck_async::test(runtime = "async_std")]
+async fn async_std_test(x: u8) -> bool {
+    async_std::task::yield_now().await;
+    x.to_string().parse() == Ok(x)
+}
+
+#[quickcheck_async::test(runtime = "smol")]
+async fn smol_test() {}
+
+#[quickcheck_async::test(runtime = "monoio", enable_timer, entries = 64)]
+async fn monoio_args() {}
+
+#[quickcheck_async::test(runtime = "wasm", unsupported = tokio::test)]
+async fn wasm_args() {}
+
+#[quickcheck_async::test(runtime = "smol", gen_size = 4)]
+async fn gen_size(v: Vec<u8>) -> bool {
+    v.len() <= 4
+}
@@ -0,0 +1,23 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "time")]
+#![warn(rust_2018_idioms)]
+
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+
+#[quickcheck_async::futures]
+async fn dates(
+    #[time] at: OffsetDateTime,
+    #[time] local: PrimitiveDateTime,
+    #[time] day: Date,
+    #[time] d: Duration,
+) -> bool {
+    (1890..2110).contains(&at.year())
+        && (1890..2110).contains(&local.year())
+        && (1890..2110).contains(&day.year())
+        && d.abs() <= Duration::days(101)
+}
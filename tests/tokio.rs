@@ -6,6 +6,14 @@
 // copied, modified, or distributed except according to those terms.
 #![warn(rust_2018_idioms)]
 
+use std::{
+    net::SocketAddr,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use tokio::runtime;
+
 #[quickcheck_async::tokio]
 async fn bool_test() -> bool {
     true
@@ -19,3 +27,212 @@ async fn core_threads() {}
 
 #[quickcheck_async::tokio(core_threads = 3, max_threads = 5)]
 async fn all_args() {}
+
+#[quickcheck_async::tokio(core_threads = 2, gen_size = 4)]
+async fn gen_size(v: Vec<u8>) -> bool {
+    v.len() <= 4
+}
+
+#[quickcheck_async::tokio(flavor = "multi_thread", worker_threads = 2)]
+async fn modern_args() {}
+
+#[quickcheck_async::tokio(flavor = "current_thread")]
+async fn current_thread() {}
+
+fn make_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .core_threads(2)
+        .thread_name("fuzzer")
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[quickcheck_async::tokio(builder = "make_runtime")]
+async fn builder() -> bool {
+    std::thread::current().name() == Some("fuzzer")
+}
+
+static STARTED: AtomicUsize = AtomicUsize::new(0);
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[quickcheck_async::tokio(isolate_cases)]
+async fn isolate_cases(x: u8) -> bool {
+    // every task spawned by a previous case must have been dropped along with its runtime
+    let clean = STARTED.load(Ordering::SeqCst) == DROPPED.load(Ordering::SeqCst);
+
+    STARTED.fetch_add(1, Ordering::SeqCst);
+    tokio::spawn(async move {
+        let _guard = Guard;
+        futures::future::pending::<()>().await
+    });
+
+    clean && tokio::spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(isolate_cases, builder = "make_runtime")]
+async fn isolate_cases_builder() -> bool {
+    tokio::spawn(async { std::thread::current().name() == Some("fuzzer") })
+        .await
+        .unwrap()
+}
+
+#[quickcheck_async::tokio(local)]
+async fn local(x: u8) -> bool {
+    let rc = Rc::new(x);
+    tokio::task::spawn_local(async move { *rc }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(local, isolate_cases, builder = "make_runtime")]
+async fn local_isolate_cases(x: u8) -> bool {
+    let rc = Rc::new(x);
+    tokio::task::spawn_local(async move { *rc }).await.unwrap() == x
+}
+
+static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_runtime() -> tokio::runtime::Runtime {
+    BUILT.fetch_add(1, Ordering::SeqCst);
+    make_runtime()
+}
+
+#[quickcheck_async::tokio(shared, builder = "counted_runtime")]
+async fn shared(x: u8) -> bool {
+    BUILT.load(Ordering::SeqCst) == 1 && tokio::spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(shared, builder = "counted_runtime")]
+async fn shared_again() -> bool {
+    BUILT.load(Ordering::SeqCst) == 1
+}
+
+#[quickcheck_async::tokio(shared, local)]
+async fn shared_local(x: u8) -> bool {
+    let rc = Rc::new(x);
+    tokio::task::spawn_local(async move { *rc }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(start_paused = true)]
+async fn start_paused(secs: u16) -> bool {
+    let start = tokio::time::Instant::now();
+    tokio::time::delay_for(Duration::from_secs(secs as u64)).await;
+    start.elapsed() >= Duration::from_secs(secs as u64)
+}
+
+#[quickcheck_async::tokio(start_paused = true, local, builder = "make_runtime")]
+async fn start_paused_local(secs: u16) {
+    let rc = Rc::new(secs as u64);
+    tokio::task::spawn_local(async move {
+        tokio::time::delay_for(Duration::from_secs(*rc)).await;
+    })
+    .await
+    .unwrap()
+}
+
+#[quickcheck_async::tokio]
+async fn handle(x: u8, handle: tokio::runtime::Handle) -> bool {
+    handle.spawn(async move { x }).await.unwrap() == x
+}
+
+/// A type of the test's own, which shares its name with tokio's handle.
+#[derive(Clone, Debug)]
+struct Handle(u8);
+
+impl quickcheck::Arbitrary for Handle {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        Handle(u8::arbitrary(g))
+    }
+}
+
+// only tokio's own handle is passed in, so this one is generated
+#[quickcheck_async::tokio]
+#[quickcheck_async::examples(Handle(7))]
+async fn other_handle(h: Handle, rt: runtime::Handle) -> bool {
+    let Handle(x) = h;
+    rt.spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(isolate_cases, builder = "make_runtime")]
+async fn handle_isolate_cases(handle: tokio::runtime::Handle) -> bool {
+    handle
+        .spawn(async { std::thread::current().name() == Some("fuzzer") })
+        .await
+        .unwrap()
+}
+
+#[quickcheck_async::tokio]
+async fn runtime(x: u8) -> bool {
+    tokio::time::delay_for(Duration::from_micros(x as u64)).await;
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let mut listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (conn, accept) = futures::join!(tokio::net::TcpStream::connect(addr), listener.accept());
+    conn.is_ok() && accept.is_ok() && tokio::spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio(core_threads = 2)]
+async fn runtime_threaded(x: u8) -> bool {
+    tokio::time::delay_for(Duration::from_micros(x as u64)).await;
+    tokio::spawn(async move { x }).await.unwrap() == x
+}
+
+#[quickcheck_async::tokio_mod(flavor = "multi_thread", worker_threads = 2)]
+mod tokio_mod {
+    use quickcheck::TestResult;
+    use std::time::Duration;
+
+    async fn double(x: u8) -> u16 {
+        tokio::time::delay_for(Duration::from_micros(1)).await;
+        x as u16 * 2
+    }
+
+    async fn doubled(x: u8) -> bool {
+        double(x).await == x as u16 + x as u16
+    }
+
+    async fn discards(x: u8) -> TestResult {
+        if x % 2 == 1 {
+            return TestResult::discard();
+        }
+        TestResult::from_bool(double(x).await % 4 == 0)
+    }
+
+    #[quickcheck_async::tokio(isolate_cases)]
+    async fn annotated() -> bool {
+        true
+    }
+}
+
+// the candidates are tried on threads outside the runtime's context
+#[quickcheck_async::tokio(shrink_parallelism = 4)]
+#[should_panic(expected = "TEST FAILED. Arguments: (10,)")]
+async fn parallel_shrink(x: u8) -> bool {
+    tokio::time::delay_for(Duration::from_micros(1)).await;
+    x < 10
+}
+
+// were shrink candidates run off the runtime, the timer would panic in each of them, so
+// this would shrink to 0
+#[quickcheck_async::tokio]
+#[should_panic(expected = "TEST FAILED. Arguments: (10,)")]
+async fn shrinks_on_runtime(x: u8) -> bool {
+    tokio::time::delay_for(Duration::from_micros(1)).await;
+    x < 10
+}
+
+// candidates are awaited together, yet the first to fail in order is taken
+#[quickcheck_async::tokio(shrink_parallelism = 4)]
+#[should_panic(expected = "TEST FAILED. Arguments: (10,)")]
+async fn parallel_shrink_order(x: u8) -> bool {
+    tokio::time::delay_for(Duration::from_micros(u64::from(100 - x % 100))).await;
+    x < 10
+}
@@ -0,0 +1,26 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use std::rc::Rc;
+
+#[quickcheck_async::tokio_uring]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::tokio_uring]
+async fn unit_test() {}
+
+#[quickcheck_async::tokio_uring]
+async fn uring_test(x: u8) -> bool {
+    let rc = Rc::new(x);
+    let file = tokio_uring::fs::File::open("Cargo.toml").await.unwrap();
+    let (res, _) = file.read_at(vec![0; 8], 0).await;
+    file.close().await.unwrap();
+    res.unwrap() == 8 && *rc == x
+}
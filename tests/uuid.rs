@@ -0,0 +1,35 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(feature = "uuid")]
+#![warn(rust_2018_idioms)]
+
+#[quickcheck_async::futures]
+async fn round_trips(id: uuid::Uuid) -> bool {
+    id.to_string().parse::<uuid::Uuid>() == Ok(id)
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "TEST FAILED. Arguments: (00000000-0000-0000-0000-000000000001,)")]
+async fn uuid_shrinks(id: uuid::Uuid) -> bool {
+    id.is_nil()
+}
+
+/// A type of the test's own, which shares its name with uuid's.
+#[derive(Clone, Debug)]
+struct Uuid(u8);
+
+impl quickcheck::Arbitrary for Uuid {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        Uuid(u8::arbitrary(g) % 10)
+    }
+}
+
+// only uuid's own type is generated as foreign, so this one uses its Arbitrary impl
+#[quickcheck_async::futures]
+async fn local_uuid(id: Uuid) -> bool {
+    id.0 < 10
+}
@@ -0,0 +1,36 @@
+// Copyright 2020 nytopop (Eric Izoita)
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![warn(rust_2018_idioms)]
+
+use quickcheck::TestResult;
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+async fn bool_test() -> bool {
+    true
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+async fn unit_test() {}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+async fn discard_test(x: u8, y: u8) -> TestResult {
+    if x > y {
+        return TestResult::discard();
+    }
+    TestResult::from_bool(x <= y)
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test)]
+#[should_panic(expected = "TEST FAILED. Arguments: (10,)")]
+async fn shrink_test(x: u8) -> bool {
+    x < 10
+}
+
+#[quickcheck_async::wasm(unsupported = tokio::test, gen_size = 4)]
+async fn gen_size(v: Vec<u8>) -> bool {
+    v.len() <= 4
+}